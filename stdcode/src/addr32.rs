@@ -0,0 +1,100 @@
+use std::convert::TryInto;
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde::{Deserializer, Serializer};
+
+/// Serializes anything that is 32 bytes long (such as a `HashVal`) as a Themelio `t...` address for human-readable formats, and exactly like a `[u8; 32]` otherwise.
+pub fn serialize<T: AsRef<[u8]>, S>(val: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let bytes: &[u8; 32] = val
+        .as_ref()
+        .try_into()
+        .map_err(|_| serde::ser::Error::custom("value is not 32 bytes long"))?;
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&encode(bytes))
+    } else {
+        bytes.serialize(serializer)
+    }
+}
+
+/// Deserializes a Themelio address, rejecting it if the checksum digit is wrong.
+pub fn deserialize<'de, T: From<[u8; 32]>, D>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        let s = String::deserialize(deserializer)?;
+        decode(&s)
+            .map(|bytes| bytes.into())
+            .ok_or_else(|| serde::de::Error::custom("invalid address"))
+    } else {
+        Ok(<[u8; 32]>::deserialize(deserializer)?.into())
+    }
+}
+
+fn checksum(bytes: &[u8; 32]) -> u8 {
+    // same as tmelcrypt::hash_keyed(b"address-checksum", bytes)
+    let key = blake3::hash(b"address-checksum");
+    blake3::keyed_hash(key.as_bytes(), bytes).as_bytes()[0] % 10
+}
+
+fn encode(bytes: &[u8; 32]) -> String {
+    let raw_base32 = base32::encode(base32::Alphabet::Crockford {}, bytes);
+    format!("T{}{}", checksum(bytes), raw_base32).to_ascii_lowercase()
+}
+
+fn decode(addr: &str) -> Option<[u8; 32]> {
+    let addr = addr.replace('-', "").to_ascii_lowercase();
+    let mut chars = addr.chars();
+    if chars.next()? != 't' {
+        return None;
+    }
+    let digit = chars.next()?.to_digit(10)?;
+    let bytes: [u8; 32] = base32::decode(base32::Alphabet::Crockford {}, chars.as_str())?
+        .try_into()
+        .ok()?;
+    if u32::from(checksum(&bytes)) != digit {
+        return None;
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+    struct Test {
+        #[serde(with = "crate::addr32")]
+        covhash: [u8; 32],
+    }
+
+    #[test]
+    fn addr32_roundtrip() {
+        let t = Test { covhash: [7; 32] };
+        let json = serde_json::to_string(&t).unwrap();
+        assert_eq!(serde_json::from_str::<Test>(&json).unwrap(), t);
+        assert_eq!(
+            crate::deserialize::<Test>(&crate::serialize(&t).unwrap()).unwrap(),
+            t
+        );
+        // corrupt the checksum digit
+        let addr = super::encode(&t.covhash);
+        let digit = addr.as_bytes()[1] - b'0';
+        let bad = format!("t{}{}", (digit + 1) % 10, &addr[2..]);
+        assert!(super::decode(&addr).is_some());
+        assert!(super::decode(&bad).is_none());
+    }
+
+    #[test]
+    fn addr32_matches_tmelcrypt() {
+        // tmelcrypt::HashVal([7; 32]).to_addr()
+        assert_eq!(
+            super::encode(&[7; 32]),
+            "t80w3ge1r70w3ge1r70w3ge1r70w3ge1r70w3ge1r70w3ge1r70w3g"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::io::{BufWriter, Read, Write};
+
+use bincode::Options;
+use futures_lite::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use serde::{de::DeserializeOwned, Serialize};
+
+const CHUNK_SIZE: usize = 65536;
+
+/// Serialize the stuff into an [AsyncWrite], streaming the encoding rather than building it all in memory first. The output is identical to [crate::serialize].
+///
+/// Encoding runs on a background thread, which sends chunks of the output to be written as they're produced. Since that thread outlives the borrow of any local, the value is taken by value.
+pub async fn serialize_async<T: Serialize + Send + 'static>(
+    mut writer: impl AsyncWrite + Unpin,
+    v: T,
+) -> bincode::Result<()> {
+    let (send_chunk, recv_chunk) = async_channel::bounded::<Vec<u8>>(4);
+    let encoder = blocking::unblock(move || {
+        let mut output = BufWriter::with_capacity(CHUNK_SIZE, ChannelWriter { send_chunk });
+        bincode::DefaultOptions::new()
+            .with_varint_encoding()
+            .reject_trailing_bytes()
+            .serialize_into(&mut output, &v)?;
+        output.flush()?;
+        bincode::Result::Ok(())
+    });
+    // the encoder hangs up once it's done, whether it succeeded or not
+    while let Ok(chunk) = recv_chunk.recv().await {
+        writer.write_all(&chunk).await?;
+    }
+    encoder.await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Safe deserialize out of an [AsyncRead], streaming the input rather than reading it all into memory first.
+///
+/// Like [crate::deserialize], the value must take up the whole input, so this reads until EOF (use [AsyncReadExt::take] to deserialize from part of a stream). `limit` plays the role that the length of the input plays for [crate::deserialize]: reading or allocating more than `limit` bytes in total is an error, rather than a DoS.
+///
+/// Decoding runs on a background thread, which is fed with chunks of the input as they're read.
+pub async fn deserialize_async<T: DeserializeOwned + Send + 'static>(
+    mut reader: impl AsyncRead + Unpin,
+    limit: u64,
+) -> bincode::Result<T> {
+    let (send_chunk, recv_chunk) = async_channel::bounded::<Vec<u8>>(4);
+    let decoder = blocking::unblock(move || {
+        let mut input = ChannelReader {
+            recv_chunk,
+            chunk: vec![],
+            pos: 0,
+        };
+        let val: T = bincode::DefaultOptions::new()
+            .with_varint_encoding()
+            .reject_trailing_bytes()
+            .with_limit(limit)
+            .deserialize_from(&mut input)?;
+        if input.read(&mut [0u8])? > 0 {
+            return Err(Box::new(bincode::ErrorKind::Custom(
+                "Reader had bytes remaining after deserialization".into(),
+            )));
+        }
+        Ok(val)
+    });
+    let mut buf = vec![0u8; CHUNK_SIZE];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        // the decoder hangs up once it's done, whether it succeeded or not
+        if n == 0 || send_chunk.send(buf[..n].to_vec()).await.is_err() {
+            break;
+        }
+    }
+    drop(send_chunk);
+    decoder.await
+}
+
+/// A blocking [Read] over chunks sent through a channel, treating a closed channel as EOF.
+struct ChannelReader {
+    recv_chunk: async_channel::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.recv_chunk.recv_blocking() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// A blocking [Write] that sends everything written to it through a channel.
+struct ChannelWriter {
+    send_chunk: async_channel::Sender<Vec<u8>>,
+}
+
+impl Write for ChannelWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.send_chunk
+            .send_blocking(buf.to_vec())
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::BrokenPipe, "writer hung up"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn async_roundtrip() {
+        futures_lite::future::block_on(async {
+            let v: (u64, Vec<String>) = (42, vec!["x".repeat(100000); 5]);
+            let mut out = vec![];
+            serialize_async(&mut out, v.clone()).await.unwrap();
+            assert_eq!(out, crate::serialize(&v).unwrap());
+            let back: (u64, Vec<String>) =
+                deserialize_async(&out[..], out.len() as u64).await.unwrap();
+            assert_eq!(back, v);
+            assert!(
+                deserialize_async::<(u64, Vec<String>)>(&out[..], out.len() as u64 - 1)
+                    .await
+                    .is_err()
+            );
+            out.push(0);
+            assert!(
+                deserialize_async::<(u64, Vec<String>)>(&out[..], out.len() as u64)
+                    .await
+                    .is_err()
+            );
+        })
+    }
+}
@@ -0,0 +1,58 @@
+//! Serializes byte vectors as unpadded base64url in human-readable formats, and as plain bytes otherwise. Use with `#[serde(with = "stdcode::b64")]`.
+
+use base64::Engine;
+use serde::Deserialize;
+use serde::{Deserializer, Serializer};
+
+/// Serializes bytes as unpadded base64url for human-readable formats, and exactly like a `Vec<u8>` otherwise.
+pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes))
+    } else {
+        serializer.serialize_bytes(bytes)
+    }
+}
+
+/// Deserializes bytes written by [serialize].
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        let s = String::deserialize(deserializer)?;
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(s)
+            .map_err(serde::de::Error::custom)
+    } else {
+        <Vec<u8>>::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+    struct Test {
+        #[serde(with = "crate::b64")]
+        data: Vec<u8>,
+    }
+
+    #[test]
+    fn b64_roundtrip() {
+        let t = Test {
+            data: vec![0xfb, 0xff, 0x00, 0x10],
+        };
+        let json = serde_json::to_string(&t).unwrap();
+        assert_eq!(json, r#"{"data":"-_8AEA"}"#);
+        assert_eq!(serde_json::from_str::<Test>(&json).unwrap(), t);
+        assert_eq!(
+            crate::deserialize::<Test>(&crate::serialize(&t).unwrap()).unwrap(),
+            t
+        );
+        assert!(serde_json::from_str::<Test>(r#"{"data":"not base64!"}"#).is_err());
+    }
+}
@@ -0,0 +1,66 @@
+use std::convert::TryInto;
+
+use base64::Engine;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::{Deserializer, Serializer};
+
+/// Serializes anything that is 32 bytes long (such as a `HashVal`) as unpadded base64url for human-readable formats, and exactly like a `[u8; 32]` otherwise.
+pub fn serialize<T: AsRef<[u8]>, S>(val: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let bytes: &[u8; 32] = val
+        .as_ref()
+        .try_into()
+        .map_err(|_| serde::ser::Error::custom("value is not 32 bytes long"))?;
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes))
+    } else {
+        bytes.serialize(serializer)
+    }
+}
+
+/// Deserializes a value written by [serialize], rejecting base64 that doesn't decode to exactly 32 bytes.
+pub fn deserialize<'de, T: From<[u8; 32]>, D>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        let s = String::deserialize(deserializer)?;
+        let bytes: [u8; 32] = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(s)
+            .map_err(serde::de::Error::custom)?
+            .try_into()
+            .map_err(|_| serde::de::Error::custom("base64 length not right"))?;
+        Ok(bytes.into())
+    } else {
+        Ok(<[u8; 32]>::deserialize(deserializer)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+    struct Test {
+        #[serde(with = "crate::b64_32")]
+        hash: [u8; 32],
+    }
+
+    #[test]
+    fn b64_32_roundtrip() {
+        let t = Test { hash: [7; 32] };
+        let json = serde_json::to_string(&t).unwrap();
+        assert_eq!(serde_json::from_str::<Test>(&json).unwrap(), t);
+        assert_eq!(
+            crate::deserialize::<Test>(&crate::serialize(&t).unwrap()).unwrap(),
+            t
+        );
+        // valid base64, but of 31 bytes
+        let short = json.replace("Bwc\"", "Bw\"");
+        assert_ne!(short, json);
+        assert!(serde_json::from_str::<Test>(&short).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+//! Renders an enum by variant name in human-readable formats, while keeping its own compact encoding (typically a numeric tag, through `serde_repr`) in binary formats:
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+//! enum Kind {
+//!     Normal,
+//!     Stake,
+//! }
+//! stdcode::enum_names!(Kind { Normal, Stake });
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Tx {
+//!     #[serde(with = "stdcode::enumname")]
+//!     kind: Kind,
+//! }
+//!
+//! assert_eq!(
+//!     serde_json::to_string(&Tx { kind: Kind::Stake }).unwrap(),
+//!     r#"{"kind":"Stake"}"#
+//! );
+//! assert_eq!(stdcode::serialize(&Tx { kind: Kind::Stake }).unwrap(), vec![1]);
+//! ```
+//!
+//! When deserializing from a human-readable format, the old encoding is accepted as well, so existing JSON keeps working.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// An enum whose variants have names, for use with [crate::enumname]. Usually implemented with [crate::enum_names].
+pub trait EnumName: Sized {
+    fn variant_name(&self) -> &'static str;
+    fn from_variant_name(name: &str) -> Option<Self>;
+}
+
+/// Implements [EnumName] for a fieldless enum, given the names of its variants.
+#[macro_export]
+macro_rules! enum_names {
+    ($ty:ident { $($variant:ident),* $(,)? }) => {
+        impl $crate::enumname::EnumName for $ty {
+            fn variant_name(&self) -> &'static str {
+                match self {
+                    $($ty::$variant => stringify!($variant),)*
+                }
+            }
+
+            fn from_variant_name(name: &str) -> Option<Self> {
+                match name {
+                    $(stringify!($variant) => Some($ty::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+pub fn serialize<T: EnumName + Serialize, S: Serializer>(
+    val: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_str(val.variant_name())
+    } else {
+        val.serialize(serializer)
+    }
+}
+
+pub fn deserialize<'de, T: EnumName + Deserialize<'de>, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    if deserializer.is_human_readable() {
+        match NameOrTee::deserialize(deserializer)? {
+            NameOrTee::Name(name) => T::from_variant_name(&name).ok_or_else(|| {
+                serde::de::Error::custom(format!("unknown variant name {:?}", name))
+            }),
+            NameOrTee::Tee(t) => Ok(t),
+        }
+    } else {
+        T::deserialize(deserializer)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NameOrTee<T> {
+    Name(String),
+    Tee(T),
+}
@@ -0,0 +1,672 @@
+use std::{convert::TryFrom, ops::Range};
+
+use bincode::ErrorKind;
+use serde::{
+    de::{
+        DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+        VariantAccess, Visitor,
+    },
+    Deserializer,
+};
+
+/// The annotated byte layout of a stdcode-encoded value, as returned by [explain].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    /// Path of this value from the root, such as `outputs[1].value`. Empty for the root.
+    pub path: String,
+    /// Byte range of this value within the input.
+    pub range: Range<usize>,
+    /// A short human-readable preview of the decoded value.
+    pub preview: String,
+    /// The values nested within this value, in encoding order.
+    pub children: Vec<Layout>,
+}
+
+impl Layout {
+    /// Visits this node and all its descendants in encoding order.
+    pub fn walk(&self, f: &mut impl FnMut(&Layout)) {
+        f(self);
+        for child in self.children.iter() {
+            child.walk(f)
+        }
+    }
+
+    /// Renders the layout as an indented "hexdump with structure", given the original bytes.
+    pub fn render(&self, bts: &[u8]) -> String {
+        let mut out = String::new();
+        self.render_inner(bts, 0, &mut out);
+        out
+    }
+
+    fn render_inner(&self, bts: &[u8], depth: usize, out: &mut String) {
+        let path = if self.path.is_empty() {
+            "<root>"
+        } else {
+            &self.path
+        };
+        let hex = if self.children.is_empty() {
+            truncate(&hex::encode(&bts[self.range.clone()]), 64)
+        } else {
+            String::new()
+        };
+        out.push_str(&format!(
+            "{:indent$}{:>5}..{:<5} {} = {} {}\n",
+            "",
+            self.range.start,
+            self.range.end,
+            path,
+            self.preview,
+            hex,
+            indent = depth * 2
+        ));
+        for child in self.children.iter() {
+            child.render_inner(bts, depth + 1, out)
+        }
+    }
+}
+
+/// Decodes a stdcode-encoded value of type `T`, returning a tree describing which bytes encode which part of the value.
+///
+/// This is much slower than [crate::deserialize], and is meant for tooling and error messages. The same rules are enforced, so this fails whenever [crate::deserialize] fails.
+pub fn explain<T: DeserializeOwned>(bts: &[u8]) -> bincode::Result<Layout> {
+    let mut explainer = Explainer {
+        input: bts,
+        pos: 0,
+        label: String::new(),
+        stack: vec![Layout {
+            path: String::new(),
+            range: 0..0,
+            preview: String::new(),
+            children: vec![],
+        }],
+        u8_children: vec![0],
+    };
+    T::deserialize(&mut explainer)?;
+    if explainer.pos != bts.len() {
+        return Err(Box::new(ErrorKind::Custom(
+            "Slice had bytes remaining after deserialization".into(),
+        )));
+    }
+    let mut root = explainer.stack.pop().unwrap();
+    Ok(root.children.pop().unwrap())
+}
+
+fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() > max {
+        format!("{}...", s.chars().take(max).collect::<String>())
+    } else {
+        s.to_string()
+    }
+}
+
+struct Explainer<'a> {
+    input: &'a [u8],
+    pos: usize,
+    // label of the next value relative to its parent, like ".field" or "[3]"
+    label: String,
+    stack: Vec<Layout>,
+    // how many direct children of each node on the stack are single u8s
+    u8_children: Vec<usize>,
+}
+
+impl<'a> Explainer<'a> {
+    fn read(&mut self, n: usize) -> bincode::Result<&'a [u8]> {
+        if self.input.len() - self.pos < n {
+            return Err(Box::new(ErrorKind::Io(
+                std::io::ErrorKind::UnexpectedEof.into(),
+            )));
+        }
+        let toret = &self.input[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(toret)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> bincode::Result<[u8; N]> {
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(self.read(N)?);
+        Ok(buf)
+    }
+
+    /// Reads a varint. Like bincode, the 16-byte form is only accepted when reading a 128-bit integer.
+    fn read_varint(&mut self, wide: bool) -> bincode::Result<u128> {
+        Ok(match self.read(1)?[0] {
+            byte @ 0..=250 => byte as u128,
+            251 => u16::from_le_bytes(self.read_array()?) as u128,
+            252 => u32::from_le_bytes(self.read_array()?) as u128,
+            253 => u64::from_le_bytes(self.read_array()?) as u128,
+            254 if wide => u128::from_le_bytes(self.read_array()?),
+            254 => return Err(Box::new(ErrorKind::Custom(
+                "Invalid value (u128 range): you may have a version or configuration disagreement?"
+                    .into(),
+            ))),
+            _ => {
+                return Err(Box::new(ErrorKind::Custom(
+                    "Invalid value (u8 255): you may have a version disagreement?".into(),
+                )))
+            }
+        })
+    }
+
+    fn read_unsigned<T: TryFrom<u128>>(&mut self) -> bincode::Result<T> {
+        T::try_from(self.read_varint(std::mem::size_of::<T>() == 16)?)
+            .map_err(|_| Box::new(ErrorKind::Custom("invalid varint".into())))
+    }
+
+    fn read_signed<T: TryFrom<i128>>(&mut self) -> bincode::Result<T> {
+        let n = self.read_varint(std::mem::size_of::<T>() == 16)?;
+        let n = if n % 2 == 0 {
+            (n / 2) as i128
+        } else {
+            !((n / 2) as i128)
+        };
+        T::try_from(n).map_err(|_| Box::new(ErrorKind::Custom("invalid varint".into())))
+    }
+
+    fn read_len(&mut self) -> bincode::Result<usize> {
+        let len: u64 = self.read_unsigned()?;
+        usize::try_from(len).map_err(|_| Box::new(ErrorKind::SizeLimit))
+    }
+
+    fn child_path(&mut self) -> String {
+        let parent = &self.stack.last().unwrap().path;
+        let label = std::mem::take(&mut self.label);
+        if parent.is_empty() {
+            label.trim_start_matches('.').to_string()
+        } else {
+            format!("{}{}", parent, label)
+        }
+    }
+
+    /// Records a leaf value that started at `start` and ends at the current position.
+    fn leaf(&mut self, path: String, start: usize, preview: String) {
+        let end = self.pos;
+        self.stack.last_mut().unwrap().children.push(Layout {
+            path,
+            range: start..end,
+            preview,
+            children: vec![],
+        })
+    }
+
+    /// Opens a composite value, which will contain everything recorded until the matching [Explainer::close].
+    fn open(&mut self, path: String, start: usize, preview: String) {
+        self.stack.push(Layout {
+            path,
+            range: start..start,
+            preview,
+            children: vec![],
+        });
+        self.u8_children.push(0);
+    }
+
+    fn close(&mut self) {
+        let mut node = self.stack.pop().unwrap();
+        node.range.end = self.pos;
+        // collapse byte arrays into a single leaf
+        let u8_children = self.u8_children.pop().unwrap();
+        if u8_children > 1 && u8_children == node.children.len() {
+            node.preview = format!("{} bytes", u8_children);
+            node.children.clear();
+        }
+        self.stack.last_mut().unwrap().children.push(node)
+    }
+
+    fn composite<'de, V: Visitor<'de>>(
+        &mut self,
+        preview: String,
+        f: impl FnOnce(&mut Self) -> bincode::Result<V::Value>,
+    ) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        self.open(path, self.pos, preview);
+        let toret = f(self)?;
+        self.close();
+        Ok(toret)
+    }
+}
+
+macro_rules! explain_num {
+    ($method:ident, $visit:ident, $read:ident, $ty:ty) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+            let path = self.child_path();
+            let start = self.pos;
+            let val: $ty = self.$read()?;
+            self.leaf(path, start, format!("{:?}", val));
+            visitor.$visit(val)
+        }
+    };
+}
+
+impl<'de, 'a> Deserializer<'de> for &mut Explainer<'a> {
+    type Error = bincode::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> bincode::Result<V::Value> {
+        Err(Box::new(ErrorKind::DeserializeAnyNotSupported))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        let start = self.pos;
+        let val = match self.read(1)?[0] {
+            0 => false,
+            1 => true,
+            other => return Err(Box::new(ErrorKind::InvalidBoolEncoding(other))),
+        };
+        self.leaf(path, start, format!("{:?}", val));
+        visitor.visit_bool(val)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        let start = self.pos;
+        let val = self.read(1)?[0];
+        self.leaf(path, start, format!("{:?}", val));
+        *self.u8_children.last_mut().unwrap() += 1;
+        visitor.visit_u8(val)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        let start = self.pos;
+        let val = self.read(1)?[0] as i8;
+        self.leaf(path, start, format!("{:?}", val));
+        visitor.visit_i8(val)
+    }
+
+    explain_num!(deserialize_u16, visit_u16, read_unsigned, u16);
+    explain_num!(deserialize_u32, visit_u32, read_unsigned, u32);
+    explain_num!(deserialize_u64, visit_u64, read_unsigned, u64);
+    explain_num!(deserialize_u128, visit_u128, read_unsigned, u128);
+    explain_num!(deserialize_i16, visit_i16, read_signed, i16);
+    explain_num!(deserialize_i32, visit_i32, read_signed, i32);
+    explain_num!(deserialize_i64, visit_i64, read_signed, i64);
+    explain_num!(deserialize_i128, visit_i128, read_signed, i128);
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        let start = self.pos;
+        let val = f32::from_le_bytes(self.read_array()?);
+        self.leaf(path, start, format!("{:?}", val));
+        visitor.visit_f32(val)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        let start = self.pos;
+        let val = f64::from_le_bytes(self.read_array()?);
+        self.leaf(path, start, format!("{:?}", val));
+        visitor.visit_f64(val)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        let start = self.pos;
+        let first = self.read(1)?[0];
+        let width = match first {
+            0x00..=0x7f => 1,
+            0xc2..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf4 => 4,
+            _ => return Err(Box::new(ErrorKind::InvalidCharEncoding)),
+        };
+        self.read(width - 1)?;
+        let val = std::str::from_utf8(&self.input[start..self.pos])
+            .ok()
+            .and_then(|s| s.chars().next())
+            .ok_or(ErrorKind::InvalidCharEncoding)?;
+        self.leaf(path, start, format!("{:?}", val));
+        visitor.visit_char(val)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        let start = self.pos;
+        let len = self.read_len()?;
+        let bts = self.read(len)?;
+        let val = std::str::from_utf8(bts).map_err(ErrorKind::InvalidUtf8Encoding)?;
+        self.leaf(path, start, truncate(&format!("{:?}", val), 64));
+        visitor.visit_str(val)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        let start = self.pos;
+        let len = self.read_len()?;
+        let bts = self.read(len)?;
+        self.leaf(path, start, format!("{} bytes", len));
+        visitor.visit_bytes(bts)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        let start = self.pos;
+        match self.read(1)?[0] {
+            0 => {
+                self.leaf(path, start, "None".into());
+                visitor.visit_none()
+            }
+            1 => {
+                self.open(path, start, "Some".into());
+                self.label = ".0".into();
+                let toret = visitor.visit_some(&mut *self)?;
+                self.close();
+                Ok(toret)
+            }
+            other => Err(Box::new(ErrorKind::InvalidTagEncoding(other as usize))),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        self.leaf(path, self.pos, "()".into());
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        self.leaf(path, self.pos, name.into());
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> bincode::Result<V::Value> {
+        self.composite::<V>(name.into(), |this| {
+            this.label = ".0".into();
+            visitor.visit_newtype_struct(this)
+        })
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        let start = self.pos;
+        let len = self.read_len()?;
+        self.open(path, start, format!("sequence of {}", len));
+        let toret = visitor.visit_seq(Elements {
+            de: self,
+            len,
+            idx: 0,
+            labels: None,
+        })?;
+        self.close();
+        Ok(toret)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> bincode::Result<V::Value> {
+        self.composite::<V>(format!("tuple of {}", len), |this| {
+            visitor.visit_seq(Elements {
+                de: this,
+                len,
+                idx: 0,
+                labels: None,
+            })
+        })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> bincode::Result<V::Value> {
+        self.composite::<V>(name.into(), |this| {
+            visitor.visit_seq(Elements {
+                de: this,
+                len,
+                idx: 0,
+                labels: None,
+            })
+        })
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        let start = self.pos;
+        let len = self.read_len()?;
+        self.open(path, start, format!("map of {}", len));
+        let toret = visitor.visit_map(Elements {
+            de: self,
+            len,
+            idx: 0,
+            labels: None,
+        })?;
+        self.close();
+        Ok(toret)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> bincode::Result<V::Value> {
+        self.composite::<V>(name.into(), |this| {
+            visitor.visit_seq(Elements {
+                de: this,
+                len: fields.len(),
+                idx: 0,
+                labels: Some(fields),
+            })
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> bincode::Result<V::Value> {
+        let path = self.child_path();
+        let start = self.pos;
+        let idx: u32 = self.read_unsigned()?;
+        let variant = variants.get(idx as usize).copied().unwrap_or("?");
+        self.open(path, start, format!("{}::{}", name, variant));
+        let toret = visitor.visit_enum(Variant { de: self, idx })?;
+        self.close();
+        Ok(toret)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> bincode::Result<V::Value> {
+        Err(Box::new(ErrorKind::Custom(
+            "stdcode does not support deserialize_identifier".into(),
+        )))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> bincode::Result<V::Value> {
+        Err(Box::new(ErrorKind::DeserializeAnyNotSupported))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Elements of a sequence, tuple, struct, or map.
+struct Elements<'b, 'a> {
+    de: &'b mut Explainer<'a>,
+    len: usize,
+    idx: usize,
+    labels: Option<&'static [&'static str]>,
+}
+
+impl<'b, 'a> Elements<'b, 'a> {
+    fn next_label(&mut self) -> String {
+        let label = match self.labels {
+            Some(labels) => format!(".{}", labels[self.idx]),
+            None => format!("[{}]", self.idx),
+        };
+        self.idx += 1;
+        label
+    }
+}
+
+impl<'de, 'b, 'a> SeqAccess<'de> for Elements<'b, 'a> {
+    type Error = bincode::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> bincode::Result<Option<T::Value>> {
+        if self.idx >= self.len {
+            return Ok(None);
+        }
+        self.de.label = self.next_label();
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.idx)
+    }
+}
+
+impl<'de, 'b, 'a> MapAccess<'de> for Elements<'b, 'a> {
+    type Error = bincode::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> bincode::Result<Option<K::Value>> {
+        if self.idx >= self.len {
+            return Ok(None);
+        }
+        self.de.label = format!("[{}].key", self.idx);
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> bincode::Result<V::Value> {
+        self.de.label = format!("[{}].value", self.idx);
+        self.idx += 1;
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.idx)
+    }
+}
+
+struct Variant<'b, 'a> {
+    de: &'b mut Explainer<'a>,
+    idx: u32,
+}
+
+impl<'de, 'b, 'a> EnumAccess<'de> for Variant<'b, 'a> {
+    type Error = bincode::Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> bincode::Result<(V::Value, Self)> {
+        let idx: serde::de::value::U32Deserializer<bincode::Error> = self.idx.into_deserializer();
+        let val = seed.deserialize(idx)?;
+        Ok((val, self))
+    }
+}
+
+impl<'de, 'b, 'a> VariantAccess<'de> for Variant<'b, 'a> {
+    type Error = bincode::Error;
+
+    fn unit_variant(self) -> bincode::Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> bincode::Result<T::Value> {
+        self.de.label = ".0".into();
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> bincode::Result<V::Value> {
+        visitor.visit_seq(Elements {
+            de: self.de,
+            len,
+            idx: 0,
+            labels: None,
+        })
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> bincode::Result<V::Value> {
+        visitor.visit_seq(Elements {
+            de: self.de,
+            len: fields.len(),
+            idx: 0,
+            labels: Some(fields),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    enum Kind {
+        Plain,
+        Tagged(u64),
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Test {
+        id: u64,
+        name: String,
+        kinds: Vec<Kind>,
+        extra: Option<u8>,
+    }
+
+    #[test]
+    fn explain_matches_deserialize() {
+        let val = Test {
+            id: 1000,
+            name: "hello".into(),
+            kinds: vec![Kind::Plain, Kind::Tagged(3)],
+            extra: Some(5),
+        };
+        let bts = crate::serialize(&val).unwrap();
+        let layout = crate::explain::<Test>(&bts).unwrap();
+        assert_eq!(layout.range, 0..bts.len());
+        let mut leaves = vec![];
+        layout.walk(&mut |node| {
+            if node.children.is_empty() {
+                leaves.push((node.path.clone(), node.range.clone()))
+            }
+        });
+        assert_eq!(
+            leaves,
+            vec![
+                ("id".to_string(), 0..3),
+                ("name".to_string(), 3..9),
+                ("kinds[0]".to_string(), 10..11),
+                ("kinds[1].0".to_string(), 12..13),
+                ("extra.0".to_string(), 14..15),
+            ]
+        );
+        // trailing bytes are rejected just like deserialize
+        let mut trailing = bts.clone();
+        trailing.push(0);
+        assert!(crate::explain::<Test>(&trailing).is_err());
+        // so is the 16-byte varint form, except for 128-bit integers
+        let mut wide = vec![254];
+        wide.extend_from_slice(&5u128.to_le_bytes());
+        assert!(crate::deserialize::<u64>(&wide).is_err());
+        assert!(crate::explain::<u64>(&wide).is_err());
+        assert_eq!(crate::deserialize::<u128>(&wide).unwrap(), 5);
+        assert!(crate::explain::<u128>(&wide).is_ok());
+    }
+}
@@ -0,0 +1,88 @@
+//! Encodes an integer field as fixed-width little-endian bytes instead of a varint, for fields whose encoded size must be predictable (database keys, memory-mapped records, etc):
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Key {
+//!     #[serde(with = "stdcode::fixed")]
+//!     height: u64,
+//!     nonce: u64,
+//! }
+//!
+//! let encoded = stdcode::serialize(&Key { height: 1, nonce: 1 }).unwrap();
+//! assert_eq!(encoded.len(), 8 + 1);
+//! ```
+//!
+//! Human-readable formats are unaffected and see a plain integer.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub fn serialize<T: FixedWidth, S: Serializer>(val: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    val.serialize_fixed(serializer)
+}
+
+pub fn deserialize<'de, T: FixedWidth, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<T, D::Error> {
+    T::deserialize_fixed(deserializer)
+}
+
+/// An integer type that can be encoded as fixed-width little-endian bytes.
+pub trait FixedWidth: Sized {
+    fn serialize_fixed<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+    fn deserialize_fixed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+macro_rules! fixed_width {
+    ($($ty:ty),*) => {
+        $(
+            impl FixedWidth for $ty {
+                fn serialize_fixed<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    if serializer.is_human_readable() {
+                        self.serialize(serializer)
+                    } else {
+                        self.to_le_bytes().serialize(serializer)
+                    }
+                }
+
+                fn deserialize_fixed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    if deserializer.is_human_readable() {
+                        <$ty>::deserialize(deserializer)
+                    } else {
+                        <[u8; std::mem::size_of::<$ty>()]>::deserialize(deserializer).map(<$ty>::from_le_bytes)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+fixed_width!(u16, u32, u64, u128, i16, i32, i64, i128);
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Record {
+        #[serde(with = "crate::fixed")]
+        a: u32,
+        #[serde(with = "crate::fixed")]
+        b: i128,
+        c: u64,
+    }
+
+    #[test]
+    fn fixed_roundtrip() {
+        let rec = Record { a: 1, b: -2, c: 3 };
+        let encoded = crate::serialize(&rec).unwrap();
+        assert_eq!(&encoded[..4], &1u32.to_le_bytes());
+        assert_eq!(&encoded[4..20], &(-2i128).to_le_bytes());
+        assert_eq!(encoded.len(), 4 + 16 + 1);
+        assert_eq!(crate::deserialize::<Record>(&encoded).unwrap(), rec);
+        let json = serde_json::to_string(&rec).unwrap();
+        assert_eq!(json, r#"{"a":1,"b":-2,"c":3}"#);
+        assert_eq!(serde_json::from_str::<Record>(&json).unwrap(), rec);
+    }
+}
@@ -8,7 +8,7 @@ where
     if serializer.is_human_readable() {
         serializer.serialize_str(&hex::encode(bytes))
     } else {
-        serializer.serialize_bytes(&bytes)
+        serializer.serialize_bytes(bytes)
     }
 
     // Could also use a wrapper type with a Display implementation to avoid
@@ -0,0 +1,83 @@
+//! Like [crate::hex32], but for byte arrays of any length, such as 64-byte signatures: hexadecimal in human-readable formats, and a tuple of bytes (exactly like a `[u8; N]` with `serde-big-array`) otherwise.
+
+use std::{convert::TryInto, fmt, marker::PhantomData};
+
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeTuple;
+use serde::Deserialize;
+use serde::{Deserializer, Serializer};
+
+pub fn serialize<const N: usize, S>(bytes: &[u8; N], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&hex::encode(bytes))
+    } else {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for b in bytes {
+            tuple.serialize_element(b)?;
+        }
+        tuple.end()
+    }
+}
+
+pub fn deserialize<'de, const N: usize, D>(deserializer: D) -> Result<[u8; N], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(s)
+            .map_err(de::Error::custom)?
+            .try_into()
+            .map_err(|_| de::Error::custom("hexadecimal length not right"))
+    } else {
+        deserializer.deserialize_tuple(N, ArrayVisitor::<N>(PhantomData))
+    }
+}
+
+struct ArrayVisitor<const N: usize>(PhantomData<[u8; N]>);
+
+impl<'de, const N: usize> Visitor<'de> for ArrayVisitor<N> {
+    type Value = [u8; N];
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "an array of {} bytes", N)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut out = [0u8; N];
+        for (i, b) in out.iter_mut().enumerate() {
+            *b = seq
+                .next_element()?
+                .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+    struct Test {
+        #[serde(with = "crate::hexarray")]
+        sig: [u8; 64],
+    }
+
+    #[test]
+    fn hexarray_roundtrip() {
+        let t = Test { sig: [7; 64] };
+        let json = serde_json::to_string(&t).unwrap();
+        assert_eq!(json, format!(r#"{{"sig":"{}"}}"#, "07".repeat(64)));
+        assert_eq!(serde_json::from_str::<Test>(&json).unwrap(), t);
+        // the same bytes as a plain array
+        let encoded = crate::serialize(&t).unwrap();
+        assert_eq!(encoded, vec![7; 64]);
+        assert_eq!(crate::deserialize::<Test>(&encoded).unwrap(), t);
+        assert!(crate::deserialize::<Test>(&encoded[1..]).is_err());
+        assert!(serde_json::from_str::<Test>(r#"{"sig":"0707"}"#).is_err());
+    }
+}
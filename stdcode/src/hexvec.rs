@@ -8,7 +8,8 @@ where
     S: Serializer,
 {
     // SAFETY: relies on repr(transparent)
-    let casted: &[HexBytesInner] = unsafe { std::mem::transmute(bytes) };
+    let casted: &[HexBytesInner] =
+        unsafe { std::mem::transmute::<&[Vec<u8>], &[HexBytesInner]>(bytes) };
     casted.serialize(serializer)
 }
 
@@ -17,5 +18,5 @@ where
     D: Deserializer<'de>,
 {
     let lala: Vec<HexBytesInner> = Deserialize::deserialize(deserializer)?;
-    Ok(unsafe { std::mem::transmute(lala) })
+    Ok(unsafe { std::mem::transmute::<Vec<HexBytesInner>, Vec<Vec<u8>>>(lala) })
 }
@@ -4,12 +4,29 @@ use std::{
 };
 
 use bincode::Options;
-use bytes::Bytes;
+use bytes::{Buf, BufMut, Bytes};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+#[cfg(feature = "addr")]
+pub mod addr32;
 pub mod asstr;
+#[cfg(feature = "async")]
+mod asyncio;
+#[cfg(feature = "async")]
+pub use asyncio::*;
+#[cfg(feature = "base64")]
+pub mod b64;
+#[cfg(feature = "base64")]
+pub mod b64_32;
+pub mod enumname;
+mod explain;
+pub use explain::*;
+pub mod fixed;
 pub mod hex;
 pub mod hex32;
+pub mod hexarray;
 pub mod hexvec;
+mod raw;
+pub use raw::*;
 pub mod try_asstr;
 
 /// A wrapper that serializes whatever's wrapped inside with its [Display] and [FromStr] implementations.
@@ -30,6 +47,16 @@ pub fn deserialize<T: DeserializeOwned>(bts: &[u8]) -> bincode::Result<T> {
         .deserialize(bts)
 }
 
+/// Fast-path deserialize for data that we wrote ourselves, such as values read back from a local database.
+///
+/// This skips the size-limit bookkeeping and the trailing-bytes check that [deserialize] does on every call. **Never use this on untrusted input**: a malicious length prefix can make it allocate unbounded memory, and garbage after the value is silently ignored.
+pub fn deserialize_trusted<T: DeserializeOwned>(bts: &[u8]) -> bincode::Result<T> {
+    bincode::DefaultOptions::new()
+        .with_varint_encoding()
+        .allow_trailing_bytes()
+        .deserialize(bts)
+}
+
 /// Serialize the stuff
 pub fn serialize<T: Serialize>(v: &T) -> bincode::Result<Vec<u8>> {
     bincode::DefaultOptions::new()
@@ -38,6 +65,49 @@ pub fn serialize<T: Serialize>(v: &T) -> bincode::Result<Vec<u8>> {
         .serialize(v)
 }
 
+/// Safe deserialize directly out of a [Buf], which may be non-contiguous, without first copying it into a flat slice. Like [deserialize], the whole buffer must be consumed.
+///
+/// ```rust
+/// use bytes::{Buf, BytesMut};
+///
+/// let val = (1000u64, "hello".to_string(), vec![1u8, 2, 3]);
+/// let mut buf = BytesMut::new();
+/// stdcode::serialize_into_buf(&mut buf, &val).unwrap();
+/// assert_eq!(buf[..], stdcode::serialize(&val).unwrap()[..]);
+///
+/// // split into two chunks, to deserialize from a non-contiguous buffer
+/// let mut first = buf.freeze();
+/// let second = first.split_off(4);
+/// let decoded: (u64, String, Vec<u8>) =
+///     stdcode::deserialize_buf(first.clone().chain(second.clone())).unwrap();
+/// assert_eq!(decoded, val);
+/// // trailing bytes are rejected
+/// let trailing = first.chain(second).chain(&[0u8][..]);
+/// assert!(stdcode::deserialize_buf::<(u64, String, Vec<u8>)>(trailing).is_err());
+/// ```
+pub fn deserialize_buf<T: DeserializeOwned>(mut buf: impl Buf) -> bincode::Result<T> {
+    let limit = buf.remaining() as u64;
+    let val = bincode::DefaultOptions::new()
+        .with_varint_encoding()
+        .reject_trailing_bytes()
+        .with_limit(limit)
+        .deserialize_from((&mut buf).reader())?;
+    if buf.has_remaining() {
+        return Err(Box::new(bincode::ErrorKind::Custom(
+            "Slice had bytes remaining after deserialization".into(),
+        )));
+    }
+    Ok(val)
+}
+
+/// Serialize the stuff directly into a [BufMut].
+pub fn serialize_into_buf<T: Serialize>(buf: &mut impl BufMut, v: &T) -> bincode::Result<()> {
+    bincode::DefaultOptions::new()
+        .with_varint_encoding()
+        .reject_trailing_bytes()
+        .serialize_into(buf.writer(), v)
+}
+
 /// An extension trait for all stdcode-serializable stuff.
 pub trait StdcodeSerializeExt: Serialize + Sized {
     fn stdcode(&self) -> Vec<u8> {
@@ -0,0 +1,76 @@
+use std::fmt::Debug;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Already-encoded stdcode bytes, carried around without decoding them. Analogous to `serde_json::value::RawValue`.
+///
+/// This lets, for example, a gateway forward an inner payload whose type it doesn't know without a decode/re-encode round trip, while the final recipient decodes it with [RawStdcode::decode].
+///
+/// Since stdcode is not self-delimiting, the bytes are embedded as a length-prefixed byte string, so a [RawStdcode] field has the same encoding as a `Vec<u8>` field holding `stdcode::serialize(&inner)`. In human-readable formats, it is a hex string.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct RawStdcode(#[serde(with = "crate::hex")] Vec<u8>);
+
+impl RawStdcode {
+    /// Encodes a value into raw stdcode.
+    pub fn encode<T: Serialize>(val: &T) -> bincode::Result<Self> {
+        Ok(Self(crate::serialize(val)?))
+    }
+
+    /// Wraps bytes that are already stdcode-encoded. They are not checked in any way.
+    pub fn from_bytes(bts: Vec<u8>) -> Self {
+        Self(bts)
+    }
+
+    /// Decodes the raw bytes into a concrete type.
+    pub fn decode<T: DeserializeOwned>(&self) -> bincode::Result<T> {
+        crate::deserialize(&self.0)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+impl AsRef<[u8]> for RawStdcode {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Debug for RawStdcode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RawStdcode({})", hex::encode(&self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Envelope {
+        route: u32,
+        payload: RawStdcode,
+    }
+
+    #[test]
+    fn raw_passthrough() {
+        let inner = ("hello".to_string(), 1234u64);
+        let env = Envelope {
+            route: 5,
+            payload: RawStdcode::encode(&inner).unwrap(),
+        };
+        let forwarded: Envelope = crate::deserialize(&crate::serialize(&env).unwrap()).unwrap();
+        assert_eq!(forwarded.payload, env.payload);
+        assert_eq!(forwarded.payload.decode::<(String, u64)>().unwrap(), inner);
+        assert_eq!(
+            crate::serialize(&env).unwrap(),
+            crate::serialize(&(5u32, crate::serialize(&inner).unwrap())).unwrap()
+        );
+    }
+}
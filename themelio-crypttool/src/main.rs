@@ -1,18 +1,78 @@
+use std::{collections::BTreeMap, convert::TryFrom, str::FromStr};
+
+use serde::{Deserialize, Serialize};
 use stdcode::StdcodeSerializeExt;
 use structopt::StructOpt;
-use themelio_stf::melvm::Covenant;
-use themelio_structs::{CoinID, Transaction};
-use tmelcrypt::Ed25519SK;
+use themelio_stf::melvm::{covenant_weight_from_bytes, Covenant};
+use themelio_structs::{Address, CoinData, CoinID, CoinValue, Denom, NetID, Transaction, TxKind};
+use tmelcrypt::{Ed25519PK, Ed25519SK, HashVal};
 #[derive(Debug, StructOpt)]
 enum Args {
     /// Generate a ed25519 keypair
-    GenerateEd25519,
+    GenerateEd25519(GenerateOpts),
     /// Hash tool
     Hash(HashOpts),
     /// Generate a CoinID for a reward
     RewardCoin(RewardOpts),
     /// Signs a transaction in hex-encoded form
     SignTx(SignTxOpts),
+    /// Decodes a transaction in hex-encoded form, summarizing its weight and fees
+    DecodeTx(DecodeTxOpts),
+    /// Builds an unsigned transaction from a JSON spec
+    BuildTx(BuildTxOpts),
+    /// Checks whether an address belongs to the standard covenant of a key
+    CheckAddress(CheckAddressOpts),
+    /// Signs a JSON document under a domain tag
+    SignDoc(SignDocOpts),
+    /// Verifies a signature produced by sign-doc
+    VerifyDoc(VerifyDocOpts),
+    /// Any other subcommand runs the `themelio-crypttool-<name>` plugin on the PATH
+    #[structopt(external_subcommand)]
+    Plugin(Vec<String>),
+}
+
+/// Context handed to plugins as JSON, through the `CRYPTTOOL_PLUGIN_CONTEXT` environment variable.
+#[derive(Debug, Serialize)]
+struct PluginContext {
+    /// Version of this handshake. Bumped on incompatible changes.
+    protocol: u32,
+    crypttool_version: &'static str,
+    /// Path to the keystore, from `THEMELIO_KEYSTORE`.
+    keystore: Option<String>,
+    /// Name of the profile in use, from `THEMELIO_PROFILE`.
+    profile: Option<String>,
+}
+
+#[derive(Debug, StructOpt)]
+struct GenerateOpts {
+    /// Network to produce addresses for: mainnet, testnet, or custom:<id>
+    #[structopt(long, default_value = "mainnet")]
+    network: Network,
+}
+
+/// A network given on the command line.
+#[derive(Debug, Clone, Copy)]
+struct Network(NetID);
+
+impl FromStr for Network {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mainnet" => Ok(Network(NetID::Mainnet)),
+            "testnet" => Ok(Network(NetID::Testnet)),
+            s => {
+                let id = s
+                    .strip_prefix("custom:")
+                    .ok_or_else(|| format!("unknown network {:?}", s))?;
+                let id: u8 = id
+                    .parse()
+                    .map_err(|_| format!("invalid custom network ID {:?}", id))?;
+                NetID::try_from(id)
+                    .map(Network)
+                    .map_err(|_| format!("no custom network with ID {}", id))
+            }
+        }
+    }
 }
 
 #[derive(Debug, StructOpt)]
@@ -39,24 +99,253 @@ struct SignTxOpts {
     /// Ed25519 private key, in hexadecimal format.
     #[structopt(long)]
     secret: Ed25519SK,
+    /// Fee multiplier at which to compute the base fee shown in the summary.
+    #[structopt(long)]
+    fee_multiplier: Option<u128>,
     /// The transaction to sign. This must be in hexadecimal format.
     tx_to_sign: String,
 }
 
+#[derive(Debug, StructOpt)]
+struct DecodeTxOpts {
+    /// Network to show output addresses for: mainnet, testnet, or custom:<id>
+    #[structopt(long, default_value = "mainnet")]
+    network: Network,
+    /// Fee multiplier at which to compute the base fee, as found in the latest block header.
+    #[structopt(long)]
+    fee_multiplier: Option<u128>,
+    /// The transaction to decode. This must be in hexadecimal format.
+    tx: String,
+}
+
+#[derive(Debug, StructOpt)]
+struct BuildTxOpts {
+    /// Fee multiplier at which to compute the base fee shown in the summary.
+    #[structopt(long)]
+    fee_multiplier: Option<u128>,
+    /// The transaction spec, in JSON format. For example: {"inputs": [{"coin": "<txhash>-<index>", "covenant": "<hex>"}], "outputs": [{"address": "t...", "value": 1000000, "denom": "MEL"}], "fee": 10000}
+    spec: String,
+}
+
+/// A JSON description of a simple transaction.
+#[derive(Debug, Deserialize)]
+struct TxSpec {
+    inputs: Vec<InputSpec>,
+    outputs: Vec<OutputSpec>,
+    fee: CoinValue,
+    #[serde(default, with = "stdcode::hex")]
+    data: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+struct InputSpec {
+    #[serde(with = "stdcode::asstr")]
+    coin: CoinID,
+    /// The covenant locking the coin, which must hash to its address.
+    #[serde(with = "stdcode::hex")]
+    covenant: Vec<u8>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OutputSpec {
+    #[serde(with = "stdcode::asstr")]
+    address: Address,
+    value: CoinValue,
+    #[serde(with = "stdcode::asstr")]
+    denom: Denom,
+    #[serde(default, with = "stdcode::hex")]
+    additional_data: Vec<u8>,
+}
+
+#[derive(Debug, StructOpt)]
+struct CheckAddressOpts {
+    /// Network to check the address on: mainnet, testnet, or custom:<id>
+    #[structopt(long, default_value = "mainnet")]
+    network: Network,
+    /// Ed25519 public key, in hexadecimal format. May be given multiple times, in which case the address is checked against every key.
+    #[structopt(long = "pk", required = true, number_of_values = 1)]
+    pks: Vec<Ed25519PK>,
+    /// The address to check.
+    address: Address,
+}
+
+#[derive(Debug, StructOpt)]
+struct SignDocOpts {
+    /// Domain tag saying what kind of document this is, e.g. "staking-declaration".
+    #[structopt(long)]
+    domain: String,
+    /// Ed25519 private key, in hexadecimal format.
+    #[structopt(long)]
+    secret: Ed25519SK,
+    /// The document to sign, in JSON format.
+    doc: String,
+}
+
+#[derive(Debug, StructOpt)]
+struct VerifyDocOpts {
+    /// Domain tag the document was signed under.
+    #[structopt(long)]
+    domain: String,
+    /// Ed25519 public key of the signer, in hexadecimal format.
+    #[structopt(long)]
+    public: Ed25519PK,
+    /// The signature, in hexadecimal format.
+    #[structopt(long)]
+    signature: String,
+    /// The signed document, in JSON format.
+    doc: String,
+}
+
 fn print_header(hdr: &str) {
     eprintln!("===== {} =====", hdr);
 }
 
+fn print_address(label: &str, addr: String, network: Network) {
+    eprintln!("{} ({}): {}", label, network.0, addr);
+    warn_network(network);
+}
+
+fn warn_network(network: Network) {
+    if network.0 != NetID::Mainnet {
+        eprintln!(
+            "WARNING: addresses do not encode their network yet. This address is NOT specific to {}, and funds sent to it on any network are spendable with the same key!",
+            network.0
+        );
+    }
+}
+
+fn decode_tx(hex_tx: &str) -> Transaction {
+    stdcode::deserialize(&hex::decode(hex_tx).expect("invalid hex in transaction"))
+        .expect("invalid transaction")
+}
+
+fn build_tx(spec: TxSpec) -> Transaction {
+    let mut covenants: Vec<Vec<u8>> = vec![];
+    for input in spec.inputs.iter() {
+        if !covenants.contains(&input.covenant) {
+            covenants.push(input.covenant.clone());
+        }
+    }
+    Transaction {
+        kind: TxKind::Normal,
+        inputs: spec.inputs.iter().map(|input| input.coin).collect(),
+        outputs: spec
+            .outputs
+            .into_iter()
+            .map(|output| CoinData {
+                covhash: output.address,
+                value: output.value,
+                denom: output.denom,
+                additional_data: output.additional_data,
+            })
+            .collect(),
+        fee: spec.fee,
+        covenants,
+        data: spec.data,
+        sigs: vec![],
+    }
+}
+
+/// The hash actually signed by sign-doc: `hash_keyed("crypttool-doc/" || domain, stdcode(doc))`. Documents are parsed into JSON values and their object keys sorted first, so neither key order nor whitespace matters.
+fn doc_signed_hash(domain: &str, doc: &str) -> HashVal {
+    let doc: serde_json::Value = serde_json::from_str(doc).expect("invalid JSON document");
+    tmelcrypt::hash_keyed(
+        format!("crypttool-doc/{}", domain),
+        canonical_json(doc).stdcode(),
+    )
+}
+
+/// Sorts the keys of every object, so that the encoding doesn't depend on whether serde_json's `preserve_order` feature is on somewhere in the dependency graph.
+fn canonical_json(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+            serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, canonical_json(v)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(vals) => {
+            serde_json::Value::Array(vals.into_iter().map(canonical_json).collect())
+        }
+        value => value,
+    }
+}
+
+/// Runs an external plugin, cargo-style, exiting with its exit code.
+fn run_plugin(args: Vec<String>) -> ! {
+    let name = &args[0];
+    let program = format!("themelio-crypttool-{}", name);
+    let context = PluginContext {
+        protocol: 1,
+        crypttool_version: env!("CARGO_PKG_VERSION"),
+        keystore: std::env::var("THEMELIO_KEYSTORE").ok(),
+        profile: std::env::var("THEMELIO_PROFILE").ok(),
+    };
+    let status = std::process::Command::new(&program)
+        .args(&args[1..])
+        .env(
+            "CRYPTTOOL_PLUGIN_CONTEXT",
+            serde_json::to_string(&context).unwrap(),
+        )
+        .status();
+    match status {
+        Ok(status) => std::process::exit(status.code().unwrap_or(1)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            eprintln!(
+                "error: no such subcommand {:?}, and no plugin {} on the PATH",
+                name, program
+            );
+            std::process::exit(1)
+        }
+        Err(err) => {
+            eprintln!("error: could not run plugin {}: {}", program, err);
+            std::process::exit(1)
+        }
+    }
+}
+
+fn print_tx_summary(tx: &Transaction, fee_multiplier: Option<u128>) {
+    print_header("TRANSACTION SUMMARY");
+    eprintln!("Weight      = {}", tx.weight(covenant_weight_from_bytes));
+    eprintln!("Fee paid    = {} MEL", tx.fee);
+    if let Some(fee_multiplier) = fee_multiplier {
+        let base_fee = tx.base_fee(fee_multiplier, 0, covenant_weight_from_bytes);
+        eprintln!(
+            "Base fee    = {} MEL (at fee multiplier {})",
+            base_fee, fee_multiplier
+        );
+        if tx.fee < base_fee {
+            eprintln!("WARNING: fee paid is lower than the base fee!");
+        }
+    }
+    let mut totals: BTreeMap<Denom, CoinValue> = BTreeMap::new();
+    for output in tx.outputs.iter() {
+        *totals.entry(output.denom).or_default() += output.value;
+    }
+    for (denom, total) in totals {
+        eprintln!("Outputs     = {} {}", total, denom);
+    }
+}
+
 fn main() {
     let args = Args::from_args();
     match args {
-        Args::GenerateEd25519 => {
+        Args::GenerateEd25519(opts) => {
             print_header("NEW ED25519 KEYPAIR");
-            let (pk, sk) = tmelcrypt::ed25519_keygen();
+            let sk = Ed25519SK::generate();
+            let pk = sk.to_public();
             eprintln!("PK = {}", hex::encode(pk.0));
             eprintln!("SK = {}", hex::encode(sk.0));
             let cov = Covenant::std_ed25519_pk_new(pk);
-            eprintln!("Address (new covenant): {}", cov.hash().0.to_addr());
+            print_address(
+                "Address (new covenant)",
+                cov.hash().0.to_addr(),
+                opts.network,
+            );
         }
         Args::Hash(opts) => {
             let h = if opts.json_transaction {
@@ -67,17 +356,15 @@ fn main() {
                 tmelcrypt::hash_single(&to_hash)
             };
             print_header("HASH OUTPUT");
-            eprintln!("{}", hex::encode(&h))
+            eprintln!("{}", hex::encode(h))
         }
         Args::RewardCoin(opts) => {
             print_header("REWARD PSEUDO-COINID");
             println!("{}", CoinID::proposer_reward(opts.height.into()))
         }
         Args::SignTx(opts) => {
-            let mut tx: Transaction = stdcode::deserialize(
-                &hex::decode(&opts.tx_to_sign).expect("invalid hex in transaction"),
-            )
-            .expect("invalid transaction");
+            let mut tx = decode_tx(&opts.tx_to_sign);
+            print_tx_summary(&tx, opts.fee_multiplier);
             let sig = opts.secret.sign(&tx.hash_nosigs().0);
             // fill zero-valued signatures into the transaction until we can write
             while tx.sigs.get(opts.posn).is_none() {
@@ -87,5 +374,86 @@ fn main() {
             print_header("SIGNED TRANSACTION");
             println!("{}", hex::encode(tx.stdcode()));
         }
+        Args::DecodeTx(opts) => {
+            let tx = decode_tx(&opts.tx);
+            print_header("DECODED TRANSACTION");
+            println!("{}", serde_json::to_string_pretty(&tx).unwrap());
+            print_tx_summary(&tx, opts.fee_multiplier);
+            for (i, output) in tx.outputs.iter().enumerate() {
+                eprintln!("Output {}    = {} ({})", i, output.covhash, opts.network.0);
+            }
+            if !tx.outputs.is_empty() {
+                warn_network(opts.network);
+            }
+        }
+        Args::BuildTx(opts) => {
+            let spec: TxSpec = serde_json::from_str(&opts.spec).expect("invalid transaction spec");
+            let tx = build_tx(spec);
+            print_tx_summary(&tx, opts.fee_multiplier);
+            eprintln!("Hash        = {}", tx.hash_nosigs());
+            print_header("UNSIGNED TRANSACTION");
+            println!("{}", hex::encode(tx.stdcode()));
+        }
+        Args::Plugin(args) => run_plugin(args),
+        Args::CheckAddress(opts) => {
+            print_header("ADDRESS CHECK");
+            let mut found = false;
+            for pk in opts.pks {
+                for (kind, cov) in [
+                    ("new", Covenant::std_ed25519_pk_new(pk)),
+                    ("legacy", Covenant::std_ed25519_pk_legacy(pk)),
+                ] {
+                    if cov.hash() == opts.address {
+                        eprintln!(
+                            "{} is the {} standard covenant of {} ({})",
+                            opts.address, kind, pk, opts.network.0
+                        );
+                        found = true;
+                    }
+                }
+            }
+            warn_network(opts.network);
+            if found {
+                println!("match");
+            } else {
+                eprintln!("WARNING: {} does not belong to any of the given keys! Do NOT send funds to it unless you know where it comes from.", opts.address);
+                println!("NO MATCH");
+                std::process::exit(1);
+            }
+        }
+        Args::SignDoc(opts) => {
+            let signed_hash = doc_signed_hash(&opts.domain, &opts.doc);
+            eprintln!("Signed hash = {}", signed_hash);
+            print_header("DOCUMENT SIGNATURE");
+            println!("{}", hex::encode(opts.secret.sign(&signed_hash.0)));
+        }
+        Args::VerifyDoc(opts) => {
+            let signed_hash = doc_signed_hash(&opts.domain, &opts.doc);
+            let sig = hex::decode(&opts.signature).expect("invalid hex in signature");
+            print_header("DOCUMENT VERIFICATION");
+            if opts.public.verify(&signed_hash.0, &sig) {
+                println!("valid");
+            } else {
+                println!("INVALID");
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doc_hash_ignores_key_order() {
+        let a = doc_signed_hash("test", r#"{"b": 1, "a": [{"y": 2, "x": 3}]}"#);
+        let b = doc_signed_hash("test", r#"{"a":[{"x":3,"y":2}],"b":1}"#);
+        assert_eq!(a, b);
+        assert_ne!(
+            a,
+            doc_signed_hash("other", r#"{"a":[{"x":3,"y":2}],"b":1}"#)
+        );
+        assert_ne!(a, doc_signed_hash("test", r#"{"a":[{"x":3,"y":3}],"b":1}"#));
     }
 }
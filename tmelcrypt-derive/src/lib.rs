@@ -0,0 +1,110 @@
+//! Derive macros for `tmelcrypt`. Use these through `tmelcrypt` with the `derive` feature, rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Fields, LitStr};
+
+/// Derives `tmelcrypt::MelHash`, hashing every field in declaration order through a `tmelcrypt::MelHasher`.
+///
+/// The domain defaults to the type name (and `Type::Variant` for enums), and can be overridden with `#[mel_hash(domain = "...")]` on the type.
+#[proc_macro_derive(MelHash, attributes(mel_hash))]
+pub fn derive_mel_hash(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+    match expand(&mut input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &mut DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let domain = domain_attr(input)?.unwrap_or_else(|| input.ident.to_string());
+    let name = &input.ident;
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, feeds) = destructure(&data.fields);
+            quote! {
+                let #name #pattern = self;
+                #[allow(unused_mut)]
+                let mut hasher = ::tmelcrypt::MelHasher::new(#domain);
+                #(#feeds)*
+                hasher.finish()
+            }
+        }
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|variant| {
+                let vname = &variant.ident;
+                let vdomain = format!("{}::{}", domain, vname);
+                let (pattern, feeds) = destructure(&variant.fields);
+                quote! {
+                    #name::#vname #pattern => {
+                        #[allow(unused_mut)]
+                        let mut hasher = ::tmelcrypt::MelHasher::new(#vdomain);
+                        #(#feeds)*
+                        hasher.finish()
+                    }
+                }
+            });
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new(
+                Span::call_site(),
+                "MelHash cannot be derived for unions",
+            ))
+        }
+    };
+
+    for param in input.generics.type_params_mut() {
+        param.bounds.push(parse_quote!(::tmelcrypt::MelHash));
+    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::tmelcrypt::MelHash for #name #ty_generics #where_clause {
+            fn mel_hash(&self) -> ::tmelcrypt::HashVal {
+                #body
+            }
+        }
+    })
+}
+
+/// Parses `#[mel_hash(domain = "...")]`.
+fn domain_attr(input: &DeriveInput) -> syn::Result<Option<String>> {
+    let mut domain = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("mel_hash")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("domain") {
+                domain = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported mel_hash attribute"))
+            }
+        })?;
+    }
+    Ok(domain)
+}
+
+/// Returns a pattern binding every field to a local variable, along with the statements feeding those variables into `hasher`.
+fn destructure(fields: &Fields) -> (proc_macro2::TokenStream, Vec<proc_macro2::TokenStream>) {
+    let bindings: Vec<Ident> = (0..fields.len())
+        .map(|i| format_ident!("__field{}", i))
+        .collect();
+    let feeds = bindings
+        .iter()
+        .map(|b| quote! { hasher.field(#b); })
+        .collect();
+    let pattern = match fields {
+        Fields::Named(named) => {
+            let names = named.named.iter().map(|f| f.ident.as_ref().unwrap());
+            quote! { { #(#names: #bindings),* } }
+        }
+        Fields::Unnamed(_) => quote! { ( #(#bindings),* ) },
+        Fields::Unit => quote! {},
+    };
+    (pattern, feeds)
+}
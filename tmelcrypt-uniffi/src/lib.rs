@@ -0,0 +1,142 @@
+//! Kotlin and Swift bindings for tmelcrypt's core operations, through [uniffi](https://mozilla.github.io/uniffi-rs/).
+//!
+//! Build the `cdylib` (Android) or `staticlib` (iOS) for the target platform, then generate the foreign-language sources from it with `uniffi-bindgen generate --library`. Byte strings cross the boundary as `ByteArray`s / `Data`, and errors are thrown as [TmelcryptError].
+
+use std::fmt;
+
+use tmelcrypt::bindings;
+
+uniffi::setup_scaffolding!();
+
+/// An error thrown to the foreign-language caller.
+#[derive(Debug, uniffi::Error)]
+pub enum TmelcryptError {
+    /// A key or hash had the wrong number of bytes.
+    InvalidLength { expected: u32, found: u32 },
+    /// An address string couldn't be parsed.
+    InvalidAddress { reason: String },
+    /// Any other failure inside tmelcrypt.
+    Other { msg: String },
+}
+
+impl fmt::Display for TmelcryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TmelcryptError::InvalidLength { expected, found } => {
+                write!(f, "expected {} bytes, found {}", expected, found)
+            }
+            TmelcryptError::InvalidAddress { reason } => write!(f, "invalid address: {}", reason),
+            TmelcryptError::Other { msg } => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TmelcryptError {}
+
+impl From<tmelcrypt::Error> for TmelcryptError {
+    fn from(err: tmelcrypt::Error) -> Self {
+        match err {
+            tmelcrypt::Error::InvalidLength { expected, actual } => TmelcryptError::InvalidLength {
+                expected: expected as u32,
+                found: actual as u32,
+            },
+            err => TmelcryptError::Other {
+                msg: err.to_string(),
+            },
+        }
+    }
+}
+
+impl From<tmelcrypt::AddressParseError> for TmelcryptError {
+    fn from(err: tmelcrypt::AddressParseError) -> Self {
+        TmelcryptError::InvalidAddress {
+            reason: err.to_string(),
+        }
+    }
+}
+
+/// Generates a new random 64-byte secret key.
+#[uniffi::export]
+pub fn generate_secret_key() -> Vec<u8> {
+    bindings::generate_secret_key()
+}
+
+/// Returns the 32-byte public key of a secret key.
+#[uniffi::export]
+pub fn public_key(sk: Vec<u8>) -> Result<Vec<u8>, TmelcryptError> {
+    Ok(bindings::public_key(&sk)?)
+}
+
+/// Signs a message, returning a 64-byte signature.
+#[uniffi::export]
+pub fn sign(sk: Vec<u8>, msg: Vec<u8>) -> Result<Vec<u8>, TmelcryptError> {
+    Ok(bindings::sign(&sk, &msg)?)
+}
+
+/// Verifies a signature. Malformed keys and signatures are simply invalid.
+#[uniffi::export]
+pub fn verify(pk: Vec<u8>, msg: Vec<u8>, sig: Vec<u8>) -> bool {
+    bindings::verify(&pk, &msg, &sig)
+}
+
+/// Hashes a value, like [tmelcrypt::hash_single].
+#[uniffi::export]
+pub fn hash(data: Vec<u8>) -> Vec<u8> {
+    bindings::hash(&data)
+}
+
+/// Hashes a value with a key, like [tmelcrypt::hash_keyed].
+#[uniffi::export]
+pub fn hash_keyed(key: Vec<u8>, data: Vec<u8>) -> Vec<u8> {
+    bindings::hash_keyed(&key, &data)
+}
+
+/// The `t...` address of the standard covenant locked by a public key.
+#[uniffi::export]
+pub fn address_from_public_key(pk: Vec<u8>) -> Result<String, TmelcryptError> {
+    Ok(bindings::address_from_public_key(&pk)?)
+}
+
+/// Formats a 32-byte covenant hash as a `t...` address.
+#[uniffi::export]
+pub fn hash_to_address(hash: Vec<u8>) -> Result<String, TmelcryptError> {
+    Ok(bindings::hash_to_address(&hash)?)
+}
+
+/// Parses a `t...` address into its 32-byte covenant hash, checking the checksum.
+#[uniffi::export]
+pub fn address_to_hash(addr: String) -> Result<Vec<u8>, TmelcryptError> {
+    Ok(bindings::address_to_hash(&addr)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let sk = generate_secret_key();
+        let pk = public_key(sk.clone()).unwrap();
+        let sig = sign(sk, b"hello".to_vec()).unwrap();
+        assert!(verify(pk.clone(), b"hello".to_vec(), sig));
+        let addr = address_from_public_key(pk).unwrap();
+        assert_eq!(
+            hash_to_address(address_to_hash(addr.clone()).unwrap()).unwrap(),
+            addr
+        );
+        assert!(matches!(
+            public_key(vec![0; 10]),
+            Err(TmelcryptError::InvalidLength {
+                expected: 64,
+                found: 10
+            })
+        ));
+    }
+
+    #[test]
+    fn other_errors() {
+        let err = TmelcryptError::from(tmelcrypt::Error::BadSignature);
+        assert!(matches!(&err, TmelcryptError::Other { .. }));
+        assert_eq!(err.to_string(), tmelcrypt::Error::BadSignature.to_string());
+    }
+}
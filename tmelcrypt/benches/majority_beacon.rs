@@ -0,0 +1,35 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tmelcrypt::{hash_single, majority_beacon, HashVal};
+
+/// The previous implementation, which collected every byte column into its own Vec, kept as a baseline.
+fn majority_beacon_columns(elems: &[HashVal]) -> HashVal {
+    let mut toret = [0u8; 32];
+    for (i, byte) in toret.iter_mut().enumerate() {
+        let column: Vec<u8> = elems.iter().map(|v| v[i]).collect();
+        for bit_idx in 0..8 {
+            let ones = column.iter().filter(|b| *b & (1 << bit_idx) == 1).count();
+            if ones * 2 > column.len() {
+                *byte |= 1 << bit_idx;
+            }
+        }
+    }
+    HashVal(toret)
+}
+
+fn bench(c: &mut Criterion) {
+    let elems: Vec<HashVal> = (0u64..50_000)
+        .map(|i| hash_single(i.to_le_bytes()))
+        .collect();
+    assert_eq!(majority_beacon(&elems), majority_beacon_columns(&elems));
+    let mut group = c.benchmark_group("majority_beacon_50k");
+    group.bench_function("columns", |b| {
+        b.iter(|| majority_beacon_columns(black_box(&elems)))
+    });
+    group.bench_function("counters", |b| {
+        b.iter(|| majority_beacon(black_box(&elems)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tmelcrypt::Ed25519SK;
+
+fn bench(c: &mut Criterion) {
+    let sk = Ed25519SK::generate();
+    let pk = sk.to_public();
+    let prepared = pk.prepare().unwrap();
+    let sig = sk.sign(b"block");
+    let mut group = c.benchmark_group("verify");
+    group.bench_function("unprepared", |b| {
+        b.iter(|| pk.verify(black_box(b"block"), black_box(&sig)))
+    });
+    group.bench_function("prepared", |b| {
+        b.iter(|| prepared.verify(black_box(b"block"), black_box(&sig)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
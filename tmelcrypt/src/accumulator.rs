@@ -0,0 +1,99 @@
+use std::iter::FromIterator;
+
+use crate::{hash_keyed, HashVal};
+
+/// Folds an ordered list of items into a single [HashVal], replacing the fragile pattern of concatenating the items and hashing the result.
+///
+/// Each item is length-prefixed and the final hash commits to the number of items, so different lists never give the same hash just because their concatenations are equal: `["ab", "c"]` and `["a", "bc"]` hash differently. Accumulators created with different domains are independent. Collecting an iterator of [HashVal]s gives an accumulator with the empty domain.
+#[derive(Clone)]
+pub struct HashAccumulator {
+    inner: blake3::Hasher,
+    count: u64,
+}
+
+impl HashAccumulator {
+    /// Creates an empty accumulator for the given domain, which should be a hardcoded string describing what the list is.
+    pub fn new(domain: &[u8]) -> Self {
+        Self {
+            inner: blake3::Hasher::new_keyed(&hash_keyed(b"tmelcrypt-hash-accumulator", domain).0),
+            count: 0,
+        }
+    }
+
+    /// Adds an item to the end of the list.
+    pub fn push(&mut self, item: impl AsRef<[u8]>) -> &mut Self {
+        let item = item.as_ref();
+        self.inner.update(&(item.len() as u64).to_le_bytes());
+        self.inner.update(item);
+        self.count += 1;
+        self
+    }
+
+    /// The number of items added so far.
+    pub fn len(&self) -> u64 {
+        self.count
+    }
+
+    /// Whether no items have been added.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// The hash of the list so far. More items can still be added afterwards.
+    pub fn finish(&self) -> HashVal {
+        let mut inner = self.inner.clone();
+        inner.update(&self.count.to_le_bytes());
+        HashVal(*inner.finalize().as_bytes())
+    }
+}
+
+impl Default for HashAccumulator {
+    fn default() -> Self {
+        Self::new(b"")
+    }
+}
+
+impl<T: AsRef<[u8]>> Extend<T> for HashAccumulator {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl FromIterator<HashVal> for HashAccumulator {
+    fn from_iter<I: IntoIterator<Item = HashVal>>(iter: I) -> Self {
+        let mut toret = Self::default();
+        toret.extend(iter);
+        toret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn framing() {
+        let mut a = HashAccumulator::new(b"test");
+        a.push(b"ab").push(b"c");
+        let mut b = HashAccumulator::new(b"test");
+        b.push(b"a").push(b"bc");
+        assert_ne!(a.finish(), b.finish());
+        assert_ne!(
+            HashAccumulator::new(b"other").finish(),
+            HashAccumulator::new(b"test").finish()
+        );
+
+        let hashes = vec![crate::hash_single(b"x"), crate::hash_single(b"y")];
+        let collected: HashAccumulator = hashes.iter().copied().collect();
+        let mut pushed = HashAccumulator::default();
+        pushed.extend(&hashes);
+        assert_eq!(collected.len(), 2);
+        assert_eq!(collected.finish(), pushed.finish());
+        // an empty item still counts
+        let mut empty = HashAccumulator::default();
+        empty.push(b"");
+        assert_ne!(empty.finish(), HashAccumulator::default().finish());
+    }
+}
@@ -0,0 +1,153 @@
+//! Adaptor signatures over ed25519, the building block of scriptless scripts such as atomic swaps and payment channels.
+//!
+//! An [AdaptorPoint] `T = tB` locks a signature behind its discrete log `t`, the [AdaptorSecret]. Anyone holding an [Ed25519SK] can make a [PreSignature] on a message locked to `T` with [Ed25519SK::adaptor_sign], and anyone can check it with [PreSignature::verify]. A pre-signature is not a valid signature, but whoever knows `t` can [complete](PreSignature::complete) it into an ordinary ed25519 signature under the signer's key. Conversely, once that signature appears (say, on chain), the signer recovers `t` from it with [PreSignature::extract]. Publishing the signature thus atomically reveals the secret.
+//!
+//! Each pre-signature uses a fresh random nonce. Never complete two different pre-signatures from the same nonce; [Ed25519SK::adaptor_sign] makes this impossible by construction.
+
+use std::fmt;
+
+use curve25519_dalek_ng::{
+    constants::ED25519_BASEPOINT_TABLE, edwards::CompressedEdwardsY, scalar::Scalar,
+};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::{expanded::ed25519_challenge, random_scalar, Ed25519PK, Ed25519SK, Signature};
+
+/// The secret scalar `t` that an adaptor signature is locked behind. Zeroed out on drop.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AdaptorSecret(pub Scalar);
+
+/// The public point `T = tB` that an adaptor signature is locked to.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AdaptorPoint(pub CompressedEdwardsY);
+
+/// A signature locked to an [AdaptorPoint]. `nonce` is the nonce `R + T` of the eventual signature, and `s` falls short of its scalar by exactly the adaptor secret.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreSignature {
+    pub nonce: CompressedEdwardsY,
+    pub adaptor: AdaptorPoint,
+    pub s: Scalar,
+}
+
+impl AdaptorSecret {
+    /// Generates a new random secret.
+    pub fn generate() -> Self {
+        Self(random_scalar())
+    }
+
+    /// The adaptor point to lock pre-signatures to.
+    pub fn to_point(&self) -> AdaptorPoint {
+        AdaptorPoint((&self.0 * &ED25519_BASEPOINT_TABLE).compress())
+    }
+}
+
+impl Ed25519SK {
+    /// Makes a pre-signature on `msg`, locked to `adaptor`. Returns [None] if `adaptor` isn't a valid curve point.
+    pub fn adaptor_sign(&self, msg: &[u8], adaptor: &AdaptorPoint) -> Option<PreSignature> {
+        let t = adaptor.0.decompress()?;
+        let mut r = random_scalar();
+        let nonce = (&r * &ED25519_BASEPOINT_TABLE + t).compress();
+        let c = ed25519_challenge(nonce.as_bytes(), &self.to_public().0, msg);
+        let mut secret = self.signing_scalar();
+        let s = r + c * secret;
+        r.zeroize();
+        secret.zeroize();
+        Some(PreSignature {
+            nonce,
+            adaptor: *adaptor,
+            s,
+        })
+    }
+}
+
+impl PreSignature {
+    /// Checks that this is a pre-signature by `pk` on `msg`, locked to `adaptor`, so that completing it gives a valid signature.
+    pub fn verify(&self, pk: &Ed25519PK, msg: &[u8], adaptor: &AdaptorPoint) -> bool {
+        if self.adaptor != *adaptor {
+            return false;
+        }
+        let (nonce, t, a) = match (
+            self.nonce.decompress(),
+            self.adaptor.0.decompress(),
+            CompressedEdwardsY(pk.0).decompress(),
+        ) {
+            (Some(nonce), Some(t), Some(a)) => (nonce, t, a),
+            _ => return false,
+        };
+        let c = ed25519_challenge(self.nonce.as_bytes(), &pk.0, msg);
+        &self.s * &ED25519_BASEPOINT_TABLE == nonce - t + c * a
+    }
+
+    /// Completes the pre-signature into an ordinary ed25519 signature, given the adaptor secret.
+    pub fn complete(&self, secret: &AdaptorSecret) -> Signature {
+        let mut sig = [0u8; 64];
+        sig[..32].copy_from_slice(self.nonce.as_bytes());
+        sig[32..].copy_from_slice((self.s + secret.0).as_bytes());
+        Signature(sig)
+    }
+
+    /// Recovers the adaptor secret from the signature that this pre-signature was completed into. Returns [None] if `sig` wasn't completed from it.
+    pub fn extract(&self, sig: &[u8]) -> Option<AdaptorSecret> {
+        if sig.len() != 64 || sig[..32] != self.nonce.0 {
+            return None;
+        }
+        let mut s = [0u8; 32];
+        s.copy_from_slice(&sig[32..]);
+        let secret = AdaptorSecret(Scalar::from_canonical_bytes(s)? - self.s);
+        if secret.to_point() != self.adaptor {
+            return None;
+        }
+        Some(secret)
+    }
+}
+
+impl Drop for AdaptorSecret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for AdaptorSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "AdaptorSecret({:?})", self.to_point())
+    }
+}
+
+impl fmt::Debug for AdaptorPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#<AdaptorPoint:{}>",
+            hex::encode(&self.0.as_bytes()[..5])
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adaptor_roundtrip() {
+        let sk = Ed25519SK::generate();
+        let pk = sk.to_public();
+        let secret = AdaptorSecret::generate();
+        let point = secret.to_point();
+
+        let pre = sk.adaptor_sign(b"swap", &point).unwrap();
+        assert!(pre.verify(&pk, b"swap", &point));
+        assert!(!pre.verify(&pk, b"other", &point));
+        assert!(!pre.verify(&pk, b"swap", &AdaptorSecret::generate().to_point()));
+        // the pre-signature alone isn't a valid signature
+        assert!(!pk.verify(b"swap", &pre.complete(&AdaptorSecret(Scalar::zero()))));
+
+        let sig = pre.complete(&secret);
+        assert!(pk.verify(b"swap", &sig));
+        assert_eq!(pre.extract(&sig), Some(secret));
+        let other = sk.adaptor_sign(b"swap", &point).unwrap();
+        assert_eq!(other.extract(&sig), None);
+    }
+}
@@ -0,0 +1,374 @@
+use std::{convert::TryInto, fmt, str::FromStr};
+
+use bech32::{FromBase32, ToBase32, Variant};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{hash_keyed, HashVal};
+
+/// A Themelio address: the hash of a covenant, written as a `t...` string with a checksum digit.
+///
+/// Parsing an [Address] checks the checksum digit, and reports exactly what was wrong with a bad address. In human-readable formats like JSON, it serializes as the `t...` string; in binary formats like stdcode, it is the same 32 raw bytes as a [HashVal].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Default)]
+pub struct Address(pub HashVal);
+
+/// Why a string is not a valid [Address].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddressParseError {
+    /// The address doesn't start with `t`.
+    BadPrefix,
+    /// The address has the wrong number of characters.
+    WrongLength(usize),
+    /// The address contains characters outside the Crockford base32 alphabet.
+    InvalidBase32,
+    /// The checksum digit doesn't match, so the address was probably mistyped.
+    BadChecksum { expected: u8, found: u8 },
+}
+
+impl fmt::Display for AddressParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressParseError::BadPrefix => write!(f, "address does not start with 't'"),
+            AddressParseError::WrongLength(len) => {
+                write!(f, "address has {} characters instead of 54", len)
+            }
+            AddressParseError::InvalidBase32 => write!(f, "address is not valid base32"),
+            AddressParseError::BadChecksum { expected, found } => write!(
+                f,
+                "address checksum digit is {} instead of {}; it was probably mistyped",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AddressParseError {}
+
+/// How to display an [Address] in a user interface. The default gives the same string as [Address]'s [Display](fmt::Display) implementation.
+///
+/// Parsing an [Address] ignores dashes, whitespace and case, so it accepts any grouped or uppercased form produced with these options. Truncated addresses can't be parsed back; they are only for display, next to some way of getting the full address.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AddressFormat {
+    /// Insert a dash after every this many characters.
+    pub group: Option<usize>,
+    /// Use uppercase instead of lowercase letters.
+    pub uppercase: bool,
+    /// Keep only this many characters at each end, joined by an ellipsis. Grouping is not applied to truncated addresses.
+    pub truncate: Option<usize>,
+}
+
+impl Address {
+    /// Formats the address for display. See [AddressFormat].
+    pub fn format(&self, format: &AddressFormat) -> String {
+        let s = self.to_string();
+        let s = if format.uppercase {
+            s.to_ascii_uppercase()
+        } else {
+            s
+        };
+        if let Some(keep) = format.truncate {
+            if keep * 2 < s.len() {
+                return format!("{}…{}", &s[..keep], &s[s.len() - keep..]);
+            }
+        }
+        match format.group {
+            Some(group) if group > 0 => s
+                .as_bytes()
+                .chunks(group)
+                .map(|c| std::str::from_utf8(c).unwrap())
+                .collect::<Vec<_>>()
+                .join("-"),
+            _ => s,
+        }
+    }
+
+    fn checksum(hash: &HashVal) -> u8 {
+        hash_keyed(b"address-checksum", hash.0).0[0] % 10
+    }
+}
+
+impl FromStr for Address {
+    type Err = AddressParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s: String = s
+            .chars()
+            .filter(|c| *c != '-' && !c.is_ascii_whitespace())
+            .collect();
+        if !s.starts_with(['t', 'T']) {
+            return Err(AddressParseError::BadPrefix);
+        }
+        if s.len() != 54 {
+            return Err(AddressParseError::WrongLength(s.len()));
+        }
+        let found = match s.as_bytes()[1] {
+            d @ b'0'..=b'9' => d - b'0',
+            _ => return Err(AddressParseError::InvalidBase32),
+        };
+        let hash = HashVal(
+            base32::decode(base32::Alphabet::Crockford, &s[2..])
+                .ok_or(AddressParseError::InvalidBase32)?
+                .try_into()
+                .map_err(|_| AddressParseError::InvalidBase32)?,
+        );
+        let expected = Self::checksum(&hash);
+        if expected != found {
+            return Err(AddressParseError::BadChecksum { expected, found });
+        }
+        Ok(Address(hash))
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.to_addr().fmt(f)
+    }
+}
+
+impl fmt::Debug for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Address({})", self)
+    }
+}
+
+impl From<HashVal> for Address {
+    fn from(hash: HashVal) -> Self {
+        Address(hash)
+    }
+}
+
+impl From<Address> for HashVal {
+    fn from(addr: Address) -> Self {
+        addr.0
+    }
+}
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            s.parse().map_err(de::Error::custom)
+        } else {
+            Ok(Address(HashVal::deserialize(deserializer)?))
+        }
+    }
+}
+
+/// The bech32m human-readable part for mainnet addresses.
+pub const MAINNET_HRP: &str = "mel";
+/// The bech32m human-readable part for testnet addresses.
+pub const TESTNET_HRP: &str = "tmel";
+
+/// Why a string is not a valid bech32m address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Bech32Error {
+    /// The string isn't valid bech32, or its checksum is wrong.
+    Invalid(bech32::Error),
+    /// The string is bech32 rather than bech32m.
+    WrongVariant,
+    /// The address is for a different network.
+    WrongHrp { expected: String, found: String },
+    /// The payload isn't 32 bytes.
+    WrongLength(usize),
+}
+
+impl fmt::Display for Bech32Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bech32Error::Invalid(err) => write!(f, "invalid bech32m: {}", err),
+            Bech32Error::WrongVariant => write!(f, "expected bech32m, not bech32"),
+            Bech32Error::WrongHrp { expected, found } => write!(
+                f,
+                "address is for network '{}' instead of '{}'",
+                found, expected
+            ),
+            Bech32Error::WrongLength(len) => write!(f, "payload is {} bytes instead of 32", len),
+        }
+    }
+}
+
+impl std::error::Error for Bech32Error {}
+
+impl HashVal {
+    /// Encodes the hash in bech32m with the given human-readable part, such as [MAINNET_HRP]. The checksum detects any error in up to 4 characters, unlike the single digit of [HashVal::to_addr]. Panics if the human-readable part is invalid.
+    pub fn to_bech32(&self, hrp: &str) -> String {
+        bech32::encode(hrp, self.0.to_base32(), Variant::Bech32m).expect("invalid bech32 hrp")
+    }
+
+    /// Decodes a bech32m string produced by [HashVal::to_bech32], checking that its human-readable part is `hrp`, so that addresses for one network can't be used on another.
+    pub fn from_bech32(s: &str, hrp: &str) -> Result<Self, Bech32Error> {
+        let (found, data, variant) = bech32::decode(s).map_err(Bech32Error::Invalid)?;
+        if variant != Variant::Bech32m {
+            return Err(Bech32Error::WrongVariant);
+        }
+        if found != hrp.to_ascii_lowercase() {
+            return Err(Bech32Error::WrongHrp {
+                expected: hrp.to_string(),
+                found,
+            });
+        }
+        let bytes = Vec::<u8>::from_base32(&data).map_err(Bech32Error::Invalid)?;
+        let len = bytes.len();
+        Ok(HashVal(
+            bytes
+                .try_into()
+                .map_err(|_| Bech32Error::WrongLength(len))?,
+        ))
+    }
+}
+
+/// Why a string is not a valid Base58Check-encoded hash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Base58CheckError {
+    /// The string isn't valid base58, or its checksum is wrong.
+    Invalid(bs58::decode::Error),
+    /// The version byte isn't the expected one.
+    WrongVersion { expected: u8, found: u8 },
+    /// The payload isn't 32 bytes.
+    WrongLength(usize),
+}
+
+impl fmt::Display for Base58CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Base58CheckError::Invalid(err) => write!(f, "invalid base58check: {}", err),
+            Base58CheckError::WrongVersion { expected, found } => {
+                write!(f, "version byte is {} instead of {}", found, expected)
+            }
+            Base58CheckError::WrongLength(len) => {
+                write!(f, "payload is {} bytes instead of 32", len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Base58CheckError {}
+
+impl HashVal {
+    /// Encodes the hash in Bitcoin-style Base58Check: a version byte, the hash, and the first 4 bytes of its double SHA-256, in base58.
+    pub fn to_base58check(&self, version: u8) -> String {
+        bs58::encode(self.0)
+            .with_check_version(version)
+            .into_string()
+    }
+
+    /// Decodes a string produced by [HashVal::to_base58check], checking its checksum and that its version byte is `version`.
+    pub fn from_base58check(s: &str, version: u8) -> Result<Self, Base58CheckError> {
+        let bytes = bs58::decode(s)
+            .with_check(None)
+            .into_vec()
+            .map_err(Base58CheckError::Invalid)?;
+        let (&found, payload) = bytes
+            .split_first()
+            .ok_or(Base58CheckError::WrongLength(0))?;
+        if found != version {
+            return Err(Base58CheckError::WrongVersion {
+                expected: version,
+                found,
+            });
+        }
+        Ok(HashVal(payload.try_into().map_err(|_| {
+            Base58CheckError::WrongLength(payload.len())
+        })?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address_checksum() {
+        let addr = Address(HashVal([0xab; 32]));
+        let s = addr.to_string();
+        assert_eq!(s.parse::<Address>(), Ok(addr));
+        assert_eq!(s.to_uppercase().parse::<Address>(), Ok(addr));
+
+        let mut mistyped = s.clone().into_bytes();
+        mistyped[1] = if mistyped[1] == b'0' { b'1' } else { b'0' };
+        assert!(matches!(
+            String::from_utf8(mistyped).unwrap().parse::<Address>(),
+            Err(AddressParseError::BadChecksum { .. })
+        ));
+        assert_eq!(
+            s[..20].parse::<Address>(),
+            Err(AddressParseError::WrongLength(20))
+        );
+        assert_eq!("x".parse::<Address>(), Err(AddressParseError::BadPrefix));
+
+        assert_eq!(serde_json::to_string(&addr).unwrap(), format!("\"{}\"", s));
+        assert_eq!(
+            stdcode::serialize(&addr).unwrap(),
+            stdcode::serialize(&addr.0).unwrap()
+        );
+    }
+
+    #[test]
+    fn address_format() {
+        let addr = Address(HashVal([0xab; 32]));
+        let s = addr.to_string();
+        assert_eq!(addr.format(&AddressFormat::default()), s);
+
+        let grouped = addr.format(&AddressFormat {
+            group: Some(4),
+            uppercase: true,
+            truncate: None,
+        });
+        assert_eq!(grouped[..4], s[..4].to_uppercase());
+        assert_eq!(&grouped[4..5], "-");
+        assert_eq!(grouped.parse::<Address>(), Ok(addr));
+        assert_eq!(grouped.replace('-', " ").parse::<Address>(), Ok(addr));
+
+        let short = addr.format(&AddressFormat {
+            truncate: Some(6),
+            ..Default::default()
+        });
+        assert_eq!(short, format!("{}…{}", &s[..6], &s[48..]));
+    }
+
+    #[test]
+    fn bech32m() {
+        let hash = HashVal([0xab; 32]);
+        let s = hash.to_bech32(MAINNET_HRP);
+        assert!(s.starts_with("mel1"));
+        assert_eq!(HashVal::from_bech32(&s, MAINNET_HRP), Ok(hash));
+        assert!(matches!(
+            HashVal::from_bech32(&s, TESTNET_HRP),
+            Err(Bech32Error::WrongHrp { .. })
+        ));
+        let mut typo = s.into_bytes();
+        typo[10] = if typo[10] == b'q' { b'p' } else { b'q' };
+        assert!(matches!(
+            HashVal::from_bech32(std::str::from_utf8(&typo).unwrap(), MAINNET_HRP),
+            Err(Bech32Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn base58check() {
+        let hash = HashVal([0xab; 32]);
+        let s = hash.to_base58check(0x80);
+        assert_eq!(HashVal::from_base58check(&s, 0x80), Ok(hash));
+        assert_eq!(
+            HashVal::from_base58check(&s, 0x00),
+            Err(Base58CheckError::WrongVersion {
+                expected: 0x00,
+                found: 0x80
+            })
+        );
+        let mut mistyped = s.into_bytes();
+        mistyped[10] = if mistyped[10] == b'2' { b'3' } else { b'2' };
+        assert!(matches!(
+            HashVal::from_base58check(&String::from_utf8(mistyped).unwrap(), 0x80),
+            Err(Base58CheckError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,115 @@
+use std::fmt;
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::fill_random;
+
+const NONCE_LEN: usize = 24;
+
+/// A symmetric key for XChaCha20-Poly1305 authenticated encryption.
+///
+/// Nonces are handled internally: every call to [AeadKey::seal] picks a fresh random 192-bit nonce, which is long enough that random nonces never collide in practice, and prepends it to the ciphertext. The key is zeroed out on drop.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AeadKey(#[serde(with = "stdcode::hex32")] pub [u8; 32]);
+
+/// Error returned when a ciphertext can't be opened, because it's truncated, was tampered with, or was sealed under a different key or associated data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AeadError;
+
+impl fmt::Display for AeadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "could not decrypt or authenticate ciphertext".fmt(f)
+    }
+}
+
+impl std::error::Error for AeadError {}
+
+impl AeadKey {
+    /// Generates a new random key.
+    pub fn generate() -> Self {
+        let mut key = [0u8; 32];
+        fill_random(&mut key);
+        Self(key)
+    }
+
+    /// Encrypts and authenticates `plaintext`, also authenticating (but not encrypting) `aad`. Returns the nonce followed by the ciphertext and tag.
+    pub fn seal(&self, plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+        let mut nonce = [0u8; NONCE_LEN];
+        fill_random(&mut nonce);
+        let ciphertext = self
+            .cipher()
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: plaintext,
+                    aad,
+                },
+            )
+            .expect("encryption cannot fail");
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        sealed
+    }
+
+    /// Decrypts and authenticates the output of [AeadKey::seal], given the same `aad`.
+    pub fn open(&self, sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, AeadError> {
+        if sealed.len() < NONCE_LEN {
+            return Err(AeadError);
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        self.cipher()
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| AeadError)
+    }
+
+    fn cipher(&self) -> XChaCha20Poly1305 {
+        XChaCha20Poly1305::new((&self.0).into())
+    }
+}
+
+impl Drop for AeadKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl PartialEq for AeadKey {
+    fn eq(&self, other: &Self) -> bool {
+        crate::ct_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for AeadKey {}
+
+impl fmt::Debug for AeadKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "AeadKey(<redacted>)".fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_open() {
+        let key = AeadKey::generate();
+        let sealed = key.seal(b"attack at dawn", b"header");
+        assert_eq!(key.open(&sealed, b"header").unwrap(), b"attack at dawn");
+        assert_eq!(key.open(&sealed, b"other"), Err(AeadError));
+        assert_eq!(AeadKey::generate().open(&sealed, b"header"), Err(AeadError));
+        assert_ne!(sealed, key.seal(b"attack at dawn", b"header"));
+    }
+}
@@ -0,0 +1,68 @@
+use std::convert::TryFrom;
+
+use ed25519_consensus::{batch, Signature, VerificationKeyBytes};
+
+use crate::{Ed25519PK, EntropyRng};
+
+/// Verifies many signatures at once, which is much faster than verifying them one by one. Returns true only if every signature is valid.
+///
+/// Batch verification accepts exactly the same signatures as [Ed25519PK::verify], but if it fails, it doesn't tell which signature was invalid.
+pub fn verify_batch(items: &[(Ed25519PK, &[u8], &[u8])]) -> bool {
+    let mut verifier = BatchVerifier::new();
+    for (pk, msg, sig) in items {
+        verifier.queue(*pk, msg, sig);
+    }
+    verifier.verify()
+}
+
+/// Incrementally accumulates signatures for batch verification. See [verify_batch].
+#[derive(Default)]
+pub struct BatchVerifier {
+    inner: batch::Verifier,
+    malformed: bool,
+}
+
+impl BatchVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a signature to the batch.
+    pub fn queue(&mut self, pk: Ed25519PK, msg: &[u8], sig: &[u8]) {
+        match <[u8; 64]>::try_from(sig) {
+            Ok(sig) => {
+                self.inner
+                    .queue((VerificationKeyBytes::from(pk.0), Signature::from(sig), &msg))
+            }
+            Err(_) => self.malformed = true,
+        }
+    }
+
+    /// Verifies every signature in the batch, returning true only if all of them are valid. An empty batch is valid.
+    pub fn verify(self) -> bool {
+        !self.malformed && self.inner.verify(EntropyRng).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ed25519SK;
+
+    #[test]
+    fn batch() {
+        let sks: Vec<Ed25519SK> = (0..10).map(|_| Ed25519SK::generate()).collect();
+        let sigs: Vec<Vec<u8>> = sks.iter().map(|sk| sk.sign(b"block")).collect();
+        let mut items: Vec<(Ed25519PK, &[u8], &[u8])> = sks
+            .iter()
+            .zip(sigs.iter())
+            .map(|(sk, sig)| (sk.to_public(), &b"block"[..], &sig[..]))
+            .collect();
+        assert!(verify_batch(&items));
+        items[3].1 = b"other";
+        assert!(!verify_batch(&items));
+        items[3].1 = b"block";
+        items[5].2 = &[0; 10];
+        assert!(!verify_batch(&items));
+    }
+}
@@ -0,0 +1,255 @@
+use std::{collections::HashMap, convert::TryInto};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use crate::{hash_single, Ed25519PK, HashVal};
+
+/// Extracts a single unbiased integer in `0..range` from a beacon output (such as the result of [crate::majority_beacon]), separated by `domain`.
+///
+/// This is the first value produced by [BeaconExtractor]. Panics if `range` is zero.
+pub fn beacon_extract(seed: HashVal, domain: impl AsRef<[u8]>, range: u64) -> u64 {
+    BeaconExtractor::new(seed, domain, range)
+        .next()
+        .expect("beacon extractor is infinite")
+}
+
+impl HashVal {
+    /// A deterministic RNG seeded with this hash, such as a beacon output, for deriving per-epoch randomness with the full [rand::Rng] API.
+    ///
+    /// The RNG is ChaCha20 keyed directly with the hash bytes, and its output stream is fixed across versions, so consensus code can rely on it. Different purposes drawing from the same beacon should first separate their seeds, e.g. with [crate::hash_keyed].
+    pub fn to_rng(&self) -> ChaCha20Rng {
+        ChaCha20Rng::from_seed(self.0)
+    }
+}
+
+/// An infinite iterator of unbiased integers in `0..range`, deterministically derived from a beacon output.
+///
+/// The algorithm is fixed and must not change: for counter `i = 0, 1, 2, ...`, compute `hash_keyed(domain, seed || i)` with `i` as a little-endian u64, and read its first 8 bytes as a little-endian u64 `x`. If `x` falls within the largest multiple of `range` that fits in a u64, yield `x % range`; otherwise reject it and move on to the next counter.
+///
+/// [BeaconExtractor::next_below] draws from the same stream of counters with a different range.
+#[derive(Clone, Debug)]
+pub struct BeaconExtractor {
+    key: HashVal,
+    seed: HashVal,
+    range: u64,
+    counter: u64,
+}
+
+impl BeaconExtractor {
+    /// Creates a new extractor. Panics if `range` is zero.
+    pub fn new(seed: HashVal, domain: impl AsRef<[u8]>, range: u64) -> Self {
+        assert!(range > 0, "cannot extract from an empty range");
+        Self {
+            key: hash_single(domain),
+            seed,
+            range,
+            counter: 0,
+        }
+    }
+
+    /// Draws the next unbiased value in `0..range`, ignoring the range the extractor was created with. Panics if `range` is zero.
+    pub fn next_below(&mut self, range: u64) -> u64 {
+        assert!(range > 0, "cannot extract from an empty range");
+        let zone = u64::MAX - (u64::MAX - range + 1) % range;
+        loop {
+            let x = self.next_raw();
+            if x <= zone {
+                return x % range;
+            }
+        }
+    }
+
+    /// Like [BeaconExtractor::next_below], but for 128-bit ranges. The first 16 bytes of each hash are used instead of the first 8.
+    pub fn next_below_u128(&mut self, range: u128) -> u128 {
+        assert!(range > 0, "cannot extract from an empty range");
+        let zone = u128::MAX - (u128::MAX - range + 1) % range;
+        loop {
+            let x = u128::from_le_bytes(self.next_hash()[..16].try_into().unwrap());
+            if x <= zone {
+                return x % range;
+            }
+        }
+    }
+
+    /// Draws the next raw 64-bit value, before range reduction.
+    fn next_raw(&mut self) -> u64 {
+        u64::from_le_bytes(self.next_hash()[..8].try_into().unwrap())
+    }
+
+    fn next_hash(&mut self) -> [u8; 32] {
+        let mut msg = [0u8; 40];
+        msg[..32].copy_from_slice(&self.seed.0);
+        msg[32..].copy_from_slice(&self.counter.to_le_bytes());
+        self.counter += 1;
+        // equivalent to hash_keyed(domain, msg), without rehashing the domain every time
+        *blake3::keyed_hash(&self.key.0, &msg).as_bytes()
+    }
+}
+
+impl Iterator for BeaconExtractor {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        Some(self.next_below(self.range))
+    }
+}
+
+/// Deterministically shuffles a slice, using randomness extracted from `seed` separated by `domain`.
+///
+/// The algorithm is the Fisher-Yates shuffle: for `i` from `len - 1` down to `1`, draw `j = next_below(i + 1)` from a [BeaconExtractor] and swap elements `i` and `j`.
+pub fn shuffle<T>(seed: HashVal, domain: impl AsRef<[u8]>, slice: &mut [T]) {
+    let mut extractor = BeaconExtractor::new(seed, domain, 1);
+    for i in (1..slice.len()).rev() {
+        let j = extractor.next_below(i as u64 + 1) as usize;
+        slice.swap(i, j);
+    }
+}
+
+/// Deterministically samples `k` distinct indices out of `0..population`, using randomness extracted from `seed` separated by `domain`. Panics if `k > population`.
+///
+/// The algorithm is a partial Fisher-Yates shuffle of the virtual array `[0, 1, ..., population - 1]`: for `i` from `0` to `k - 1`, draw `j = i + next_below(population - i)` from a [BeaconExtractor], swap elements `i` and `j`, and output element `i`. Indices are returned in the order they were selected.
+pub fn sample_k(seed: HashVal, domain: impl AsRef<[u8]>, population: u64, k: u64) -> Vec<u64> {
+    assert!(k <= population, "cannot sample more than the population");
+    let mut extractor = BeaconExtractor::new(seed, domain, 1);
+    // sparse representation of the virtual array, storing only swapped positions
+    let mut swapped: HashMap<u64, u64> = HashMap::new();
+    (0..k)
+        .map(|i| {
+            let j = i + extractor.next_below(population - i);
+            let at_i = swapped.get(&i).copied().unwrap_or(i);
+            let at_j = swapped.get(&j).copied().unwrap_or(j);
+            swapped.insert(j, at_i);
+            at_j
+        })
+        .collect()
+}
+
+/// Elects `slots` keys, each with probability proportional to its weight (stake), using randomness extracted from `seed`. The same key may be elected to multiple slots. Returns an empty vector if the total weight is zero.
+///
+/// The algorithm is "follow-the-satoshi": lay out the weights end to end in the given order, then for every slot, draw `x = next_below_u128(total_weight)` from a [BeaconExtractor] with domain `"tmelcrypt-elect"`, and elect the key whose interval contains `x`. Panics if the total weight overflows a u128.
+pub fn elect(seed: HashVal, weights: &[(Ed25519PK, u128)], slots: usize) -> Vec<Ed25519PK> {
+    let mut cumulative = Vec::with_capacity(weights.len());
+    let mut total: u128 = 0;
+    for (_, weight) in weights {
+        total = total.checked_add(*weight).expect("total weight overflowed");
+        cumulative.push(total);
+    }
+    if total == 0 {
+        return vec![];
+    }
+    let mut extractor = BeaconExtractor::new(seed, b"tmelcrypt-elect", 1);
+    (0..slots)
+        .map(|_| {
+            let x = extractor.next_below_u128(total);
+            // first key whose interval ends after x
+            let idx = cumulative.partition_point(|end| *end <= x);
+            weights[idx].0
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_and_shuffle() {
+        let seed = HashVal([42; 32]);
+        let sample = sample_k(seed, b"test", 100, 20);
+        assert_eq!(sample, sample_k(seed, b"test", 100, 20));
+        let mut dedup = sample.clone();
+        dedup.sort_unstable();
+        dedup.dedup();
+        assert_eq!(dedup.len(), 20);
+        assert!(sample.iter().all(|i| *i < 100));
+        assert_eq!(sample_k(seed, b"test", 5, 5).len(), 5);
+
+        let mut deck: Vec<u32> = (0..52).collect();
+        shuffle(seed, b"test", &mut deck);
+        assert_ne!(deck, (0..52).collect::<Vec<_>>());
+        deck.sort_unstable();
+        assert_eq!(deck, (0..52).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn extract() {
+        let seed = HashVal([42; 32]);
+        let x = beacon_extract(seed, b"test", 1000);
+        assert!(x < 1000);
+        assert_eq!(x, beacon_extract(seed, b"test", 1000));
+        assert_eq!(Some(x), BeaconExtractor::new(seed, b"test", 1000).next());
+        // follows the documented algorithm, with counter 0
+        let mut msg = seed.0.to_vec();
+        msg.extend_from_slice(&0u64.to_le_bytes());
+        let raw = u64::from_le_bytes(crate::hash_keyed(b"test", &msg)[..8].try_into().unwrap());
+        assert_eq!(x, raw % 1000);
+        // pinned, since consensus relies on this never changing
+        assert_eq!(x, 568);
+        assert_eq!(beacon_extract(seed, b"test", 1), 0);
+        let other: Vec<u64> = (0..8)
+            .map(|i| beacon_extract(HashVal([i; 32]), b"test", u64::MAX))
+            .collect();
+        assert!(other.iter().any(|v| *v != other[0]));
+        assert_ne!(
+            beacon_extract(seed, b"test", u64::MAX),
+            beacon_extract(seed, b"other", u64::MAX)
+        );
+    }
+
+    #[test]
+    fn election() {
+        let seed = HashVal([42; 32]);
+        let a = crate::Ed25519SK::generate().to_public();
+        let b = crate::Ed25519SK::generate().to_public();
+        let c = crate::Ed25519SK::generate().to_public();
+        let weights = vec![(a, 1), (b, 3), (c, 0)];
+        let elected = elect(seed, &weights, 4000);
+        assert_eq!(elected, elect(seed, &weights, 4000));
+        assert_ne!(elected, elect(HashVal([43; 32]), &weights, 4000));
+        // zero-weight keys are never elected, and the rest in proportion to their weight
+        assert!(!elected.contains(&c));
+        let b_count = elected.iter().filter(|k| **k == b).count();
+        assert!((2800..3200).contains(&b_count), "{}", b_count);
+
+        assert_eq!(elect(seed, &[], 10), vec![]);
+        assert_eq!(elect(seed, &[(a, 0), (b, 0)], 10), vec![]);
+        assert_eq!(elect(seed, &weights, 0), vec![]);
+        assert_eq!(elect(seed, &[(c, 0), (a, 5)], 3), vec![a; 3]);
+    }
+
+    #[test]
+    fn hashval_rng() {
+        use rand::Rng;
+        // pinned, since consensus relies on this never changing
+        let mut rng = HashVal([42; 32]).to_rng();
+        assert_eq!(rng.gen::<u64>(), 1585975039535028632);
+    }
+
+    #[test]
+    fn majority() {
+        let mut elems = vec![HashVal([0b1010_1011; 32]); 3];
+        elems.push(HashVal([0xff; 32]));
+        elems.push(HashVal([0x00; 32]));
+        // only the lowest bit of each byte is taken into account
+        assert_eq!(crate::majority_beacon(&elems), HashVal([0b0000_0001; 32]));
+        // ties go to zero
+        assert_eq!(
+            crate::majority_beacon(&[HashVal([0xff; 32]), HashVal([0; 32])]),
+            HashVal([0; 32])
+        );
+        assert_eq!(crate::majority_beacon(&[]), HashVal([0; 32]));
+    }
+
+    #[test]
+    fn majority_pinned() {
+        // pinned to the output of 0.2.7, since every party deriving a beacon together must get the same value
+        let elems: Vec<HashVal> = (0u64..1000)
+            .map(|i| crate::hash_single(i.to_le_bytes()))
+            .collect();
+        assert_eq!(
+            crate::majority_beacon(&elems).to_string(),
+            "0001010000000101000001000101010000000001000000010001010000000000"
+        );
+    }
+}
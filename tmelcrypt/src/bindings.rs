@@ -0,0 +1,94 @@
+//! The core operations over plain byte slices and strings, shared by the foreign-language bindings (the `wasm` module and the `tmelcrypt-uniffi` crate) so that they check lengths and encode results identically. Each binding only converts its own argument and error types.
+
+use std::convert::TryInto;
+
+use crate::{Address, AddressParseError, Ed25519PK, Ed25519SK, Error, HashVal};
+
+fn parse_sk(sk: &[u8]) -> Result<Ed25519SK, Error> {
+    Ed25519SK::from_bytes(sk).ok_or(Error::InvalidLength {
+        expected: 64,
+        actual: sk.len(),
+    })
+}
+
+fn parse_32(bytes: &[u8]) -> Result<[u8; 32], Error> {
+    bytes.try_into().map_err(|_| Error::InvalidLength {
+        expected: 32,
+        actual: bytes.len(),
+    })
+}
+
+/// Generates a new random 64-byte secret key.
+pub fn generate_secret_key() -> Vec<u8> {
+    Ed25519SK::generate().0.to_vec()
+}
+
+/// Returns the 32-byte public key of a secret key.
+pub fn public_key(sk: &[u8]) -> Result<Vec<u8>, Error> {
+    Ok(parse_sk(sk)?.to_public().0.to_vec())
+}
+
+/// Signs a message, returning a 64-byte signature.
+pub fn sign(sk: &[u8], msg: &[u8]) -> Result<Vec<u8>, Error> {
+    Ok(parse_sk(sk)?.sign(msg))
+}
+
+/// Verifies a signature. Malformed keys and signatures are simply invalid.
+pub fn verify(pk: &[u8], msg: &[u8], sig: &[u8]) -> bool {
+    match parse_32(pk) {
+        Ok(pk) => Ed25519PK(pk).verify(msg, sig),
+        Err(_) => false,
+    }
+}
+
+/// Hashes a value, like [crate::hash_single].
+pub fn hash(data: &[u8]) -> Vec<u8> {
+    crate::hash_single(data).0.to_vec()
+}
+
+/// Hashes a value with a key, like [crate::hash_keyed].
+pub fn hash_keyed(key: &[u8], data: &[u8]) -> Vec<u8> {
+    crate::hash_keyed(key, data).0.to_vec()
+}
+
+/// The `t...` address of the standard covenant locked by a public key.
+pub fn address_from_public_key(pk: &[u8]) -> Result<String, Error> {
+    Ok(Ed25519PK(parse_32(pk)?).std_address().to_addr())
+}
+
+/// Formats a 32-byte covenant hash as a `t...` address.
+pub fn hash_to_address(hash: &[u8]) -> Result<String, Error> {
+    Ok(HashVal(parse_32(hash)?).to_addr())
+}
+
+/// Parses a `t...` address into its 32-byte covenant hash, checking the checksum.
+pub fn address_to_hash(addr: &str) -> Result<Vec<u8>, AddressParseError> {
+    let addr: Address = addr.parse()?;
+    Ok(addr.0 .0.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let sk = generate_secret_key();
+        let pk = public_key(&sk).unwrap();
+        let sig = sign(&sk, b"hello").unwrap();
+        assert!(verify(&pk, b"hello", &sig));
+        assert!(!verify(&pk[..31], b"hello", &sig));
+        let addr = address_from_public_key(&pk).unwrap();
+        assert_eq!(
+            hash_to_address(&address_to_hash(&addr).unwrap()).unwrap(),
+            addr
+        );
+        assert_eq!(
+            public_key(&[0; 10]),
+            Err(Error::InvalidLength {
+                expected: 64,
+                actual: 10
+            })
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use std::ops::{BitAnd, BitXor};
+
+use ethnum::U256;
+
+use crate::HashVal;
+
+impl BitXor for HashVal {
+    type Output = HashVal;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        let mut toret = self.0;
+        for (a, b) in toret.iter_mut().zip(rhs.0.iter()) {
+            *a ^= b;
+        }
+        HashVal(toret)
+    }
+}
+
+impl BitAnd for HashVal {
+    type Output = HashVal;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        let mut toret = self.0;
+        for (a, b) in toret.iter_mut().zip(rhs.0.iter()) {
+            *a &= b;
+        }
+        HashVal(toret)
+    }
+}
+
+impl HashVal {
+    /// The number of leading zero bits, reading the hash as a big-endian number. An all-zero hash has 256.
+    pub fn leading_zeros(&self) -> u32 {
+        let mut toret = 0;
+        for byte in self.0.iter() {
+            toret += byte.leading_zeros();
+            if *byte != 0 {
+                break;
+            }
+        }
+        toret
+    }
+
+    /// Whether the hash, read as a big-endian number, is at most `target`. This is the usual proof-of-work and sortition check: a random hash meets a target `t` with probability `(t + 1) / 2^256`.
+    pub fn meets_target(&self, target: &HashVal) -> bool {
+        self <= target
+    }
+
+    /// Whether the hash has at least `bits` leading zero bits, which a random hash does with probability `2^-bits`.
+    pub fn meets_difficulty(&self, bits: u32) -> bool {
+        self.leading_zeros() >= bits
+    }
+
+    /// The hash as a big-endian 256-bit integer.
+    pub fn to_u256(&self) -> U256 {
+        U256::from_be_bytes(self.0)
+    }
+
+    /// The hash whose big-endian reading is `n`.
+    pub fn from_u256(n: U256) -> Self {
+        HashVal(n.to_be_bytes())
+    }
+
+    /// The high and low halves of the hash, each read as a big-endian integer.
+    pub fn to_u128s(&self) -> (u128, u128) {
+        let (hi, lo) = self.to_u256().into_words();
+        (hi, lo)
+    }
+
+    /// The hash with the given high and low big-endian halves.
+    pub fn from_u128s(hi: u128, lo: u128) -> Self {
+        Self::from_u256(U256::from_words(hi, lo))
+    }
+
+    /// The Kademlia XOR distance to another hash. Distances compare as big-endian numbers through [Ord], so the closest of several hashes is the one with the smallest distance.
+    pub fn xor_distance(&self, other: &HashVal) -> HashVal {
+        *self ^ *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bitops() {
+        let a = HashVal([0b1100; 32]);
+        let b = HashVal([0b1010; 32]);
+        assert_eq!(a ^ b, HashVal([0b0110; 32]));
+        assert_eq!(a & b, HashVal([0b1000; 32]));
+        assert_eq!(HashVal([0; 32]).leading_zeros(), 256);
+        let mut x = [0; 32];
+        x[1] = 0b0001_0000;
+        assert_eq!(HashVal(x).leading_zeros(), 11);
+        assert_eq!(a.xor_distance(&a), HashVal::default());
+        let close = HashVal([0b1101; 32]);
+        assert!(a.xor_distance(&close) < a.xor_distance(&b));
+
+        let mut one = [0; 32];
+        one[31] = 1;
+        assert_eq!(HashVal(one).to_u256(), U256::ONE);
+        assert_eq!(HashVal(one).to_u128s(), (0, 1));
+        assert_eq!(HashVal::from_u128s(u128::MAX, 0).0[..16], [0xff; 16]);
+        assert_eq!(HashVal::from_u256(a.to_u256()), a);
+        let target = HashVal::from_u256(U256::MAX >> 4);
+        assert!(HashVal(x).meets_target(&target));
+        assert!(HashVal(x).meets_difficulty(11));
+        assert!(a.meets_target(&target));
+        assert!(!a.meets_difficulty(5));
+        assert!(!HashVal([0x10; 32]).meets_target(&target));
+    }
+}
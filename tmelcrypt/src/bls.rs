@@ -0,0 +1,227 @@
+use blst::{
+    min_pk::{AggregatePublicKey, AggregateSignature, PublicKey, SecretKey, Signature},
+    BLST_ERROR,
+};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::fill_random;
+
+/// Domain separation tag, from the "proof of possession" ciphersuite of the IETF BLS signature draft.
+const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+/// Domain separation tag for proofs of possession.
+const POP_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// A BLS12-381 public key (a compressed G1 point).
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct BlsPK(#[serde(with = "stdcode::hexarray")] pub [u8; 48]);
+
+/// A BLS12-381 secret key. Zeroed out on drop. It can only hold a valid key: parsing and deserializing reject zero and values not less than the group order.
+#[derive(Clone, Serialize)]
+pub struct BlsSK([u8; 32]);
+
+/// A BLS12-381 signature (a compressed G2 point), possibly aggregated from many signatures.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct BlsSig(#[serde(with = "stdcode::hexarray")] pub [u8; 96]);
+
+impl BlsSK {
+    /// Generates a new random secret key.
+    pub fn generate() -> Self {
+        let mut ikm = [0u8; 32];
+        fill_random(&mut ikm);
+        let sk = Self::from_ikm(&ikm);
+        ikm.zeroize();
+        sk
+    }
+
+    /// Deterministically derives a secret key from at least 32 bytes of keying material, with the standard `KeyGen` procedure. Panics if `ikm` is too short.
+    pub fn from_ikm(ikm: &[u8]) -> Self {
+        let sk = SecretKey::key_gen(ikm, &[]).expect("keying material must be at least 32 bytes");
+        Self(sk.to_bytes())
+    }
+
+    /// Creates a secret key from its 32 big-endian bytes, returning `None` if they are zero or not less than the group order.
+    pub fn from_bytes(bts: [u8; 32]) -> Option<Self> {
+        SecretKey::from_bytes(&bts).ok()?;
+        Some(Self(bts))
+    }
+
+    /// The secret key's 32 bytes, for intentionally exporting it.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    pub fn to_public(&self) -> BlsPK {
+        BlsPK(self.inner().sk_to_pk().compress())
+    }
+
+    pub fn sign(&self, msg: &[u8]) -> BlsSig {
+        BlsSig(self.inner().sign(msg, DST, &[]).compress())
+    }
+
+    /// Proves possession of this secret key. Verifiers should check the proof with [BlsPK::verify_possession] before accepting a public key for use with [BlsSig::fast_aggregate_verify], which is otherwise vulnerable to rogue-key attacks.
+    pub fn prove_possession(&self) -> BlsSig {
+        let pk = self.to_public();
+        BlsSig(self.inner().sign(&pk.0, POP_DST, &[]).compress())
+    }
+
+    fn inner(&self) -> SecretKey {
+        SecretKey::from_bytes(&self.0).expect("secret keys are validated on construction")
+    }
+}
+
+impl BlsPK {
+    pub fn verify(&self, msg: &[u8], sig: &BlsSig) -> bool {
+        let (pk, sig) = match (self.inner(), sig.inner()) {
+            (Some(pk), Some(sig)) => (pk, sig),
+            _ => return false,
+        };
+        sig.verify(true, msg, DST, &[], &pk, true) == BLST_ERROR::BLST_SUCCESS
+    }
+
+    /// Verifies a proof from [BlsSK::prove_possession].
+    pub fn verify_possession(&self, proof: &BlsSig) -> bool {
+        let (pk, sig) = match (self.inner(), proof.inner()) {
+            (Some(pk), Some(sig)) => (pk, sig),
+            _ => return false,
+        };
+        sig.verify(true, &self.0, POP_DST, &[], &pk, true) == BLST_ERROR::BLST_SUCCESS
+    }
+
+    /// Aggregates public keys, for verifying an aggregate signature of a single message. Returns `None` if any key is invalid or there are no keys.
+    pub fn aggregate(pks: &[BlsPK]) -> Option<BlsPK> {
+        let pks = pks
+            .iter()
+            .map(|pk| pk.inner())
+            .collect::<Option<Vec<_>>>()?;
+        let refs: Vec<&PublicKey> = pks.iter().collect();
+        let agg = AggregatePublicKey::aggregate(&refs, true).ok()?;
+        Some(BlsPK(agg.to_public_key().compress()))
+    }
+
+    fn inner(&self) -> Option<PublicKey> {
+        PublicKey::uncompress(&self.0).ok()
+    }
+}
+
+impl BlsSig {
+    /// Aggregates many signatures into one. Returns `None` if any signature is invalid or there are no signatures.
+    pub fn aggregate(sigs: &[BlsSig]) -> Option<BlsSig> {
+        let sigs = sigs.iter().map(|s| s.inner()).collect::<Option<Vec<_>>>()?;
+        let refs: Vec<&Signature> = sigs.iter().collect();
+        let agg = AggregateSignature::aggregate(&refs, true).ok()?;
+        Some(BlsSig(agg.to_signature().compress()))
+    }
+
+    /// Verifies an aggregate of signatures on pairwise distinct messages, each by the corresponding key.
+    pub fn aggregate_verify(&self, signers: &[(BlsPK, &[u8])]) -> bool {
+        let sig = match self.inner() {
+            Some(sig) => sig,
+            None => return false,
+        };
+        let pks = match signers
+            .iter()
+            .map(|(pk, _)| pk.inner())
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(pks) => pks,
+            None => return false,
+        };
+        let pk_refs: Vec<&PublicKey> = pks.iter().collect();
+        let msgs: Vec<&[u8]> = signers.iter().map(|(_, msg)| *msg).collect();
+        sig.aggregate_verify(true, &msgs, DST, &pk_refs, true) == BLST_ERROR::BLST_SUCCESS
+    }
+
+    /// Verifies an aggregate of signatures on the same message, e.g. a committee's signatures on a block. Every key must have had its proof of possession checked with [BlsPK::verify_possession].
+    pub fn fast_aggregate_verify(&self, msg: &[u8], pks: &[BlsPK]) -> bool {
+        let sig = match self.inner() {
+            Some(sig) => sig,
+            None => return false,
+        };
+        let pks = match pks.iter().map(|pk| pk.inner()).collect::<Option<Vec<_>>>() {
+            Some(pks) => pks,
+            None => return false,
+        };
+        let pk_refs: Vec<&PublicKey> = pks.iter().collect();
+        sig.fast_aggregate_verify(true, msg, DST, &pk_refs) == BLST_ERROR::BLST_SUCCESS
+    }
+
+    fn inner(&self) -> Option<Signature> {
+        Signature::uncompress(&self.0).ok()
+    }
+}
+
+bytes_impls!(BlsPK, 48, "#<BlsPK:{}>");
+bytes_impls!(BlsSig, 96, "#<BlsSig:{}>");
+
+secret_key_impls!(BlsSK, <[u8; 32]>::deserialize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregate() {
+        let sks: Vec<BlsSK> = (0..4).map(|_| BlsSK::generate()).collect();
+        let pks: Vec<BlsPK> = sks.iter().map(|sk| sk.to_public()).collect();
+        assert!(pks
+            .iter()
+            .zip(sks.iter())
+            .all(|(pk, sk)| pk.verify_possession(&sk.prove_possession())));
+
+        let sigs: Vec<BlsSig> = sks.iter().map(|sk| sk.sign(b"block")).collect();
+        assert!(pks[0].verify(b"block", &sigs[0]));
+        assert!(!pks[1].verify(b"block", &sigs[0]));
+        let agg = BlsSig::aggregate(&sigs).unwrap();
+        assert!(agg.fast_aggregate_verify(b"block", &pks));
+        assert!(BlsPK::aggregate(&pks).unwrap().verify(b"block", &agg));
+        assert!(!agg.fast_aggregate_verify(b"other", &pks));
+
+        let msgs: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i]).collect();
+        let sigs: Vec<BlsSig> = sks
+            .iter()
+            .zip(msgs.iter())
+            .map(|(sk, m)| sk.sign(m))
+            .collect();
+        let agg = BlsSig::aggregate(&sigs).unwrap();
+        let signers: Vec<(BlsPK, &[u8])> = pks
+            .iter()
+            .zip(msgs.iter())
+            .map(|(pk, m)| (*pk, &m[..]))
+            .collect();
+        assert!(agg.aggregate_verify(&signers));
+        assert_eq!(agg.to_string().parse::<BlsSig>().unwrap(), agg);
+
+        // hex in JSON, like ed25519 keys, and raw bytes in stdcode
+        let json = serde_json::to_string(&pks[0]).unwrap();
+        assert_eq!(json, format!("\"{}\"", pks[0]));
+        assert_eq!(serde_json::from_str::<BlsPK>(&json).unwrap(), pks[0]);
+        assert_eq!(
+            serde_json::from_str::<BlsSig>(&serde_json::to_string(&agg).unwrap()).unwrap(),
+            agg
+        );
+        assert_eq!(stdcode::serialize(&agg).unwrap(), agg.0.to_vec());
+    }
+
+    #[test]
+    fn invalid_secret_key() {
+        let sk = BlsSK::generate();
+        assert_eq!(sk.reveal_hex().parse::<BlsSK>().unwrap(), sk);
+        assert!(!sk.to_string().contains(&sk.reveal_hex()[..10]));
+        assert!(!format!("{:?}", sk).contains(&sk.reveal_hex()[..10]));
+        assert_eq!(
+            stdcode::deserialize::<BlsSK>(&stdcode::serialize(&sk).unwrap()).unwrap(),
+            sk
+        );
+        assert_eq!(
+            "00".repeat(32).parse::<BlsSK>(),
+            Err(crate::Error::InvalidSecretKey)
+        );
+        assert_eq!(
+            "ff".repeat(32).parse::<BlsSK>(),
+            Err(crate::Error::InvalidSecretKey)
+        );
+        assert!(stdcode::deserialize::<BlsSK>(&[0; 32]).is_err());
+        assert!(serde_json::from_str::<BlsSK>(&format!("{:?}", [0u8; 32])).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+use std::ops::{BitOr, BitOrAssign};
+
+use serde::{Deserialize, Serialize};
+use stdcode::StdcodeSerializeExt;
+
+use crate::{hash_keyed, Ed25519PK, Ed25519SK, HashVal};
+
+/// A set of flags describing what a certified key may be used for.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KeyUsage(pub u32);
+
+impl KeyUsage {
+    /// The key may sign arbitrary application messages.
+    pub const SIGN: Self = KeyUsage(1);
+    /// The key may issue further certificates, extending the chain.
+    pub const CERTIFY: Self = KeyUsage(1 << 1);
+    /// The key may be used to authenticate network sessions.
+    pub const AUTHENTICATE: Self = KeyUsage(1 << 2);
+
+    /// Returns true if no flags are set.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if every flag in `other` is also set in `self`.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for KeyUsage {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        KeyUsage(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for KeyUsage {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0
+    }
+}
+
+/// An ed25519 public key, certified by an issuer key for a limited validity window and a limited set of usages.
+///
+/// Times are UNIX timestamps in seconds. The window is inclusive of `valid_after` and exclusive of `valid_before`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CertifiedKey {
+    pub key: Ed25519PK,
+    pub valid_after: u64,
+    pub valid_before: u64,
+    pub usage: KeyUsage,
+    pub issuer: Ed25519PK,
+    #[serde(with = "stdcode::hex")]
+    pub signature: Vec<u8>,
+}
+
+impl CertifiedKey {
+    /// Certifies `key` using the issuer's secret key.
+    pub fn issue(
+        issuer: &Ed25519SK,
+        key: Ed25519PK,
+        valid_after: u64,
+        valid_before: u64,
+        usage: KeyUsage,
+    ) -> Self {
+        let mut cert = CertifiedKey {
+            key,
+            valid_after,
+            valid_before,
+            usage,
+            issuer: issuer.to_public(),
+            signature: vec![],
+        };
+        cert.signature = issuer.sign(&cert.signed_hash());
+        cert
+    }
+
+    /// The hash that the issuer signs. Covers every field except the signature itself.
+    pub fn signed_hash(&self) -> HashVal {
+        let body = (
+            &self.key,
+            self.valid_after,
+            self.valid_before,
+            self.usage,
+            &self.issuer,
+        );
+        hash_keyed(b"tmelcrypt-certified-key", body.stdcode())
+    }
+
+    /// Checks only the issuer's signature, ignoring the validity window.
+    pub fn verify_signature(&self) -> bool {
+        self.issuer.verify(&self.signed_hash(), &self.signature)
+    }
+
+    /// Checks the issuer's signature, and that `time` falls within the validity window.
+    pub fn is_valid_at(&self, time: u64) -> bool {
+        self.valid_after <= time && time < self.valid_before && self.verify_signature()
+    }
+}
+
+/// Validates a chain of certificates rooted at `root`, returning the final certified key if the whole chain is valid at `time` and the final key is allowed `usage`. An empty `usage` is never allowed.
+///
+/// The first certificate must be issued by `root`, and every following certificate must be issued by the key certified just before it. Every certificate except the last must carry [KeyUsage::CERTIFY].
+pub fn verify_cert_chain(
+    root: Ed25519PK,
+    chain: &[CertifiedKey],
+    time: u64,
+    usage: KeyUsage,
+) -> Option<Ed25519PK> {
+    if usage.is_empty() {
+        return None;
+    }
+    let (last, intermediate) = chain.split_last()?;
+    let mut expected_issuer = root;
+    for cert in intermediate {
+        if cert.issuer != expected_issuer
+            || !cert.usage.contains(KeyUsage::CERTIFY)
+            || !cert.is_valid_at(time)
+        {
+            return None;
+        }
+        expected_issuer = cert.key;
+    }
+    if last.issuer != expected_issuer || !last.usage.contains(usage) || !last.is_valid_at(time) {
+        return None;
+    }
+    Some(last.key)
+}
+
+/// A parent key's authorization for a child key to act on its behalf within a scope, until an expiry time. This lets a validator keep its identity key cold and delegate day-to-day signing to a hot key.
+///
+/// Unlike a [CertifiedKey], whose usages are a fixed set of flags, the scope is an application-defined `/`-separated path such as `"staking/votes"`. A delegation for a scope also covers every scope below it, and the empty scope covers everything. The expiry is a UNIX timestamp in seconds, and is exclusive.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DelegationCert {
+    pub parent: Ed25519PK,
+    pub child: Ed25519PK,
+    pub scope: String,
+    pub expires: u64,
+    #[serde(with = "stdcode::hex")]
+    pub signature: Vec<u8>,
+}
+
+impl DelegationCert {
+    /// Delegates `scope` to `child` until `expires`, using the parent's secret key.
+    pub fn issue(parent: &Ed25519SK, child: Ed25519PK, scope: &str, expires: u64) -> Self {
+        let mut cert = DelegationCert {
+            parent: parent.to_public(),
+            child,
+            scope: scope.to_string(),
+            expires,
+            signature: vec![],
+        };
+        cert.signature = parent.sign(&cert.signed_hash());
+        cert
+    }
+
+    /// The hash that the parent signs. Covers every field except the signature itself.
+    pub fn signed_hash(&self) -> HashVal {
+        let body = (&self.parent, &self.child, &self.scope, self.expires);
+        hash_keyed(b"tmelcrypt-delegation-cert", body.stdcode())
+    }
+
+    /// Checks the parent's signature, and that the delegation hasn't expired at `time`.
+    pub fn is_valid_at(&self, time: u64) -> bool {
+        time < self.expires && self.parent.verify(&self.signed_hash(), &self.signature)
+    }
+
+    /// Validates a chain of delegations rooted at `root`, returning the final child key if the whole chain is valid at `time` and authorizes `scope`.
+    ///
+    /// The first delegation must be issued by `root`, and every following one by the child of the one before it. Each delegation's scope must lie within the previous one's, so a key can never delegate more than it was given.
+    pub fn verify_chain(
+        root: Ed25519PK,
+        chain: &[DelegationCert],
+        time: u64,
+        scope: &str,
+    ) -> Option<Ed25519PK> {
+        let mut expected_parent = root;
+        let mut allowed = "";
+        for cert in chain {
+            if cert.parent != expected_parent
+                || !scope_contains(allowed, &cert.scope)
+                || !cert.is_valid_at(time)
+            {
+                return None;
+            }
+            expected_parent = cert.child;
+            allowed = &cert.scope;
+        }
+        if chain.is_empty() || !scope_contains(allowed, scope) {
+            return None;
+        }
+        Some(expected_parent)
+    }
+}
+
+/// Whether `inner` is `outer` or a scope below it.
+fn scope_contains(outer: &str, inner: &str) -> bool {
+    outer.is_empty()
+        || inner == outer
+        || (inner.starts_with(outer) && inner.as_bytes()[outer.len()] == b'/')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cert_chain() {
+        let root = Ed25519SK::generate();
+        let intermediate = Ed25519SK::generate();
+        let leaf = Ed25519SK::generate();
+        let chain = vec![
+            CertifiedKey::issue(&root, intermediate.to_public(), 100, 200, KeyUsage::CERTIFY),
+            CertifiedKey::issue(&intermediate, leaf.to_public(), 150, 300, KeyUsage::SIGN),
+        ];
+        assert_eq!(
+            verify_cert_chain(root.to_public(), &chain, 160, KeyUsage::SIGN),
+            Some(leaf.to_public())
+        );
+        // outside the intermediate's window
+        assert_eq!(
+            verify_cert_chain(root.to_public(), &chain, 250, KeyUsage::SIGN),
+            None
+        );
+        // leaf not allowed to certify
+        assert_eq!(
+            verify_cert_chain(root.to_public(), &chain, 160, KeyUsage::CERTIFY),
+            None
+        );
+        // wrong root
+        assert_eq!(
+            verify_cert_chain(leaf.to_public(), &chain, 160, KeyUsage::SIGN),
+            None
+        );
+        // an empty usage set grants nothing
+        assert_eq!(
+            verify_cert_chain(root.to_public(), &chain, 160, KeyUsage::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn delegation_chain() {
+        let cold = Ed25519SK::generate();
+        let warm = Ed25519SK::generate();
+        let hot = Ed25519SK::generate();
+        let chain = vec![
+            DelegationCert::issue(&cold, warm.to_public(), "staking", 200),
+            DelegationCert::issue(&warm, hot.to_public(), "staking/votes", 300),
+        ];
+        let cert: DelegationCert =
+            stdcode::deserialize(&stdcode::serialize(&chain[1]).unwrap()).unwrap();
+        assert_eq!(cert, chain[1]);
+        let root = cold.to_public();
+        assert_eq!(
+            DelegationCert::verify_chain(root, &chain, 100, "staking/votes/epoch5"),
+            Some(hot.to_public())
+        );
+        assert_eq!(
+            DelegationCert::verify_chain(root, &chain, 100, "staking"),
+            None
+        );
+        assert_eq!(
+            DelegationCert::verify_chain(root, &chain, 100, "staking/voters"),
+            None
+        );
+        // the warm key's delegation has expired
+        assert_eq!(
+            DelegationCert::verify_chain(root, &chain, 250, "staking/votes"),
+            None
+        );
+        // a key can't widen its own scope
+        let wider = vec![
+            chain[0].clone(),
+            DelegationCert::issue(&warm, hot.to_public(), "", 300),
+        ];
+        assert_eq!(
+            DelegationCert::verify_chain(root, &wider, 100, "transfers"),
+            None
+        );
+        assert_eq!(DelegationCert::verify_chain(root, &[], 100, ""), None);
+    }
+}
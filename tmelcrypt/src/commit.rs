@@ -0,0 +1,144 @@
+//! Commitment schemes.
+//!
+//! A [PedersenCommitment] hides a scalar value behind a random blinding factor, and binds the committer to it. Commitments are additively homomorphic: the sum of two commitments is a commitment to the sum of the values, under the sum of the blinding factors. This makes them a building block for confidential amounts, where a verifier checks that inputs and outputs balance without learning either.
+//!
+//! A [Commitment] is a salted hash commitment to arbitrary bytes, for commit-reveal protocols such as beacons and sealed-bid auctions. Unlike a bare [crate::hash_single] of the value, the random salt stops anyone from brute-forcing the value when it comes from a small domain.
+
+use std::ops::{Add, Neg, Sub};
+
+use curve25519_dalek_ng::{constants::RISTRETTO_BASEPOINT_POINT, traits::Identity};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+use crate::{fill_random, hash_keyed, random_scalar, HashVal, RistrettoPoint, Scalar};
+
+/// The second Pedersen generator, whose discrete logarithm with respect to the basepoint is unknown.
+fn blinding_generator() -> RistrettoPoint {
+    let hash: [u8; 64] = Sha512::digest(b"tmelcrypt-pedersen-blinding").into();
+    RistrettoPoint::from_uniform_bytes(&hash)
+}
+
+/// A Pedersen commitment `value * G + blinding * H` over the Ristretto group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PedersenCommitment(pub RistrettoPoint);
+
+impl PedersenCommitment {
+    /// Commits to `value` with the given blinding factor. The blinding factor must be uniformly random and secret, or the commitment doesn't hide the value.
+    pub fn commit(value: Scalar, blinding: Scalar) -> Self {
+        Self(value * RISTRETTO_BASEPOINT_POINT + blinding * blinding_generator())
+    }
+
+    /// Commits to `value` with a fresh random blinding factor, which is returned to open the commitment later.
+    pub fn commit_random(value: Scalar) -> (Self, Scalar) {
+        let blinding = random_scalar();
+        (Self::commit(value, blinding), blinding)
+    }
+
+    /// A commitment to zero with no blinding, which is the identity for addition.
+    pub fn zero() -> Self {
+        Self(RistrettoPoint::identity())
+    }
+
+    /// Checks that this commitment opens to `value` with `blinding`.
+    pub fn verify(&self, value: Scalar, blinding: Scalar) -> bool {
+        *self == Self::commit(value, blinding)
+    }
+}
+
+impl Add for PedersenCommitment {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl Sub for PedersenCommitment {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl Neg for PedersenCommitment {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self(-self.0)
+    }
+}
+
+impl std::iter::Sum for PedersenCommitment {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |a, b| a + b)
+    }
+}
+
+/// A salted hash commitment to a byte string. See [Commitment::commit].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Commitment(pub HashVal);
+
+/// What the committer reveals to open a [Commitment]: the value, and the salt that hid it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Opening {
+    #[serde(with = "stdcode::hex")]
+    pub value: Vec<u8>,
+    #[serde(with = "stdcode::hex32")]
+    pub salt: [u8; 32],
+}
+
+impl Commitment {
+    /// Commits to `value` under a fresh random salt. Publish the commitment now, and the opening when it's time to reveal.
+    pub fn commit(value: &[u8]) -> (Self, Opening) {
+        let mut salt = [0u8; 32];
+        fill_random(&mut salt);
+        let opening = Opening {
+            value: value.to_vec(),
+            salt,
+        };
+        (opening.commitment(), opening)
+    }
+
+    /// Checks that the opening reveals the committed value.
+    pub fn verify(&self, opening: &Opening) -> bool {
+        crate::ct_eq(&self.0 .0, &opening.commitment().0 .0)
+    }
+}
+
+impl Opening {
+    /// The commitment that this opening opens.
+    pub fn commitment(&self) -> Commitment {
+        let mut salted = self.salt.to_vec();
+        salted.extend_from_slice(&self.value);
+        Commitment(hash_keyed(b"tmelcrypt-commit-reveal", &salted))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pedersen_homomorphic() {
+        let (a, ra) = PedersenCommitment::commit_random(Scalar::from(30u64));
+        let (b, rb) = PedersenCommitment::commit_random(Scalar::from(12u64));
+        assert!(a.verify(Scalar::from(30u64), ra));
+        assert!(!a.verify(Scalar::from(31u64), ra));
+        assert!((a + b).verify(Scalar::from(42u64), ra + rb));
+        assert!((a - b).verify(Scalar::from(18u64), ra - rb));
+        assert_eq!(vec![a, b, -b].into_iter().sum::<PedersenCommitment>(), a);
+        assert_eq!(
+            stdcode::deserialize::<PedersenCommitment>(&stdcode::serialize(&a).unwrap()).unwrap(),
+            a
+        );
+    }
+
+    #[test]
+    fn commit_reveal() {
+        let (commitment, opening) = Commitment::commit(b"bid: 100");
+        assert!(commitment.verify(&opening));
+        let (other, _) = Commitment::commit(b"bid: 100");
+        assert_ne!(commitment, other);
+        let mut forged = opening.clone();
+        forged.value = b"bid: 200".to_vec();
+        assert!(!commitment.verify(&forged));
+    }
+}
@@ -0,0 +1,74 @@
+//! Decoding and comparison of secret material in constant time.
+//!
+//! The usual hex decoder branches on, and index tables with, every input character, so the time it takes leaks information about secret keys. The decoder here only uses arithmetic on the input, and only checks for errors at the very end. Its timing depends on nothing but the length of the input.
+
+use subtle::ConstantTimeEq;
+
+/// Compares two byte strings in constant time, for checking secrets, MACs, and derived keys without leaking how many leading bytes matched. Only the lengths are compared in variable time.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+/// Returns 0xff if `lo <= c <= hi` and 0 otherwise, without branching.
+#[inline]
+fn in_range(c: u8, lo: u8, hi: u8) -> u8 {
+    let c = c as i16;
+    // both differences are negative exactly when c is in range
+    (((lo as i16 - 1 - c) & (c - hi as i16 - 1)) >> 8) as u8
+}
+
+/// Decodes one hex digit, returning its value and 0xff if it's valid, or 0 if it's not.
+#[inline]
+fn hex_digit(c: u8) -> (u8, u8) {
+    let digit = in_range(c, b'0', b'9');
+    let lower = in_range(c, b'a', b'f');
+    let upper = in_range(c, b'A', b'F');
+    let val = (digit & c.wrapping_sub(b'0'))
+        | (lower & c.wrapping_sub(b'a' - 10))
+        | (upper & c.wrapping_sub(b'A' - 10));
+    (val, digit | lower | upper)
+}
+
+/// Decodes a hexadecimal string in constant time. Returns `None` if the string has odd length or contains non-hex characters.
+pub fn ct_hex_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.as_bytes();
+    if s.len() % 2 == 1 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(s.len() / 2);
+    let mut valid = 0xff;
+    for pair in s.chunks_exact(2) {
+        let (hi, hi_valid) = hex_digit(pair[0]);
+        let (lo, lo_valid) = hex_digit(pair[1]);
+        out.push((hi << 4) | lo);
+        valid &= hi_valid & lo_valid;
+    }
+    if valid == 0xff {
+        Some(out)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_reference() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let hexed = hex::encode(&bytes);
+        assert_eq!(ct_hex_decode(&hexed).unwrap(), bytes);
+        assert_eq!(ct_hex_decode(&hexed.to_uppercase()).unwrap(), bytes);
+        assert_eq!(ct_hex_decode("0g"), None);
+        assert_eq!(ct_hex_decode("abc"), None);
+    }
+
+    #[test]
+    fn constant_time_eq() {
+        assert!(ct_eq(b"secret", b"secret"));
+        assert!(!ct_eq(b"secret", b"secreT"));
+        assert!(!ct_eq(b"secret", b"secret!"));
+        assert!(ct_eq(b"", b""));
+    }
+}
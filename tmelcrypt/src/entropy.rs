@@ -0,0 +1,160 @@
+use std::{fmt, sync::RwLock};
+
+use rand::{rngs::OsRng, CryptoRng, RngCore};
+
+use crate::HashVal;
+
+/// A function that fills a buffer with entropy.
+pub type EntropySource = fn(&mut [u8]);
+
+static ENTROPY_SOURCE: RwLock<Option<EntropySource>> = RwLock::new(None);
+
+/// Replaces the operating system RNG with a custom entropy source for all randomness in this crate. Intended for embedded targets without an OS RNG.
+///
+/// The source must be cryptographically secure. Running [entropy_self_test] afterwards is strongly recommended.
+pub fn set_entropy_source(source: EntropySource) {
+    *ENTROPY_SOURCE.write().unwrap() = Some(source);
+}
+
+/// Fills the buffer with cryptographically secure random bytes.
+pub fn fill_random(buf: &mut [u8]) {
+    match *ENTROPY_SOURCE.read().unwrap() {
+        Some(source) => source(buf),
+        None => OsRng.fill_bytes(buf),
+    }
+}
+
+/// Generates `n` cryptographically secure random bytes.
+pub fn rand_bytes(n: usize) -> Vec<u8> {
+    let mut buf = vec![0u8; n];
+    fill_random(&mut buf);
+    buf
+}
+
+/// Generates a uniformly random HashVal.
+pub fn rand_hashval() -> HashVal {
+    let mut buf = [0u8; 32];
+    fill_random(&mut buf);
+    HashVal(buf)
+}
+
+/// A [CryptoRng] that draws from the same entropy source as [rand_bytes], for passing into APIs that take an RNG.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EntropyRng;
+
+impl RngCore for EntropyRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut buf = [0u8; 4];
+        fill_random(&mut buf);
+        u32::from_le_bytes(buf)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0u8; 8];
+        fill_random(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        fill_random(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        fill_random(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for EntropyRng {}
+
+/// A failure of the entropy self-test.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntropyError {
+    /// The entropy source returned the same block twice.
+    RepeatedOutput,
+    /// The entropy source's output has far too many or too few one bits.
+    BiasedOutput,
+}
+
+impl fmt::Display for EntropyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntropyError::RepeatedOutput => "entropy source repeated its output".fmt(f),
+            EntropyError::BiasedOutput => "entropy source output is heavily biased".fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for EntropyError {}
+
+/// Sanity-checks the entropy source. Meant to be run once at startup.
+///
+/// This catches catastrophic failures (a stuck or non-random source) using repeat detection and the FIPS 140-2 monobit test. It cannot prove that the source is actually unpredictable.
+pub fn entropy_self_test() -> Result<(), EntropyError> {
+    self_test_with(fill_random)
+}
+
+fn self_test_with(mut fill: impl FnMut(&mut [u8])) -> Result<(), EntropyError> {
+    let blocks: Vec<[u8; 32]> = (0..8)
+        .map(|_| {
+            let mut block = [0u8; 32];
+            fill(&mut block);
+            block
+        })
+        .collect();
+    for (i, a) in blocks.iter().enumerate() {
+        if blocks[i + 1..].contains(a) {
+            return Err(EntropyError::RepeatedOutput);
+        }
+    }
+    // 20000 bits, as in the FIPS 140-2 monobit test
+    let mut sample = [0u8; 2500];
+    fill(&mut sample);
+    let ones: u32 = sample.iter().map(|b| b.count_ones()).sum();
+    if !(9725..=10275).contains(&ones) {
+        return Err(EntropyError::BiasedOutput);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    // still backed by the OS RNG, so that installing it doesn't affect other tests
+    fn counting_source(buf: &mut [u8]) {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        OsRng.fill_bytes(buf)
+    }
+
+    #[test]
+    fn custom_source() {
+        set_entropy_source(counting_source);
+        let before = CALLS.load(Ordering::SeqCst);
+        assert_eq!(rand_bytes(16).len(), 16);
+        rand_hashval();
+        EntropyRng.next_u64();
+        assert!(CALLS.load(Ordering::SeqCst) >= before + 3);
+        assert_eq!(entropy_self_test(), Ok(()));
+    }
+
+    #[test]
+    fn self_test_failures() {
+        assert_eq!(
+            self_test_with(|buf| buf.fill(0x5a)),
+            Err(EntropyError::RepeatedOutput)
+        );
+        let mut counter = 0u64;
+        let biased = |buf: &mut [u8]| {
+            counter += 1;
+            buf.fill(0xff);
+            buf[..8].copy_from_slice(&counter.to_le_bytes());
+        };
+        assert_eq!(self_test_with(biased), Err(EntropyError::BiasedOutput));
+        assert_eq!(self_test_with(|buf| OsRng.fill_bytes(buf)), Ok(()));
+    }
+}
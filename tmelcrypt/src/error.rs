@@ -0,0 +1,177 @@
+use std::{convert::TryInto, fmt};
+
+use curve25519_dalek_ng::edwards::CompressedEdwardsY;
+
+use crate::{AddressParseError, Ed25519PK, Ed25519SK, HashVal, VerifyError};
+
+/// The crate-wide error type, returned by the `try_` variants of functions that otherwise return an [Option] or a [bool]. It tells malformed input apart from a signature that is merely wrong.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Some input had the wrong length.
+    InvalidLength { expected: usize, actual: usize },
+    /// Some input wasn't validly encoded, such as an address with a bad prefix or characters.
+    InvalidEncoding,
+    /// A checksum didn't match, so the input was probably mistyped.
+    BadChecksum,
+    /// A public key doesn't encode a curve point.
+    InvalidPublicKey,
+    /// A public key is a point of small order, which anyone can forge signatures for.
+    WeakKey,
+    /// A public key is a valid point, but not in its one canonical encoding.
+    NonCanonicalKey,
+    /// A well-formed signature isn't valid for the message and key.
+    BadSignature,
+    /// A secret key's bytes aren't a valid scalar for its curve, such as zero or a value not less than the group order.
+    InvalidSecretKey,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidLength { expected, actual } => {
+                write!(f, "input is {} bytes long, expected {}", actual, expected)
+            }
+            Error::InvalidEncoding => "input is not validly encoded".fmt(f),
+            Error::BadChecksum => "checksum does not match".fmt(f),
+            Error::InvalidPublicKey => "public key is not a valid ed25519 point".fmt(f),
+            Error::WeakKey => "public key is a small-order point".fmt(f),
+            Error::NonCanonicalKey => "public key is not canonically encoded".fmt(f),
+            Error::BadSignature => "signature does not match the message and key".fmt(f),
+            Error::InvalidSecretKey => "secret key is not a valid scalar".fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<VerifyError> for Error {
+    fn from(err: VerifyError) -> Self {
+        match err {
+            VerifyError::BadSignatureLength(actual) => Error::InvalidLength {
+                expected: 64,
+                actual,
+            },
+            VerifyError::InvalidPublicKey => Error::InvalidPublicKey,
+            VerifyError::Mismatch => Error::BadSignature,
+        }
+    }
+}
+
+impl From<AddressParseError> for Error {
+    fn from(err: AddressParseError) -> Self {
+        match err {
+            AddressParseError::BadPrefix | AddressParseError::InvalidBase32 => {
+                Error::InvalidEncoding
+            }
+            AddressParseError::WrongLength(actual) => Error::InvalidLength {
+                expected: 54,
+                actual,
+            },
+            AddressParseError::BadChecksum { .. } => Error::BadChecksum,
+        }
+    }
+}
+
+impl HashVal {
+    /// Like [HashVal::from_addr], but says what was wrong with the address.
+    pub fn try_from_addr(addr: &str) -> Result<Self, Error> {
+        Ok(addr.parse::<crate::Address>()?.0)
+    }
+}
+
+impl Ed25519PK {
+    /// Like [Ed25519PK::from_bytes], but says what was wrong with the input. Like it, this only checks the length; see [Ed25519PK::validate].
+    pub fn try_from_bytes(bts: &[u8]) -> Result<Self, Error> {
+        Ok(Ed25519PK(bts.try_into().map_err(|_| {
+            Error::InvalidLength {
+                expected: 32,
+                actual: bts.len(),
+            }
+        })?))
+    }
+
+    /// Checks that the key is a canonically encoded curve point, and not one of the small-order points that can't serve as a real key. Signature verification doesn't require this, but registering a key for long-term use, such as a staker's key, should.
+    pub fn validate(&self) -> Result<(), Error> {
+        let point = CompressedEdwardsY(self.0)
+            .decompress()
+            .ok_or(Error::InvalidPublicKey)?;
+        if point.is_small_order() {
+            return Err(Error::WeakKey);
+        }
+        if point.compress().0 != self.0 {
+            return Err(Error::NonCanonicalKey);
+        }
+        Ok(())
+    }
+
+    /// Whether [Ed25519PK::validate] accepts the key.
+    pub fn is_safe(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    /// Like [Ed25519PK::verify], but also rejects keys that aren't [safe](Ed25519PK::is_safe), so that adversarially chosen weak keys never verify anything.
+    pub fn verify_safe(&self, msg: &[u8], sig: &[u8]) -> bool {
+        self.is_safe() && self.verify(msg, sig)
+    }
+}
+
+impl Ed25519SK {
+    /// Like [Ed25519SK::from_bytes], but says what was wrong with the input.
+    pub fn try_from_bytes(bts: &[u8]) -> Result<Self, Error> {
+        Ok(Ed25519SK(bts.try_into().map_err(|_| {
+            Error::InvalidLength {
+                expected: 64,
+                actual: bts.len(),
+            }
+        })?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_errors() {
+        let sk = Ed25519SK::generate();
+        let pk = sk.to_public();
+        let sig = sk.sign(b"hello");
+        let try_verify = |msg: &[u8], sig: &[u8]| pk.verify_detailed(msg, sig).map_err(Error::from);
+        assert_eq!(try_verify(b"hello", &sig), Ok(()));
+        assert_eq!(try_verify(b"other", &sig), Err(Error::BadSignature));
+        assert_eq!(
+            try_verify(b"hello", &sig[..10]),
+            Err(Error::InvalidLength {
+                expected: 64,
+                actual: 10
+            })
+        );
+        assert_eq!(pk.validate(), Ok(()));
+        // the identity point
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        assert_eq!(Ed25519PK(identity).validate(), Err(Error::WeakKey));
+        assert!(!Ed25519PK(identity).verify_safe(b"hello", &sig));
+        assert!(pk.verify_safe(b"hello", &sig));
+        // y = p + 3, a non-canonical encoding of a point of large order
+        let mut noncanonical = [0xff; 32];
+        noncanonical[0] = 0xf0;
+        noncanonical[31] = 0x7f;
+        assert_eq!(
+            Ed25519PK(noncanonical).validate(),
+            Err(Error::NonCanonicalKey)
+        );
+
+        assert_eq!(
+            Ed25519SK::try_from_bytes(&[0; 3]),
+            Err(Error::InvalidLength {
+                expected: 64,
+                actual: 3
+            })
+        );
+        let addr = pk.std_address().to_addr();
+        assert_eq!(HashVal::try_from_addr(&addr), Ok(pk.std_address()));
+        assert_eq!(HashVal::try_from_addr("x"), Err(Error::InvalidEncoding));
+    }
+}
@@ -0,0 +1,565 @@
+//! FROST threshold signing for ed25519.
+//!
+//! A group of `n` participants jointly holds an ed25519 key, so that any `threshold` of them can produce a signature that verifies with the ordinary [Ed25519PK::verify]. The key is either created with a distributed key generation ([dkg_begin] and [DkgState::finish]), where no one ever learns the whole secret, or split from an existing key with [split_key], which keeps existing covenants and addresses working.
+//!
+//! Signing takes two rounds. First, every signer calls [KeyShare::commit] and broadcasts the [SigningCommitment], keeping the [SigningNonces] secret. Then every signer calls [KeyShare::sign] with the message and all the commitments, and sends the resulting [SignatureShare] to an aggregator, who combines them with [PublicKeyPackage::aggregate].
+
+use std::{collections::BTreeMap, fmt};
+
+use curve25519_dalek_ng::{
+    constants::ED25519_BASEPOINT_TABLE,
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+    traits::Identity,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use zeroize::Zeroize;
+
+use crate::{
+    expanded::ed25519_challenge, lagrange_at_zero, random_scalar, split_secret_with_coeffs,
+    Ed25519PK, Ed25519SK, SecretShare,
+};
+
+/// An error in a FROST protocol run. Errors that can be pinned on a particular participant carry their index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrostError {
+    /// Some index was zero, repeated, out of range, or missing.
+    BadIndex(u32),
+    /// Some encoded point was invalid.
+    Malformed(u32),
+    /// A participant's DKG proof of knowledge didn't verify.
+    BadProof(u32),
+    /// A participant sent a DKG share that doesn't match their commitment.
+    BadShare(u32),
+    /// A participant produced an invalid signature share.
+    BadSignatureShare(u32),
+    /// There were fewer signers than the threshold.
+    TooFewSigners,
+}
+
+impl fmt::Display for FrostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrostError::BadIndex(i) => write!(f, "bad or missing participant index {}", i),
+            FrostError::Malformed(i) => write!(f, "malformed message from participant {}", i),
+            FrostError::BadProof(i) => write!(f, "bad proof of knowledge from participant {}", i),
+            FrostError::BadShare(i) => write!(f, "bad key share from participant {}", i),
+            FrostError::BadSignatureShare(i) => {
+                write!(f, "bad signature share from participant {}", i)
+            }
+            FrostError::TooFewSigners => write!(f, "fewer signers than the threshold"),
+        }
+    }
+}
+
+impl std::error::Error for FrostError {}
+
+/// The public half of a FROST key: the group public key and every participant's verification share.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKeyPackage {
+    /// The group public key, under which aggregated signatures verify.
+    pub group_pk: Ed25519PK,
+    /// How many signers are needed.
+    pub threshold: u32,
+    /// The public key `s_i B` corresponding to each participant's secret share.
+    pub verification_shares: BTreeMap<u32, CompressedEdwardsY>,
+}
+
+/// One participant's share of a FROST key. Zeroed out on drop.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KeyShare {
+    pub index: u32,
+    secret: Scalar,
+    pub public: PublicKeyPackage,
+}
+
+impl Drop for KeyShare {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl fmt::Debug for KeyShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyShare")
+            .field("index", &self.index)
+            .field("group_pk", &self.public.group_pk)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Splits an existing ed25519 key into `n` FROST key shares, any `threshold` of which can sign under the same public key. The dealer sees the whole key, so this is meant for moving an existing key into threshold custody; use [dkg_begin] for new keys. Panics unless `0 < threshold <= n`.
+pub fn split_key(sk: &Ed25519SK, threshold: u32, n: u32) -> (PublicKeyPackage, Vec<KeyShare>) {
+    let (coeffs, shares) = split_secret_with_coeffs(sk.signing_scalar(), threshold, n);
+    let commitment: Vec<EdwardsPoint> = coeffs
+        .iter()
+        .map(|c| c * &ED25519_BASEPOINT_TABLE)
+        .collect();
+    let public = PublicKeyPackage {
+        group_pk: sk.to_public(),
+        threshold,
+        verification_shares: (1..=n)
+            .map(|i| (i, eval_commitment(&commitment, i).compress()))
+            .collect(),
+    };
+    let shares = shares
+        .into_iter()
+        .map(|share| KeyShare {
+            index: share.index,
+            secret: share.value,
+            public: public.clone(),
+        })
+        .collect();
+    (public, shares)
+}
+
+/// A participant's broadcast message in the distributed key generation: a commitment to their sharing polynomial and a proof that they know its constant term.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgCommitment {
+    pub index: u32,
+    pub coefficients: Vec<CompressedEdwardsY>,
+    pub proof_r: CompressedEdwardsY,
+    pub proof_z: Scalar,
+}
+
+/// A secret share sent privately from one DKG participant to another. Zeroed out on drop.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DkgShare {
+    pub from: u32,
+    pub to: u32,
+    pub value: Scalar,
+}
+
+impl Drop for DkgShare {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+impl fmt::Debug for DkgShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DkgShare")
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A participant's private state during the distributed key generation. Its shares are zeroed out on drop.
+pub struct DkgState {
+    index: u32,
+    threshold: u32,
+    n: u32,
+    shares: Vec<SecretShare>,
+}
+
+/// Starts the distributed key generation as participant `index` (from `1..=n`). The returned commitment must be broadcast to everyone, and [DkgState::share_for] gives the shares to send privately to each other participant. Panics unless `0 < threshold <= n` and `0 < index <= n`.
+pub fn dkg_begin(index: u32, threshold: u32, n: u32) -> (DkgState, DkgCommitment) {
+    assert!(index > 0 && index <= n, "index must be between 1 and n");
+    let secret = random_scalar();
+    let (coeffs, shares) = split_secret_with_coeffs(secret, threshold, n);
+    let coefficients: Vec<CompressedEdwardsY> = coeffs
+        .iter()
+        .map(|c| (c * &ED25519_BASEPOINT_TABLE).compress())
+        .collect();
+    let k = random_scalar();
+    let proof_r = (&k * &ED25519_BASEPOINT_TABLE).compress();
+    let c = dkg_challenge(index, &coefficients[0], &proof_r);
+    let commitment = DkgCommitment {
+        index,
+        coefficients,
+        proof_r,
+        proof_z: k + secret * c,
+    };
+    let state = DkgState {
+        index,
+        threshold,
+        n,
+        shares,
+    };
+    (state, commitment)
+}
+
+impl DkgState {
+    /// The share to send privately to participant `to`.
+    pub fn share_for(&self, to: u32) -> Option<DkgShare> {
+        let share = self.shares.iter().find(|s| s.index == to)?;
+        Some(DkgShare {
+            from: self.index,
+            to,
+            value: share.value,
+        })
+    }
+
+    /// Finishes the key generation, given every participant's commitment and the shares sent to us by every other participant.
+    pub fn finish(
+        self,
+        commitments: &[DkgCommitment],
+        shares: &[DkgShare],
+    ) -> Result<KeyShare, FrostError> {
+        let mut polys: BTreeMap<u32, Vec<EdwardsPoint>> = BTreeMap::new();
+        for cmt in commitments {
+            if cmt.index == 0 || cmt.index > self.n || polys.contains_key(&cmt.index) {
+                return Err(FrostError::BadIndex(cmt.index));
+            }
+            if cmt.coefficients.len() != self.threshold as usize {
+                return Err(FrostError::Malformed(cmt.index));
+            }
+            let poly = cmt
+                .coefficients
+                .iter()
+                .map(|c| c.decompress())
+                .collect::<Option<Vec<_>>>()
+                .ok_or(FrostError::Malformed(cmt.index))?;
+            let proof_r = cmt
+                .proof_r
+                .decompress()
+                .ok_or(FrostError::Malformed(cmt.index))?;
+            let c = dkg_challenge(cmt.index, &cmt.coefficients[0], &cmt.proof_r);
+            if &cmt.proof_z * &ED25519_BASEPOINT_TABLE != proof_r + c * poly[0] {
+                return Err(FrostError::BadProof(cmt.index));
+            }
+            polys.insert(cmt.index, poly);
+        }
+        if let Some(missing) = (1..=self.n).find(|i| !polys.contains_key(i)) {
+            return Err(FrostError::BadIndex(missing));
+        }
+
+        let mut secret = self.share_for(self.index).unwrap().value;
+        let mut received = vec![self.index];
+        for share in shares
+            .iter()
+            .filter(|s| s.to == self.index && s.from != self.index)
+        {
+            if received.contains(&share.from) {
+                return Err(FrostError::BadIndex(share.from));
+            }
+            let poly = polys
+                .get(&share.from)
+                .ok_or(FrostError::BadIndex(share.from))?;
+            if &share.value * &ED25519_BASEPOINT_TABLE != eval_commitment(poly, self.index) {
+                return Err(FrostError::BadShare(share.from));
+            }
+            secret += share.value;
+            received.push(share.from);
+        }
+        if let Some(missing) = (1..=self.n).find(|i| !received.contains(i)) {
+            return Err(FrostError::BadIndex(missing));
+        }
+
+        let group_pk = polys
+            .values()
+            .fold(EdwardsPoint::identity(), |acc, poly| acc + poly[0]);
+        let verification_shares = (1..=self.n)
+            .map(|i| {
+                let point = polys.values().fold(EdwardsPoint::identity(), |acc, poly| {
+                    acc + eval_commitment(poly, i)
+                });
+                (i, point.compress())
+            })
+            .collect();
+        Ok(KeyShare {
+            index: self.index,
+            secret,
+            public: PublicKeyPackage {
+                group_pk: Ed25519PK(group_pk.compress().to_bytes()),
+                threshold: self.threshold,
+                verification_shares,
+            },
+        })
+    }
+}
+
+/// The secret nonces for one signing session. They are consumed by [KeyShare::sign], since reusing them leaks the key share.
+pub struct SigningNonces {
+    index: u32,
+    hiding: Scalar,
+    binding: Scalar,
+}
+
+impl Drop for SigningNonces {
+    fn drop(&mut self) {
+        self.hiding.zeroize();
+        self.binding.zeroize();
+    }
+}
+
+/// A signer's public commitment to their [SigningNonces], broadcast in the first round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningCommitment {
+    pub index: u32,
+    pub hiding: CompressedEdwardsY,
+    pub binding: CompressedEdwardsY,
+}
+
+/// A signer's share of the final signature, produced in the second round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureShare {
+    pub index: u32,
+    pub value: Scalar,
+}
+
+impl KeyShare {
+    /// Generates fresh nonces for a signing session, along with the commitment to broadcast.
+    pub fn commit(&self) -> (SigningNonces, SigningCommitment) {
+        let hiding = random_scalar();
+        let binding = random_scalar();
+        let commitment = SigningCommitment {
+            index: self.index,
+            hiding: (&hiding * &ED25519_BASEPOINT_TABLE).compress(),
+            binding: (&binding * &ED25519_BASEPOINT_TABLE).compress(),
+        };
+        (
+            SigningNonces {
+                index: self.index,
+                hiding,
+                binding,
+            },
+            commitment,
+        )
+    }
+
+    /// Produces this signer's share of a signature on `msg`, given the commitments of every signer in the session (including our own).
+    pub fn sign(
+        &self,
+        nonces: SigningNonces,
+        msg: &[u8],
+        commitments: &[SigningCommitment],
+    ) -> Result<SignatureShare, FrostError> {
+        if nonces.index != self.index {
+            return Err(FrostError::BadIndex(nonces.index));
+        }
+        let session = Session::new(&self.public, msg, commitments)?;
+        let rho = session
+            .binding_factor(self.index)
+            .ok_or(FrostError::BadIndex(self.index))?;
+        let lambda = lagrange_at_zero(&session.signers(), self.index)
+            .ok_or(FrostError::BadIndex(self.index))?;
+        Ok(SignatureShare {
+            index: self.index,
+            value: nonces.hiding + nonces.binding * rho + lambda * self.secret * session.challenge,
+        })
+    }
+}
+
+impl PublicKeyPackage {
+    /// Checks every signature share and combines them into an ordinary 64-byte ed25519 signature. If a share is invalid, the error says whose.
+    pub fn aggregate(
+        &self,
+        msg: &[u8],
+        commitments: &[SigningCommitment],
+        shares: &[SignatureShare],
+    ) -> Result<Vec<u8>, FrostError> {
+        let session = Session::new(self, msg, commitments)?;
+        let signers = session.signers();
+        let mut received = Vec::with_capacity(signers.len());
+        let mut z = Scalar::zero();
+        for share in shares {
+            if received.contains(&share.index) {
+                return Err(FrostError::BadIndex(share.index));
+            }
+            let (_, hiding, binding, rho) = session
+                .nonces
+                .iter()
+                .find(|n| n.0 == share.index)
+                .ok_or(FrostError::BadIndex(share.index))?;
+            let verification_share = self
+                .verification_shares
+                .get(&share.index)
+                .and_then(|p| p.decompress())
+                .ok_or(FrostError::BadIndex(share.index))?;
+            let lambda =
+                lagrange_at_zero(&signers, share.index).ok_or(FrostError::BadIndex(share.index))?;
+            if &share.value * &ED25519_BASEPOINT_TABLE
+                != hiding + binding * rho + verification_share * (lambda * session.challenge)
+            {
+                return Err(FrostError::BadSignatureShare(share.index));
+            }
+            z += share.value;
+            received.push(share.index);
+        }
+        if let Some(missing) = signers.iter().find(|i| !received.contains(i)) {
+            return Err(FrostError::BadIndex(*missing));
+        }
+        let mut sig = session.group_commitment.compress().to_bytes().to_vec();
+        sig.extend_from_slice(z.as_bytes());
+        Ok(sig)
+    }
+}
+
+/// Everything about a signing session that signers and the aggregator compute identically from the public inputs.
+struct Session {
+    nonces: Vec<(u32, EdwardsPoint, EdwardsPoint, Scalar)>,
+    group_commitment: EdwardsPoint,
+    challenge: Scalar,
+}
+
+impl Session {
+    fn new(
+        public: &PublicKeyPackage,
+        msg: &[u8],
+        commitments: &[SigningCommitment],
+    ) -> Result<Self, FrostError> {
+        let mut sorted = commitments.to_vec();
+        sorted.sort_unstable_by_key(|c| c.index);
+        for pair in sorted.windows(2) {
+            if pair[0].index == pair[1].index {
+                return Err(FrostError::BadIndex(pair[0].index));
+            }
+        }
+        if sorted.len() < public.threshold as usize {
+            return Err(FrostError::TooFewSigners);
+        }
+        let mut hasher = Sha512::new()
+            .chain_update(b"tmelcrypt-frost-rho")
+            .chain_update(public.group_pk.0)
+            .chain_update(Sha512::digest(msg));
+        for cmt in sorted.iter() {
+            if !public.verification_shares.contains_key(&cmt.index) {
+                return Err(FrostError::BadIndex(cmt.index));
+            }
+            hasher.update(cmt.index.to_le_bytes());
+            hasher.update(cmt.hiding.as_bytes());
+            hasher.update(cmt.binding.as_bytes());
+        }
+        let mut nonces = Vec::with_capacity(sorted.len());
+        let mut group_commitment = EdwardsPoint::identity();
+        for cmt in sorted.iter() {
+            let hiding = cmt
+                .hiding
+                .decompress()
+                .ok_or(FrostError::Malformed(cmt.index))?;
+            let binding = cmt
+                .binding
+                .decompress()
+                .ok_or(FrostError::Malformed(cmt.index))?;
+            let rho: [u8; 64] = hasher
+                .clone()
+                .chain_update(cmt.index.to_le_bytes())
+                .finalize()
+                .into();
+            let rho = Scalar::from_bytes_mod_order_wide(&rho);
+            group_commitment += hiding + binding * rho;
+            nonces.push((cmt.index, hiding, binding, rho));
+        }
+        let challenge = ed25519_challenge(
+            group_commitment.compress().as_bytes(),
+            &public.group_pk.0,
+            msg,
+        );
+        Ok(Self {
+            nonces,
+            group_commitment,
+            challenge,
+        })
+    }
+
+    fn signers(&self) -> Vec<u32> {
+        self.nonces.iter().map(|n| n.0).collect()
+    }
+
+    fn binding_factor(&self, index: u32) -> Option<Scalar> {
+        self.nonces.iter().find(|n| n.0 == index).map(|n| n.3)
+    }
+}
+
+fn dkg_challenge(index: u32, c0: &CompressedEdwardsY, r: &CompressedEdwardsY) -> Scalar {
+    let hash: [u8; 64] = Sha512::new()
+        .chain_update(b"tmelcrypt-frost-dkg")
+        .chain_update(index.to_le_bytes())
+        .chain_update(c0.as_bytes())
+        .chain_update(r.as_bytes())
+        .finalize()
+        .into();
+    Scalar::from_bytes_mod_order_wide(&hash)
+}
+
+/// Evaluates a committed polynomial at `x`, "in the exponent".
+fn eval_commitment(poly: &[EdwardsPoint], x: u32) -> EdwardsPoint {
+    let x = Scalar::from(x);
+    poly.iter()
+        .rev()
+        .fold(EdwardsPoint::identity(), |acc, coeff| acc * x + coeff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign_with(shares: &[&KeyShare], public: &PublicKeyPackage, msg: &[u8]) -> Vec<u8> {
+        let (nonces, commitments): (Vec<_>, Vec<_>) = shares.iter().map(|s| s.commit()).unzip();
+        let sig_shares: Vec<SignatureShare> = shares
+            .iter()
+            .zip(nonces)
+            .map(|(s, nonces)| s.sign(nonces, msg, &commitments).unwrap())
+            .collect();
+        public.aggregate(msg, &commitments, &sig_shares).unwrap()
+    }
+
+    #[test]
+    fn frost_dkg_and_sign() {
+        let (states, commitments): (Vec<_>, Vec<_>) = (1..=3).map(|i| dkg_begin(i, 2, 3)).unzip();
+        let dkg_shares: Vec<DkgShare> = states
+            .iter()
+            .flat_map(|s| (1..=3).filter_map(move |to| s.share_for(to)))
+            .collect();
+        let value = hex::encode(dkg_shares[0].value.as_bytes());
+        assert!(!format!("{:?}", dkg_shares[0]).contains(&value[..10]));
+        let keys: Vec<KeyShare> = states
+            .into_iter()
+            .map(|s| s.finish(&commitments, &dkg_shares).unwrap())
+            .collect();
+        let public = keys[0].public.clone();
+        assert!(keys.iter().all(|k| k.public == public));
+
+        let sig = sign_with(&[&keys[0], &keys[2]], &public, b"withdraw");
+        assert!(public.group_pk.verify(b"withdraw", &sig));
+        assert!(!public.group_pk.verify(b"other", &sig));
+
+        // an existing key keeps its public key
+        let sk = Ed25519SK::generate();
+        let (public, keys) = split_key(&sk, 2, 3);
+        assert_eq!(public.group_pk, sk.to_public());
+        let sig = sign_with(&[&keys[1], &keys[2]], &public, b"withdraw");
+        assert!(sk.to_public().verify(b"withdraw", &sig));
+    }
+
+    #[test]
+    fn frost_aggregate_rejects_bad_indices() {
+        let (public, keys) = split_key(&Ed25519SK::generate(), 2, 3);
+        let (nonces, commitments): (Vec<_>, Vec<_>) = keys[..2].iter().map(|k| k.commit()).unzip();
+        let sig_shares: Vec<SignatureShare> = keys[..2]
+            .iter()
+            .zip(nonces)
+            .map(|(k, nonces)| k.sign(nonces, b"withdraw", &commitments).unwrap())
+            .collect();
+        assert!(public
+            .aggregate(b"withdraw", &commitments, &sig_shares)
+            .is_ok());
+
+        // the same signer twice, in place of another
+        let dup = [sig_shares[0], sig_shares[0]];
+        assert_eq!(
+            public.aggregate(b"withdraw", &commitments, &dup),
+            Err(FrostError::BadIndex(sig_shares[0].index))
+        );
+        // a signer who didn't commit
+        let unknown = [
+            sig_shares[0],
+            SignatureShare {
+                index: 3,
+                value: sig_shares[1].value,
+            },
+        ];
+        assert_eq!(
+            public.aggregate(b"withdraw", &commitments, &unknown),
+            Err(FrostError::BadIndex(3))
+        );
+        // a missing signer
+        assert_eq!(
+            public.aggregate(b"withdraw", &commitments, &sig_shares[..1]),
+            Err(FrostError::BadIndex(sig_shares[1].index))
+        );
+    }
+}
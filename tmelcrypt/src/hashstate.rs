@@ -0,0 +1,108 @@
+use std::io;
+
+use crate::{hash_single, HashVal};
+
+/// An incremental hasher, for hashing large files or streamed data without first buffering all of it.
+///
+/// Feeding data in pieces gives the same result as hashing it all at once: a [HashState::new] hasher agrees with [crate::hash_single], and a [HashState::new_keyed] hasher agrees with [crate::hash_keyed] with the same key. It also implements [io::Write], so [io::copy] can feed it from any reader.
+#[derive(Clone, Default)]
+pub struct HashState {
+    inner: blake3::Hasher,
+}
+
+impl HashState {
+    /// Creates a hasher equivalent to [crate::hash_single].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a hasher equivalent to [crate::hash_keyed] with the given key.
+    pub fn new_keyed(key: impl AsRef<[u8]>) -> Self {
+        Self {
+            inner: blake3::Hasher::new_keyed(&hash_single(key).0),
+        }
+    }
+
+    /// Adds more data to the hash.
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        self.inner.update(data);
+        self
+    }
+
+    /// Returns the hash of all the data so far. More data can still be added afterwards.
+    pub fn finalize(&self) -> HashVal {
+        HashVal(*self.inner.finalize().as_bytes())
+    }
+
+    /// Returns a reader producing an unlimited stream of output derived from all the data so far, using blake3's extendable-output mode. The first 32 bytes are the same as [HashState::finalize].
+    pub fn finalize_xof(&self) -> HashXof {
+        HashXof {
+            inner: self.inner.finalize_xof(),
+        }
+    }
+}
+
+/// An unlimited stream of hash output, returned by [HashState::finalize_xof].
+#[derive(Clone)]
+pub struct HashXof {
+    inner: blake3::OutputReader,
+}
+
+impl HashXof {
+    /// Fills the buffer with the next bytes of output.
+    pub fn fill(&mut self, buf: &mut [u8]) {
+        self.inner.fill(buf)
+    }
+}
+
+impl io::Read for HashXof {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.fill(buf);
+        Ok(buf.len())
+    }
+}
+
+/// Derives `out_len` bytes from a value and a key, for when 32 bytes of [crate::hash_keyed] aren't enough. The first 32 bytes agree with [crate::hash_keyed], and asking for more output only extends it.
+pub fn hash_xof(key: impl AsRef<[u8]>, val: impl AsRef<[u8]>, out_len: usize) -> Vec<u8> {
+    let mut out = vec![0u8; out_len];
+    HashState::new_keyed(key)
+        .update(val.as_ref())
+        .finalize_xof()
+        .fill(&mut out);
+    out
+}
+
+impl io::Write for HashState {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash_keyed;
+
+    #[test]
+    fn incremental_matches_oneshot() {
+        let data: Vec<u8> = (0..10000u32).map(|i| i as u8).collect();
+        let mut state = HashState::new();
+        for chunk in data.chunks(777) {
+            state.update(chunk);
+        }
+        assert_eq!(state.finalize(), hash_single(&data));
+
+        let mut keyed = HashState::new_keyed(b"key");
+        io::copy(&mut &data[..], &mut keyed).unwrap();
+        assert_eq!(keyed.finalize(), hash_keyed(b"key", &data));
+
+        let long = hash_xof(b"key", &data, 100);
+        assert_eq!(long[..32], hash_keyed(b"key", &data).0);
+        assert_eq!(long[..50], hash_xof(b"key", &data, 50)[..]);
+    }
+}
@@ -0,0 +1,214 @@
+use std::{fmt, str::FromStr};
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha512;
+use zeroize::Zeroize;
+
+use crate::Ed25519SK;
+
+/// The bit set in the index of hardened children. SLIP-0010 only defines hardened derivation for ed25519.
+pub const HARDENED: u32 = 0x8000_0000;
+
+/// A SLIP-0010 extended ed25519 key: a secret key along with the chain code needed to derive its children.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExtendedKey {
+    #[serde(with = "stdcode::hex32")]
+    pub secret: [u8; 32],
+    #[serde(with = "stdcode::hex32")]
+    pub chain_code: [u8; 32],
+    /// How many derivations away from the master key this key is.
+    pub depth: u8,
+    /// The index this key was derived with, or 0 for the master key.
+    pub child_index: u32,
+}
+
+impl ExtendedKey {
+    /// Derives the master key from a seed, such as a BIP39 seed from [crate::mnemonic::phrase_to_seed].
+    pub fn master(seed: &[u8]) -> Self {
+        let (secret, chain_code) = hmac_split(b"ed25519 seed", &[seed]);
+        Self {
+            secret,
+            chain_code,
+            depth: 0,
+            child_index: 0,
+        }
+    }
+
+    /// Derives a hardened child. The [HARDENED] bit is set on `index` if it isn't already.
+    pub fn derive_child(&self, index: u32) -> Self {
+        let index = index | HARDENED;
+        let (secret, chain_code) = hmac_split(
+            &self.chain_code,
+            &[&[0], &self.secret, &index.to_be_bytes()],
+        );
+        Self {
+            secret,
+            chain_code,
+            depth: self.depth.saturating_add(1),
+            child_index: index,
+        }
+    }
+
+    /// Derives the key at a path relative to this key.
+    pub fn derive(&self, path: &DerivationPath) -> Self {
+        path.0
+            .iter()
+            .fold(self.clone(), |key, index| key.derive_child(*index))
+    }
+
+    pub fn secret_key(&self) -> Ed25519SK {
+        Ed25519SK::from_seed(self.secret)
+    }
+}
+
+impl Drop for ExtendedKey {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+        self.chain_code.zeroize();
+    }
+}
+
+impl PartialEq for ExtendedKey {
+    fn eq(&self, other: &Self) -> bool {
+        crate::ct_eq(&self.secret, &other.secret)
+            & crate::ct_eq(&self.chain_code, &other.chain_code)
+            & (self.depth == other.depth)
+            & (self.child_index == other.child_index)
+    }
+}
+
+impl Eq for ExtendedKey {}
+
+impl fmt::Debug for ExtendedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtendedKey")
+            .field("public", &self.secret_key().to_public())
+            .field("depth", &self.depth)
+            .field("child_index", &self.child_index)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Computes HMAC-SHA512 over the concatenation of `data`, split into its left and right halves.
+fn hmac_split(key: &[u8], data: &[&[u8]]) -> ([u8; 32], [u8; 32]) {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC takes any key");
+    for d in data {
+        mac.update(d);
+    }
+    let out = mac.finalize().into_bytes();
+    let mut left = [0u8; 32];
+    let mut right = [0u8; 32];
+    left.copy_from_slice(&out[..32]);
+    right.copy_from_slice(&out[32..]);
+    (left, right)
+}
+
+/// A SLIP-0010 derivation path like `m/44'/990'/0'`, with every index hardened. Hardened indices may be written with either `'` or `h`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DerivationPath(pub Vec<u32>);
+
+/// Error returned when parsing an invalid [DerivationPath].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DerivationPathError {
+    /// The path doesn't start with `m`.
+    MissingMaster,
+    /// A path component isn't a valid index.
+    InvalidIndex(String),
+    /// A path component isn't hardened. ed25519 only supports hardened derivation.
+    NotHardened(String),
+}
+
+impl fmt::Display for DerivationPathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DerivationPathError::MissingMaster => "derivation path must start with m".fmt(f),
+            DerivationPathError::InvalidIndex(s) => write!(f, "invalid path component {:?}", s),
+            DerivationPathError::NotHardened(s) => write!(
+                f,
+                "path component {:?} is not hardened, which ed25519 does not support",
+                s
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DerivationPathError {}
+
+impl FromStr for DerivationPath {
+    type Err = DerivationPathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut components = s.split('/');
+        if components.next() != Some("m") {
+            return Err(DerivationPathError::MissingMaster);
+        }
+        components
+            .map(|c| {
+                let index = c
+                    .strip_suffix('\'')
+                    .or_else(|| c.strip_suffix('h'))
+                    .ok_or_else(|| DerivationPathError::NotHardened(c.to_string()))?;
+                let index: u32 = index
+                    .parse()
+                    .map_err(|_| DerivationPathError::InvalidIndex(c.to_string()))?;
+                if index >= HARDENED {
+                    return Err(DerivationPathError::InvalidIndex(c.to_string()));
+                }
+                Ok(index | HARDENED)
+            })
+            .collect::<Result<_, _>>()
+            .map(DerivationPath)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "m".fmt(f)?;
+        for index in self.0.iter() {
+            write!(f, "/{}'", index & !HARDENED)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ed25519SK {
+    /// Derives a child key with SLIP-0010, using this key's 32-byte seed as the SLIP-0010 seed.
+    ///
+    /// To derive keys compatible with other BIP39 wallets, start from [ExtendedKey::master] of the BIP39 seed instead.
+    pub fn derive_path(&self, path: &str) -> Result<Ed25519SK, DerivationPathError> {
+        let path: DerivationPath = path.parse()?;
+        Ok(ExtendedKey::master(&self.0[..32])
+            .derive(&path)
+            .secret_key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slip10_vector() {
+        // SLIP-0010 test vector 1 for ed25519
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = ExtendedKey::master(&seed);
+        assert_eq!(
+            hex::encode(master.chain_code),
+            "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb"
+        );
+        assert_eq!(
+            hex::encode(master.secret),
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7"
+        );
+        let path: DerivationPath = "m/0'/1'/2h".parse().unwrap();
+        assert_eq!(path.to_string(), "m/0'/1'/2'");
+        let child = master.derive(&path);
+        assert_eq!(
+            hex::encode(child.secret),
+            "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9"
+        );
+        assert_eq!(child.depth, 3);
+        assert!("m/0".parse::<DerivationPath>().is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use std::fmt;
+
+use zeroize::Zeroize;
+
+use crate::{hash_keyed, HashVal};
+
+/// A pseudorandom key produced by [hkdf_extract], from which any number of labeled keys can be expanded.
+///
+/// This follows the extract-then-expand structure of HKDF (RFC 5869), with blake3's keyed mode in place of HMAC. It is zeroed out on drop, and deliberately not [Copy].
+#[derive(Clone, PartialEq, Eq)]
+pub struct Prk([u8; 32]);
+
+/// Extracts a uniformly random [Prk] from input keying material that may not be, such as a Diffie-Hellman shared secret. The salt should be a protocol-specific constant or a public random value; it may be empty.
+pub fn hkdf_extract(salt: &[u8], ikm: &[u8]) -> Prk {
+    Prk(hash_keyed(salt, ikm).0)
+}
+
+impl Prk {
+    /// Fills `out` with key material bound to `label`. Different labels give independent outputs, so each key derived from the same [Prk] should have its own label, such as `"client-to-server"`. Panics if `out` is longer than 8160 bytes.
+    pub fn expand(&self, label: &str, out: &mut [u8]) {
+        assert!(out.len() <= 255 * 32, "HKDF output too long");
+        let mut block: Vec<u8> = Vec::new();
+        for (i, chunk) in out.chunks_mut(32).enumerate() {
+            block.extend_from_slice(label.as_bytes());
+            block.push(i as u8 + 1);
+            let next = blake3::keyed_hash(&self.0, &block);
+            chunk.copy_from_slice(&next.as_bytes()[..chunk.len()]);
+            block.zeroize();
+            block.extend_from_slice(next.as_bytes());
+        }
+        block.zeroize();
+    }
+
+    /// Expands a single 32-byte key bound to `label`.
+    pub fn expand_key(&self, label: &str) -> HashVal {
+        let mut out = [0u8; 32];
+        self.expand(label, &mut out);
+        HashVal(out)
+    }
+}
+
+impl Drop for Prk {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for Prk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "Prk(<redacted>)".fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hkdf_labels() {
+        let prk = hkdf_extract(b"themelio-session", b"shared secret");
+        assert_eq!(prk, hkdf_extract(b"themelio-session", b"shared secret"));
+        assert_ne!(prk, hkdf_extract(b"other", b"shared secret"));
+
+        let send = prk.expand_key("client-to-server");
+        assert_ne!(send, prk.expand_key("server-to-client"));
+        let mut long = [0u8; 100];
+        prk.expand("client-to-server", &mut long);
+        assert_eq!(&long[..32], &send.0[..]);
+        assert_ne!(&long[32..64], &long[..32]);
+    }
+}
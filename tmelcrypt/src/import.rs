@@ -0,0 +1,137 @@
+use std::{convert::TryInto, fmt};
+
+use base64::Engine;
+use ed25519_consensus::SigningKey;
+use serde::Deserialize;
+
+use crate::{hash_keyed, Ed25519PK, Ed25519SK, HashVal};
+
+/// Errors encountered when importing a key from a foreign format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyImportError {
+    /// The key material has the wrong length.
+    WrongLength { expected: usize, actual: usize },
+    /// The public key stored alongside the secret key doesn't correspond to it. This usually means that the bytes are in some other layout.
+    PublicKeyMismatch,
+    /// The key file is malformed.
+    Malformed(String),
+    /// The key file holds a key of some other type than ed25519.
+    UnsupportedKeyType(String),
+    /// The key file is encrypted, and the passphrase is missing or wrong.
+    BadPassphrase,
+}
+
+impl fmt::Display for KeyImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyImportError::WrongLength { expected, actual } => write!(
+                f,
+                "key material is {} bytes long, expected {}",
+                actual, expected
+            ),
+            KeyImportError::PublicKeyMismatch => {
+                "public key does not correspond to the secret key".fmt(f)
+            }
+            KeyImportError::Malformed(msg) => write!(f, "malformed key file: {}", msg),
+            KeyImportError::UnsupportedKeyType(ty) => write!(f, "unsupported key type {}", ty),
+            KeyImportError::BadPassphrase => "missing or wrong passphrase".fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for KeyImportError {}
+
+#[derive(Deserialize)]
+struct TendermintKeyFile {
+    pub_key: TendermintKey,
+    priv_key: TendermintKey,
+}
+
+#[derive(Deserialize)]
+struct TendermintKey {
+    #[serde(rename = "type")]
+    ty: String,
+    value: String,
+}
+
+impl Ed25519SK {
+    /// Creates a secret key from a 32-byte ed25519 seed, which is what ed25519-dalek, ed25519-consensus, and RFC 8032 call the secret key.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self::from_signing_key(SigningKey::from(seed))
+    }
+
+    /// Deterministically derives a secret key from a hash, such as the hash of a name, for reproducible keys in tests and simulations. The hash goes through a keyed hash first, so the key differs from `from_seed(h.0)`.
+    pub fn derive_from_hashval(h: HashVal) -> Self {
+        Self::from_seed(hash_keyed(b"tmelcrypt-derive-sk", h).0)
+    }
+
+    /// Imports a key in libsodium's 64-byte layout, which is the seed followed by the public key. The public key is checked against the seed.
+    pub fn from_libsodium(bts: &[u8]) -> Result<Self, KeyImportError> {
+        let bts: [u8; 64] = bts.try_into().map_err(|_| KeyImportError::WrongLength {
+            expected: 64,
+            actual: bts.len(),
+        })?;
+        let sk = Self::from_seed(bts[..32].try_into().unwrap());
+        if sk.0 != bts {
+            return Err(KeyImportError::PublicKeyMismatch);
+        }
+        Ok(sk)
+    }
+
+    /// Imports a key from the contents of a Tendermint/CometBFT `priv_validator_key.json` file, as used by Cosmos validators. The embedded public key is checked against the secret key.
+    pub fn from_tendermint_json(json: &str) -> Result<Self, KeyImportError> {
+        let file: TendermintKeyFile =
+            serde_json::from_str(json).map_err(|e| KeyImportError::Malformed(e.to_string()))?;
+        for key in [&file.pub_key, &file.priv_key] {
+            if !key.ty.ends_with("Ed25519") {
+                return Err(KeyImportError::UnsupportedKeyType(key.ty.clone()));
+            }
+        }
+        let decode = |s: &str| {
+            base64::engine::general_purpose::STANDARD
+                .decode(s)
+                .map_err(|e| KeyImportError::Malformed(e.to_string()))
+        };
+        // Tendermint uses the same layout as libsodium
+        let sk = Self::from_libsodium(&decode(&file.priv_key.value)?)?;
+        let pk = Ed25519PK::from_bytes(&decode(&file.pub_key.value)?).ok_or(
+            KeyImportError::Malformed("public key has the wrong length".into()),
+        )?;
+        if sk.to_public() != pk {
+            return Err(KeyImportError::PublicKeyMismatch);
+        }
+        Ok(sk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_foreign() {
+        let sk = Ed25519SK::from_seed([7; 32]);
+        assert_eq!(sk, Ed25519SK::from_seed([7; 32]));
+        let alice = Ed25519SK::derive_from_hashval(crate::hash_single(b"alice"));
+        assert_eq!(
+            alice,
+            Ed25519SK::derive_from_hashval(crate::hash_single(b"alice"))
+        );
+        assert_ne!(alice, Ed25519SK::from_seed(crate::hash_single(b"alice").0));
+        assert_eq!(Ed25519SK::from_libsodium(&sk.0), Ok(sk.clone()));
+        let mut swapped = sk.0;
+        swapped.rotate_left(32);
+        assert_eq!(
+            Ed25519SK::from_libsodium(&swapped),
+            Err(KeyImportError::PublicKeyMismatch)
+        );
+
+        let b64 = |b: &[u8]| base64::engine::general_purpose::STANDARD.encode(b);
+        let json = format!(
+            r#"{{"address":"00","pub_key":{{"type":"tendermint/PubKeyEd25519","value":"{}"}},"priv_key":{{"type":"tendermint/PrivKeyEd25519","value":"{}"}}}}"#,
+            b64(&sk.to_public().0),
+            b64(&sk.0)
+        );
+        assert_eq!(Ed25519SK::from_tendermint_json(&json), Ok(sk));
+    }
+}
@@ -0,0 +1,127 @@
+//! Password-based key derivation, for encrypting secrets such as wallet keys with a user's password.
+//!
+//! Both functions are deliberately slow and memory-hungry, so that guessing passwords is expensive. [argon2id] should be preferred for new data; [scrypt] is there for compatibility with existing keystores. The parameters used must be stored alongside whatever the key encrypts, since the same password with different parameters gives a different key; both parameter types are serializable for that purpose.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{fill_random, HashVal};
+
+/// Error returned when key derivation parameters are out of range.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KdfError(String);
+
+impl fmt::Display for KdfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid key derivation parameters: {}", self.0)
+    }
+}
+
+impl std::error::Error for KdfError {}
+
+/// Parameters for [argon2id].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Argon2Params {
+    /// Memory use, in KiB.
+    pub memory_kib: u32,
+    /// Number of passes over the memory.
+    pub iterations: u32,
+    /// Number of lanes that can be computed in parallel.
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    /// 64 MiB and 3 passes, which takes a fraction of a second on a typical machine.
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+/// Parameters for [scrypt].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScryptParams {
+    /// The base-2 logarithm of the CPU/memory cost `N`.
+    pub log_n: u8,
+    /// The block size `r`.
+    pub r: u32,
+    /// The parallelism `p`.
+    pub p: u32,
+}
+
+impl Default for ScryptParams {
+    /// `N = 2^15`, `r = 8`, `p = 1`, which uses 32 MiB.
+    fn default() -> Self {
+        Self {
+            log_n: 15,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+/// Generates a random 16-byte salt. Every password should be stretched with its own salt.
+pub fn random_salt() -> [u8; 16] {
+    let mut salt = [0u8; 16];
+    fill_random(&mut salt);
+    salt
+}
+
+/// Derives a 32-byte key from a password and salt with Argon2id. The salt must be at least 8 bytes.
+pub fn argon2id(password: &[u8], salt: &[u8], params: Argon2Params) -> Result<HashVal, KdfError> {
+    let params = argon2::Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(32),
+    )
+    .map_err(|e| KdfError(e.to_string()))?;
+    let mut out = [0u8; 32];
+    argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+        .hash_password_into(password, salt, &mut out)
+        .map_err(|e| KdfError(e.to_string()))?;
+    Ok(HashVal(out))
+}
+
+/// Derives a 32-byte key from a password and salt with scrypt.
+pub fn scrypt(password: &[u8], salt: &[u8], params: ScryptParams) -> Result<HashVal, KdfError> {
+    let params = scrypt::Params::new(params.log_n, params.r, params.p, 32)
+        .map_err(|e| KdfError(e.to_string()))?;
+    let mut out = [0u8; 32];
+    scrypt::scrypt(password, salt, &params, &mut out).map_err(|e| KdfError(e.to_string()))?;
+    Ok(HashVal(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kdf() {
+        // first half of the third test vector from RFC 7914
+        let params = ScryptParams {
+            log_n: 10,
+            r: 8,
+            p: 16,
+        };
+        assert_eq!(
+            scrypt(b"password", b"NaCl", params).unwrap().to_string(),
+            "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162"
+        );
+
+        let params = Argon2Params {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+        let salt = random_salt();
+        let key = argon2id(b"hunter2", &salt, params).unwrap();
+        assert_eq!(argon2id(b"hunter2", &salt, params).unwrap(), key);
+        assert_ne!(argon2id(b"hunter3", &salt, params).unwrap(), key);
+        assert!(argon2id(b"hunter2", b"short", params).is_err());
+    }
+}
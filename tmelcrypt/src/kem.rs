@@ -0,0 +1,218 @@
+//! Post-quantum key encapsulation, using ML-KEM-768 (Kyber, FIPS 203).
+//!
+//! Encapsulating to a [KemPK] produces a fresh 32-byte shared secret along with a [KemCiphertext] that only the holder of the matching [KemSK] can turn back into the same secret. The shared secret is returned as an [AeadKey], so the two modules together give a complete sealed channel: see [KemPK::seal] and [KemSK::open]. Hybrid channels can instead feed the secret's bytes into [crate::hkdf_extract] together with a classical Diffie-Hellman secret.
+//!
+//! Public keys are 1184 bytes and ciphertexts 1088 bytes. Secret keys are stored as their 64-byte seed, and expanded when decapsulating.
+
+use std::{convert::TryInto, fmt};
+
+use fips203::{
+    ml_kem_768,
+    traits::{Decaps, Encaps, KeyGen, SerDes},
+};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::{ct_eq, fill_random, AeadKey, EntropyRng};
+
+/// The length of an ML-KEM-768 public key, in bytes.
+pub const KEM_PK_LEN: usize = ml_kem_768::EK_LEN;
+/// The length of an ML-KEM-768 ciphertext, in bytes.
+pub const KEM_CT_LEN: usize = ml_kem_768::CT_LEN;
+
+/// An ML-KEM-768 secret key, stored as its 64-byte seed. Zeroed out on drop, and deliberately not [Copy].
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KemSK(#[serde(with = "stdcode::hexarray")] pub [u8; 64]);
+
+/// An ML-KEM-768 public key.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KemPK(#[serde(with = "stdcode::hex")] pub Vec<u8>);
+
+/// An ML-KEM-768 ciphertext, carrying an encapsulated shared secret.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KemCiphertext(#[serde(with = "stdcode::hex")] pub Vec<u8>);
+
+/// Error returned when encapsulation or decapsulation fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KemError {
+    /// The public key is malformed.
+    InvalidPublicKey,
+    /// The ciphertext, or the sealed message containing it, is malformed.
+    InvalidCiphertext,
+    /// A sealed message couldn't be decrypted or authenticated.
+    Decryption,
+}
+
+impl fmt::Display for KemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KemError::InvalidPublicKey => "invalid ML-KEM public key".fmt(f),
+            KemError::InvalidCiphertext => "invalid ML-KEM ciphertext".fmt(f),
+            KemError::Decryption => "could not decrypt or authenticate sealed message".fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for KemError {}
+
+impl KemSK {
+    /// Generates a new random key.
+    pub fn generate() -> Self {
+        let mut seed = [0u8; 64];
+        fill_random(&mut seed);
+        Self(seed)
+    }
+
+    /// The public key.
+    pub fn to_public(&self) -> KemPK {
+        let (ek, _) = self.expand();
+        KemPK(ek.into_bytes().to_vec())
+    }
+
+    /// Recovers the shared secret from a ciphertext encapsulated to this key. A well-formed ciphertext meant for another key yields an unrelated secret rather than an error, so the mismatch only shows up when the secret is used.
+    pub fn decapsulate(&self, ct: &KemCiphertext) -> Result<AeadKey, KemError> {
+        let ct: [u8; KEM_CT_LEN] =
+            ct.0.as_slice()
+                .try_into()
+                .map_err(|_| KemError::InvalidCiphertext)?;
+        let ct =
+            ml_kem_768::CipherText::try_from_bytes(ct).map_err(|_| KemError::InvalidCiphertext)?;
+        let (_, dk) = self.expand();
+        let ssk = dk
+            .try_decaps(&ct)
+            .map_err(|_| KemError::InvalidCiphertext)?;
+        Ok(AeadKey(ssk.into_bytes()))
+    }
+
+    /// Opens a message sealed with [KemPK::seal], given the same `aad`.
+    pub fn open(&self, sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>, KemError> {
+        if sealed.len() < KEM_CT_LEN {
+            return Err(KemError::InvalidCiphertext);
+        }
+        let (ct, body) = sealed.split_at(KEM_CT_LEN);
+        let key = self.decapsulate(&KemCiphertext(ct.to_vec()))?;
+        key.open(body, &sealed_aad(ct, aad))
+            .map_err(|_| KemError::Decryption)
+    }
+
+    fn expand(&self) -> (ml_kem_768::EncapsKey, ml_kem_768::DecapsKey) {
+        ml_kem_768::KG::keygen_from_seed(
+            self.0[..32].try_into().unwrap(),
+            self.0[32..].try_into().unwrap(),
+        )
+    }
+}
+
+impl KemPK {
+    /// Encapsulates a fresh shared secret to this key, returning the secret and the ciphertext to send to the key's owner.
+    pub fn encapsulate(&self) -> Result<(AeadKey, KemCiphertext), KemError> {
+        let ek: [u8; KEM_PK_LEN] = self
+            .0
+            .as_slice()
+            .try_into()
+            .map_err(|_| KemError::InvalidPublicKey)?;
+        let ek =
+            ml_kem_768::EncapsKey::try_from_bytes(ek).map_err(|_| KemError::InvalidPublicKey)?;
+        let (ssk, ct) = ek
+            .try_encaps_with_rng(&mut EntropyRng)
+            .map_err(|_| KemError::InvalidPublicKey)?;
+        Ok((
+            AeadKey(ssk.into_bytes()),
+            KemCiphertext(ct.into_bytes().to_vec()),
+        ))
+    }
+
+    /// Encrypts a message so that only the owner of this key can read it, also authenticating (but not encrypting) `aad`. The result is the KEM ciphertext followed by the [AeadKey::seal] output, and is opened with [KemSK::open].
+    ///
+    /// Sealing is anonymous: the recipient learns nothing about who sent the message, so anything that needs a sender should also be signed.
+    pub fn seal(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, KemError> {
+        let (key, ct) = self.encapsulate()?;
+        let mut sealed = ct.0;
+        let body = key.seal(plaintext, &sealed_aad(&sealed, aad));
+        sealed.extend_from_slice(&body);
+        Ok(sealed)
+    }
+}
+
+/// Binds the AEAD layer to the KEM ciphertext as well as the caller's associated data.
+fn sealed_aad(ct: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut toret = ct.to_vec();
+    toret.extend_from_slice(aad);
+    toret
+}
+
+impl PartialEq for KemSK {
+    /// Compares the keys in constant time.
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for KemSK {}
+
+impl Drop for KemSK {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for KemSK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KemSK({:?})", self.to_public())
+    }
+}
+
+impl fmt::Debug for KemPK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#<KemPK:{}>",
+            hex::encode(&self.0[..self.0.len().min(5)])
+        )
+    }
+}
+
+impl fmt::Debug for KemCiphertext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#<KemCiphertext:{}>",
+            hex::encode(&self.0[..self.0.len().min(5)])
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kem_roundtrip() {
+        let sk = KemSK::generate();
+        let pk = sk.to_public();
+        assert_eq!(pk.0.len(), KEM_PK_LEN);
+        let (key, ct) = pk.encapsulate().unwrap();
+        assert_eq!(ct.0.len(), KEM_CT_LEN);
+        assert_eq!(sk.decapsulate(&ct).unwrap().0, key.0);
+        assert_ne!(KemSK::generate().decapsulate(&ct).unwrap().0, key.0);
+        assert_eq!(
+            sk.decapsulate(&KemCiphertext(vec![0; 3])).unwrap_err(),
+            KemError::InvalidCiphertext
+        );
+
+        let sealed = pk.seal(b"channel hello", b"session 1").unwrap();
+        assert_eq!(sk.open(&sealed, b"session 1").unwrap(), b"channel hello");
+        assert_eq!(sk.open(&sealed, b"session 2"), Err(KemError::Decryption));
+        let pk: KemPK = stdcode::deserialize(&stdcode::serialize(&pk).unwrap()).unwrap();
+        assert_eq!(pk, sk.to_public());
+        let json = serde_json::to_string(&sk).unwrap();
+        assert_eq!(json, format!("\"{}\"", hex::encode(sk.0)));
+        assert_eq!(serde_json::from_str::<KemSK>(&json).unwrap(), sk);
+        let bytes = stdcode::serialize(&sk).unwrap();
+        assert_eq!(bytes, sk.0);
+        assert_eq!(stdcode::deserialize::<KemSK>(&bytes).unwrap(), sk);
+    }
+}
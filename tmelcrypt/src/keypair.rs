@@ -0,0 +1,125 @@
+use std::{convert::TryInto, fmt};
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::{Ed25519PK, Ed25519SK, HashVal};
+
+/// An ed25519 keypair, whose public and secret halves are guaranteed to match.
+///
+/// For safety, the [Debug] output and the [Serialize] implementation only include the public key. Use [KeyPair::export] to explicitly get at the secret key in serializable form.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct KeyPair {
+    pk: Ed25519PK,
+    sk: Ed25519SK,
+}
+
+impl KeyPair {
+    /// Generates a new random keypair.
+    pub fn generate() -> Self {
+        Self::from_secret(Ed25519SK::generate()).unwrap()
+    }
+
+    /// Deterministically derives a keypair from a 32-byte ed25519 seed.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self::from_secret(Ed25519SK::from_seed(seed)).unwrap()
+    }
+
+    /// Creates a keypair from a secret key, checking that the public key embedded in the secret key actually corresponds to it.
+    pub fn from_secret(sk: Ed25519SK) -> Option<Self> {
+        let seed: [u8; 32] = sk.0[..32].try_into().unwrap();
+        let derived = Ed25519SK::from_seed(seed);
+        if derived != sk {
+            return None;
+        }
+        Some(Self {
+            pk: sk.to_public(),
+            sk,
+        })
+    }
+
+    /// The public half.
+    pub fn public(&self) -> Ed25519PK {
+        self.pk
+    }
+
+    /// The secret half.
+    pub fn secret(&self) -> &Ed25519SK {
+        &self.sk
+    }
+
+    pub fn sign(&self, msg: &[u8]) -> Vec<u8> {
+        self.sk.sign(msg)
+    }
+
+    pub fn verify(&self, msg: &[u8], sig: &[u8]) -> bool {
+        self.pk.verify(msg, sig)
+    }
+
+    /// The address (covenant hash) of the standard covenant locked by this keypair.
+    pub fn address(&self) -> HashVal {
+        self.pk.std_address()
+    }
+
+    /// The address of the standard covenant locked by this keypair, as a `t...` string.
+    pub fn address_string(&self) -> String {
+        self.address().to_addr()
+    }
+
+    /// Exports both halves of the keypair in serializable form. This is the only way to serialize the secret key through a keypair.
+    pub fn export(&self) -> ExportedKeyPair {
+        ExportedKeyPair {
+            pk: self.pk,
+            sk: self.sk.clone(),
+        }
+    }
+
+    /// Imports a keypair previously returned by [KeyPair::export], checking that the halves match.
+    pub fn import(exported: ExportedKeyPair) -> Option<Self> {
+        let kp = Self::from_secret(exported.sk)?;
+        if kp.pk != exported.pk {
+            return None;
+        }
+        Some(kp)
+    }
+}
+
+impl fmt::Debug for KeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPair")
+            .field("pk", &self.pk)
+            .field("sk", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Serialize for KeyPair {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.pk.serialize(serializer)
+    }
+}
+
+/// Both halves of a [KeyPair], including the secret key, in serializable form.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExportedKeyPair {
+    pub pk: Ed25519PK,
+    pub sk: Ed25519SK,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keypair_address() {
+        // matches Covenant::std_ed25519_pk_new in themelio-stf
+        assert_eq!(
+            Ed25519PK([0xab; 32]).std_address().to_addr(),
+            "t2ca2jbkw9e5x6gnyeg9hb21q191kx4y67kefwq0nfmpnjsp65t40g"
+        );
+        let kp = KeyPair::from_seed([1; 32]);
+        assert_eq!(kp, KeyPair::from_seed([1; 32]));
+        assert!(kp.verify(b"hello", &kp.sign(b"hello")));
+        assert_eq!(KeyPair::import(kp.export()), Some(kp.clone()));
+        assert!(!format!("{:?}", kp).contains(&hex::encode(&kp.secret().0[..32])));
+    }
+}
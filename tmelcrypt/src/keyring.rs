@@ -0,0 +1,347 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{hash_keyed, Ed25519PK, Ed25519SK, HashVal};
+
+/// Something a secret key can be unlocked from on demand, such as a file, an encrypted keystore, or a hardware backend.
+pub trait KeySource: Send + Sync {
+    /// Returns the public key corresponding to the secret key, ideally without unlocking it.
+    fn public_key(&self) -> io::Result<Ed25519PK>;
+
+    /// Unlocks the secret key.
+    fn unlock(&self) -> io::Result<Ed25519SK>;
+}
+
+impl KeySource for Ed25519SK {
+    fn public_key(&self) -> io::Result<Ed25519PK> {
+        Ok(self.to_public())
+    }
+
+    fn unlock(&self) -> io::Result<Ed25519SK> {
+        Ok(self.clone())
+    }
+}
+
+/// A secret key stored in hexadecimal in a plain file.
+#[derive(Clone, Debug)]
+pub struct FileKeySource {
+    path: PathBuf,
+}
+
+impl FileKeySource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl KeySource for FileKeySource {
+    fn public_key(&self) -> io::Result<Ed25519PK> {
+        Ok(self.unlock()?.to_public())
+    }
+
+    fn unlock(&self) -> io::Result<Ed25519SK> {
+        let contents = std::fs::read_to_string(&self.path)?;
+        Ed25519SK::from_str(contents.trim())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Computes the fingerprint used to refer to a public key in a [KeyRing].
+pub fn key_fingerprint(pk: &Ed25519PK) -> HashVal {
+    hash_keyed(b"tmelcrypt-key-fingerprint", pk.0)
+}
+
+struct KeyRingEntry {
+    source: Arc<dyn KeySource>,
+    public: Ed25519PK,
+    fingerprint: HashVal,
+    unlocked: Option<(Ed25519SK, Instant)>,
+}
+
+/// A thread-safe collection of named secret keys, which are unlocked from their [KeySource] when first used and kept in memory for a configurable timeout.
+///
+/// Keys are unlocked without holding the keyring's lock, so a slow unlock (such as a passphrase prompt or a hardware device) doesn't block other keys. Expired keys are dropped from memory whenever a key is looked up, or by calling [KeyRing::lock_expired].
+pub struct KeyRing {
+    entries: Mutex<HashMap<String, KeyRingEntry>>,
+    cache_timeout: Duration,
+}
+
+impl KeyRing {
+    /// Creates an empty keyring, caching unlocked keys for `cache_timeout`.
+    pub fn new(cache_timeout: Duration) -> Self {
+        Self {
+            entries: Default::default(),
+            cache_timeout,
+        }
+    }
+
+    /// Adds a key source under the given name, replacing any existing key with that name. Returns the public key.
+    pub fn add(
+        &self,
+        name: impl Into<String>,
+        source: impl KeySource + 'static,
+    ) -> io::Result<Ed25519PK> {
+        let public = source.public_key()?;
+        let entry = KeyRingEntry {
+            source: Arc::new(source),
+            public,
+            fingerprint: key_fingerprint(&public),
+            unlocked: None,
+        };
+        self.entries.lock().unwrap().insert(name.into(), entry);
+        Ok(public)
+    }
+
+    /// Adds a hex-encoded secret key file under the given name.
+    pub fn add_file(
+        &self,
+        name: impl Into<String>,
+        path: impl Into<PathBuf>,
+    ) -> io::Result<Ed25519PK> {
+        self.add(name, FileKeySource::new(path))
+    }
+
+    /// Removes a key from the keyring.
+    pub fn remove(&self, name: &str) -> bool {
+        self.entries.lock().unwrap().remove(name).is_some()
+    }
+
+    /// Lists the names and public keys of all keys in the keyring.
+    pub fn list(&self) -> Vec<(String, Ed25519PK)> {
+        let entries = self.entries.lock().unwrap();
+        let mut toret: Vec<_> = entries
+            .iter()
+            .map(|(name, entry)| (name.clone(), entry.public))
+            .collect();
+        toret.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        toret
+    }
+
+    /// Gets the secret key with the given name, unlocking it if it isn't cached.
+    pub fn get(&self, name: &str) -> io::Result<Ed25519SK> {
+        self.unlock_where(|entry_name, _| entry_name == name)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no key with that name"))?
+    }
+
+    /// Gets the secret key with the given fingerprint, unlocking it if it isn't cached.
+    pub fn get_by_fingerprint(&self, fingerprint: HashVal) -> io::Result<Ed25519SK> {
+        self.unlock_where(|_, entry| entry.fingerprint == fingerprint)
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "no key with that fingerprint")
+            })?
+    }
+
+    /// Resolves either a name or a hex-encoded fingerprint into a secret key. Names take priority.
+    pub fn resolve(&self, name_or_fingerprint: &str) -> io::Result<Ed25519SK> {
+        match self.get(name_or_fingerprint) {
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                if let Ok(fingerprint) = HashVal::from_str(name_or_fingerprint) {
+                    self.get_by_fingerprint(fingerprint)
+                } else {
+                    Err(err)
+                }
+            }
+            res => res,
+        }
+    }
+
+    /// Drops every cached secret key, so that they must be unlocked again.
+    pub fn lock_all(&self) {
+        for entry in self.entries.lock().unwrap().values_mut() {
+            entry.unlocked = None;
+        }
+    }
+
+    /// Drops every cached secret key whose timeout has passed.
+    pub fn lock_expired(&self) {
+        Self::evict_expired(&mut self.entries.lock().unwrap(), self.cache_timeout);
+    }
+
+    fn evict_expired(entries: &mut HashMap<String, KeyRingEntry>, timeout: Duration) {
+        for entry in entries.values_mut() {
+            if matches!(&entry.unlocked, Some((_, unlocked_at)) if unlocked_at.elapsed() >= timeout)
+            {
+                entry.unlocked = None;
+            }
+        }
+    }
+
+    /// Finds the first entry matching `pred` and returns its secret key, unlocking it if it isn't cached. Returns `None` if there is no such entry.
+    fn unlock_where(
+        &self,
+        pred: impl Fn(&str, &KeyRingEntry) -> bool,
+    ) -> Option<io::Result<Ed25519SK>> {
+        let (name, source, public) = {
+            let mut entries = self.entries.lock().unwrap();
+            Self::evict_expired(&mut entries, self.cache_timeout);
+            let (name, entry) = entries.iter().find(|(name, entry)| pred(name, entry))?;
+            if let Some((sk, _)) = &entry.unlocked {
+                return Some(Ok(sk.clone()));
+            }
+            (name.clone(), entry.source.clone(), entry.public)
+        };
+        // the lock is released while unlocking, which may be slow
+        let sk = match source.unlock() {
+            Ok(sk) => sk,
+            Err(err) => return Some(Err(err)),
+        };
+        if sk.to_public() != public {
+            return Some(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unlocked key does not match the registered public key",
+            )));
+        }
+        // only cache the key if the entry wasn't replaced or removed in the meantime
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(&name) {
+            if Arc::ptr_eq(&entry.source, &source) {
+                entry.unlocked = Some((sk.clone(), Instant::now()));
+            }
+        }
+        Some(Ok(sk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    /// Counts how many times the key is unlocked.
+    struct CountingSource {
+        sk: Ed25519SK,
+        unlocks: Arc<AtomicUsize>,
+    }
+
+    impl KeySource for CountingSource {
+        fn public_key(&self) -> io::Result<Ed25519PK> {
+            Ok(self.sk.to_public())
+        }
+
+        fn unlock(&self) -> io::Result<Ed25519SK> {
+            self.unlocks.fetch_add(1, Ordering::SeqCst);
+            Ok(self.sk.clone())
+        }
+    }
+
+    #[test]
+    fn keyring() {
+        let ring = KeyRing::new(Duration::from_secs(3600));
+        let sk = Ed25519SK::generate();
+        let unlocks = Arc::new(AtomicUsize::new(0));
+        let pk = ring
+            .add(
+                "validator",
+                CountingSource {
+                    sk: sk.clone(),
+                    unlocks: unlocks.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(pk, sk.to_public());
+        let other = ring.add("other", Ed25519SK::generate()).unwrap();
+        assert_eq!(
+            ring.list(),
+            vec![("other".to_string(), other), ("validator".to_string(), pk)]
+        );
+
+        // unlocked once, then cached until locked again
+        assert_eq!(ring.get("validator").unwrap(), sk);
+        assert_eq!(ring.get("validator").unwrap(), sk);
+        assert_eq!(unlocks.load(Ordering::SeqCst), 1);
+        ring.lock_all();
+        assert_eq!(ring.get("validator").unwrap(), sk);
+        assert_eq!(unlocks.load(Ordering::SeqCst), 2);
+
+        let fingerprint = key_fingerprint(&pk);
+        assert_eq!(ring.get_by_fingerprint(fingerprint).unwrap(), sk);
+        assert_eq!(ring.resolve(&fingerprint.to_string()).unwrap(), sk);
+        assert_eq!(ring.resolve("validator").unwrap(), sk);
+
+        assert!(ring.remove("validator"));
+        assert!(!ring.remove("validator"));
+        assert_eq!(
+            ring.get("validator").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(
+            ring.get_by_fingerprint(fingerprint).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn keyring_expiry() {
+        let ring = KeyRing::new(Duration::from_secs(0));
+        let sk = Ed25519SK::generate();
+        let unlocks = Arc::new(AtomicUsize::new(0));
+        ring.add(
+            "validator",
+            CountingSource {
+                sk: sk.clone(),
+                unlocks: unlocks.clone(),
+            },
+        )
+        .unwrap();
+        assert_eq!(ring.get("validator").unwrap(), sk);
+        assert!(ring.entries.lock().unwrap()["validator"].unlocked.is_some());
+        ring.lock_expired();
+        assert!(ring.entries.lock().unwrap()["validator"].unlocked.is_none());
+        assert_eq!(ring.get("validator").unwrap(), sk);
+        assert_eq!(unlocks.load(Ordering::SeqCst), 2);
+    }
+
+    /// Blocks in unlock until told to continue.
+    struct SlowSource {
+        sk: Ed25519SK,
+        proceed: Mutex<std::sync::mpsc::Receiver<()>>,
+    }
+
+    impl KeySource for SlowSource {
+        fn public_key(&self) -> io::Result<Ed25519PK> {
+            Ok(self.sk.to_public())
+        }
+
+        fn unlock(&self) -> io::Result<Ed25519SK> {
+            self.proceed.lock().unwrap().recv().unwrap();
+            Ok(self.sk.clone())
+        }
+    }
+
+    #[test]
+    fn keyring_unlocks_without_lock() {
+        let ring = Arc::new(KeyRing::new(Duration::from_secs(3600)));
+        let slow = Ed25519SK::generate();
+        let (send, recv) = std::sync::mpsc::channel();
+        ring.add(
+            "slow",
+            SlowSource {
+                sk: slow.clone(),
+                proceed: Mutex::new(recv),
+            },
+        )
+        .unwrap();
+        let fast = Ed25519SK::generate();
+        ring.add("fast", fast.clone()).unwrap();
+
+        let handle = {
+            let ring = ring.clone();
+            std::thread::spawn(move || ring.get("slow").unwrap())
+        };
+        // the other key stays usable while the slow one is being unlocked
+        assert_eq!(ring.get("fast").unwrap(), fast);
+        assert_eq!(ring.list().len(), 2);
+        send.send(()).unwrap();
+        assert_eq!(handle.join().unwrap(), slow);
+        assert_eq!(ring.get("slow").unwrap(), slow);
+    }
+}
@@ -28,12 +28,142 @@ use std::{convert::TryInto, fmt::Formatter};
 use arbitrary::Arbitrary;
 
 use arrayref::array_ref;
-use ed25519_consensus::{Signature, SigningKey, VerificationKey};
-use rand::{prelude::*, rngs::OsRng};
+use ed25519_consensus::{SigningKey, VerificationKey};
+use rand::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 use serde_big_array::big_array;
-
-big_array! { BigArray; }
+use zeroize::Zeroize;
+
+#[cfg(any(feature = "bls", feature = "secp256k1"))]
+#[macro_use]
+mod macros;
+mod accumulator;
+pub use accumulator::*;
+pub mod adaptor;
+mod address;
+pub use address::*;
+mod aead;
+pub use aead::*;
+mod batch;
+pub use batch::*;
+mod beacon;
+pub use beacon::*;
+pub mod bindings;
+mod bitops;
+#[cfg(feature = "bls")]
+mod bls;
+#[cfg(feature = "bls")]
+pub use bls::*;
+mod cert;
+pub use cert::*;
+pub mod commit;
+mod ctdecode;
+pub use ctdecode::*;
+mod entropy;
+pub use entropy::*;
+mod error;
+pub use error::*;
+mod expanded;
+pub use expanded::*;
+pub mod frost;
+mod hashstate;
+pub use hashstate::*;
+mod hd;
+pub use hd::*;
+mod hkdf;
+pub use hkdf::*;
+mod import;
+pub use import::*;
+pub mod kdf;
+#[cfg(feature = "pq")]
+pub mod kem;
+mod keyring;
+pub use keyring::*;
+mod keypair;
+pub use keypair::*;
+mod melhash;
+pub use melhash::*;
+pub mod merkle;
+pub mod mnemonic;
+mod multiformat;
+pub use multiformat::*;
+pub mod musig;
+#[cfg(feature = "openssh")]
+mod openssh;
+mod pkcs8;
+#[cfg(feature = "poseidon")]
+pub mod poseidon;
+mod possession;
+#[cfg(feature = "pq")]
+pub mod pq;
+mod prehash;
+pub use prehash::*;
+mod prepared;
+pub use prepared::*;
+pub mod ring;
+#[cfg(feature = "secp256k1")]
+mod schnorr;
+#[cfg(feature = "secp256k1")]
+pub use schnorr::*;
+#[cfg(feature = "secp256k1")]
+mod secp256k1;
+#[cfg(feature = "secp256k1")]
+pub use secp256k1::*;
+mod shamir;
+pub use shamir::*;
+mod sharing;
+pub use sharing::*;
+mod shorthash;
+pub use shorthash::*;
+mod signature;
+pub use signature::*;
+mod signer;
+pub use signer::*;
+pub mod stealth;
+#[cfg(feature = "proptest")]
+pub mod strategies;
+mod strict;
+mod threshold;
+pub use threshold::*;
+mod timelock;
+pub use timelock::*;
+mod transcript;
+pub use transcript::*;
+mod treehash;
+#[cfg(feature = "derive")]
+pub use tmelcrypt_derive::MelHash;
+pub use treehash::*;
+pub mod vrf;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod wots;
+mod x25519;
+pub use x25519::*;
+
+big_array! { BigArray; +33, 65, 80, }
+
+/// A [HashVal] constant written in hex, checked at compile time.
+///
+/// ```rust
+/// use tmelcrypt::{hashval, HashVal};
+///
+/// const GENESIS: HashVal =
+///     hashval!("af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262");
+/// assert_eq!(GENESIS, tmelcrypt::hash_single(b""));
+/// ```
+///
+/// Invalid hex doesn't compile:
+///
+/// ```compile_fail
+/// let _ = tmelcrypt::hashval!("not hex");
+/// ```
+#[macro_export]
+macro_rules! hashval {
+    ($hex:expr) => {{
+        const HASH: $crate::HashVal = $crate::HashVal::from_hex_const($hex);
+        HASH
+    }};
+}
 
 #[derive(
     Copy, Clone, Eq, PartialEq, Hash, Arbitrary, Ord, PartialOrd, Default, Serialize, Deserialize,
@@ -51,69 +181,151 @@ impl FromStr for HashVal {
     }
 }
 
+impl From<[u8; 32]> for HashVal {
+    fn from(bytes: [u8; 32]) -> Self {
+        HashVal(bytes)
+    }
+}
+
+impl From<HashVal> for [u8; 32] {
+    fn from(h: HashVal) -> Self {
+        h.0
+    }
+}
+
 impl Display for HashVal {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        hex::encode(&self.0).fmt(f)
+        hex::encode(self.0).fmt(f)
     }
 }
 
 impl HashVal {
     /// Randomly generates a HashVal. This will almost certainly not collide with the actual hash of anything.
     pub fn random() -> Self {
-        HashVal(rand::thread_rng().gen())
+        rand_hashval()
+    }
+
+    /// Generates a HashVal from the given RNG instead of the crate's entropy source.
+    ///
+    /// ```rust
+    /// use rand::{rngs::StdRng, SeedableRng};
+    /// use tmelcrypt::HashVal;
+    ///
+    /// assert_eq!(
+    ///     HashVal::random_with_rng(&mut StdRng::seed_from_u64(42)),
+    ///     HashVal::random_with_rng(&mut StdRng::seed_from_u64(42))
+    /// );
+    /// ```
+    pub fn random_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        let mut buf = [0u8; 32];
+        rng.fill_bytes(&mut buf);
+        HashVal(buf)
+    }
+
+    /// Parses a 64-character hex string, like [FromStr], but usable in constants. Panics if the string isn't valid hex of the right length, which in a constant is a compile error. The [hashval!] macro wraps this and guarantees that it runs at compile time.
+    pub const fn from_hex_const(s: &str) -> Self {
+        const fn nibble(c: u8) -> u8 {
+            match c {
+                b'0'..=b'9' => c - b'0',
+                b'a'..=b'f' => c - b'a' + 10,
+                b'A'..=b'F' => c - b'A' + 10,
+                _ => panic!("invalid hex character in hash"),
+            }
+        }
+        let s = s.as_bytes();
+        if s.len() != 64 {
+            panic!("hash must be 64 hex characters");
+        }
+        let mut toret = [0u8; 32];
+        let mut i = 0;
+        while i < 32 {
+            toret[i] = (nibble(s[2 * i]) << 4) | nibble(s[2 * i + 1]);
+            i += 1;
+        }
+        HashVal(toret)
     }
 
     pub fn to_addr(&self) -> String {
         let raw_base32 = base32::encode(base32::Alphabet::Crockford {}, &self.0);
-        let checksum = hash_keyed(b"address-checksum", &self.0).0[0] % 10;
+        let checksum = hash_keyed(b"address-checksum", self.0).0[0] % 10;
         format!("T{}{}", checksum, raw_base32).to_ascii_lowercase()
     }
 
+    /// Parses a `t...` address string, returning `None` if it is malformed or its checksum is wrong. Use [Address] to find out what exactly was wrong.
     pub fn from_addr(addr: &str) -> Option<Self> {
-        // TODO check checksum
-        if addr.len() < 10 {
-            return None;
-        }
-        let addr = addr.replace("-", "");
-        Some(HashVal(
-            base32::decode(base32::Alphabet::Crockford {}, &addr[2..])?
-                .as_slice()
-                .try_into()
-                .ok()?,
-        ))
+        Self::try_from_addr(addr).ok()
     }
 }
 
 /// Computes an entropy seed from a large number of hashes using the "majority beacon".
+///
+/// Only the lowest bit of each output byte is ever set, to a strict majority of the inputs' lowest bits at that byte, or 0 on a tie; the other bits are always 0. This makes a single pass over the inputs, counting set bits. With the `rayon` feature, large input sets are counted in parallel.
 pub fn majority_beacon(elems: &[HashVal]) -> HashVal {
-    let bts: Vec<u8> = (0..32)
-        .map(|i| {
-            let bytes: Vec<u8> = elems.iter().map(|v| v[i]).collect();
-            bitwise_majority(&bytes)
-        })
-        .collect();
-    HashVal(bts.try_into().unwrap())
-}
-
-// helper function that takes the bitwise majority of a large number of u8's
-fn bitwise_majority(bytes: &[u8]) -> u8 {
-    let mut toret = 0u8;
-    for bit_idx in 0..8 {
-        let mut zero_count = 0;
-        let mut one_count = 0;
-        for member in bytes {
-            if member & (1 << bit_idx) == 1 {
-                one_count += 1;
-            } else {
-                zero_count += 1;
+    #[cfg(feature = "rayon")]
+    let counts = {
+        use rayon::prelude::*;
+        elems
+            .par_chunks(4096)
+            .map(count_bits)
+            .reduce(|| [[0; 8]; 32], add_counts)
+    };
+    #[cfg(not(feature = "rayon"))]
+    let counts = count_bits(elems);
+
+    let mut toret = [0u8; 32];
+    for (byte, byte_counts) in toret.iter_mut().zip(counts.iter()) {
+        for (bit_idx, &ones) in byte_counts.iter().enumerate() {
+            // matches the original implementation, whose bit test only ever succeeded for bit 0
+            if bit_idx == 0 && ones as usize * 2 > elems.len() {
+                *byte |= 1 << bit_idx;
+            }
+        }
+    }
+    HashVal(toret)
+}
+
+/// Counts, for every bit position, how many of the hashes have that bit set.
+fn count_bits(elems: &[HashVal]) -> [[u32; 8]; 32] {
+    // Each byte of the input is spread out so that each of its bits lands in its own byte of a u64. Adding these up counts all 8 bits at once, as long as no lane passes 255.
+    let mut counts = [[0u32; 8]; 32];
+    for chunk in elems.chunks(255) {
+        let mut lanes = [0u64; 32];
+        for elem in chunk {
+            for (lane, byte) in lanes.iter_mut().zip(elem.0.iter()) {
+                *lane += SPREAD_BITS[*byte as usize];
             }
         }
-        assert_eq!(zero_count + one_count, bytes.len());
-        if one_count > zero_count {
-            toret |= 1 << bit_idx;
+        for (byte_counts, lane) in counts.iter_mut().zip(lanes.iter()) {
+            for (bit_idx, count) in byte_counts.iter_mut().enumerate() {
+                *count += ((lane >> (8 * bit_idx)) & 0xff) as u32;
+            }
+        }
+    }
+    counts
+}
+
+const SPREAD_BITS: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut bit = 0;
+        while bit < 8 {
+            if i & (1 << bit) != 0 {
+                table[i] |= 1 << (8 * bit);
+            }
+            bit += 1;
         }
+        i += 1;
     }
-    toret
+    table
+};
+
+#[cfg(feature = "rayon")]
+fn add_counts(mut a: [[u32; 8]; 32], b: [[u32; 8]; 32]) -> [[u32; 8]; 32] {
+    for (x, y) in a.iter_mut().flatten().zip(b.iter().flatten()) {
+        *x += y;
+    }
+    a
 }
 
 impl Deref for HashVal {
@@ -132,7 +344,7 @@ impl AsRef<[u8]> for HashVal {
 
 impl fmt::Debug for HashVal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_fmt(format_args!("#<{}>", hex::encode(&self.0)))
+        f.write_fmt(format_args!("#<{}>", hex::encode(self.0)))
     }
 }
 
@@ -161,6 +373,121 @@ pub fn hash_keyed<K: AsRef<[u8]>, V: AsRef<[u8]>>(key: K, val: V) -> HashVal {
     HashVal((*b3h.as_bytes().as_ref()).try_into().unwrap())
 }
 
+/// Hashes many values, returning their hashes in the same order. Each hash equals [hash_single] of the value. With the `rayon` feature, the values are hashed in parallel.
+///
+/// ```rust
+/// use tmelcrypt::{hash_many, hash_single};
+///
+/// let vals: Vec<Vec<u8>> = (0..1000u32).map(|i| vec![i as u8; i as usize]).collect();
+/// let hashes = hash_many(&vals);
+/// assert_eq!(hashes.len(), vals.len());
+/// for (val, hash) in vals.iter().zip(hashes) {
+///     assert_eq!(hash, hash_single(val));
+/// }
+/// ```
+pub fn hash_many<T: AsRef<[u8]> + Sync>(vals: &[T]) -> Vec<HashVal> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        vals.par_iter().map(hash_single).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        vals.iter().map(hash_single).collect()
+    }
+}
+
+/// Hashes a single large value, like [hash_single] but using all cores when the `rayon` feature is on. This only pays off for inputs of at least a few hundred kilobytes; smaller ones are better hashed with [hash_single].
+///
+/// ```rust
+/// use tmelcrypt::{hash_large, hash_single};
+///
+/// let val: Vec<u8> = (0..3_000_000u32).map(|i| (i % 251) as u8).collect();
+/// assert_eq!(hash_large(&val), hash_single(&val));
+/// assert_eq!(hash_large(b"small"), hash_single(b"small"));
+/// ```
+pub fn hash_large(val: impl AsRef<[u8]>) -> HashVal {
+    #[cfg(feature = "rayon")]
+    {
+        HashVal(
+            *blake3::Hasher::new()
+                .update_rayon(val.as_ref())
+                .finalize()
+                .as_bytes(),
+        )
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        hash_single(val)
+    }
+}
+
+/// Hashes the contents of a file, giving the same result as [hash_single] of its bytes. Large files are memory-mapped rather than read into memory, and hashed using all cores when the `rayon` feature is on.
+///
+/// ```rust
+/// use tmelcrypt::{hash_file, hash_single};
+///
+/// let path = std::env::temp_dir().join(format!("tmelcrypt-hash-file-{}", std::process::id()));
+/// for len in [0, 1000, 3_000_000] {
+///     let bytes: Vec<u8> = (0..len).map(|i: u32| (i % 251) as u8).collect();
+///     std::fs::write(&path, &bytes).unwrap();
+///     assert_eq!(hash_file(&path).unwrap(), hash_single(&bytes));
+/// }
+/// std::fs::remove_file(&path).unwrap();
+/// assert!(hash_file(&path).is_err());
+/// ```
+pub fn hash_file(path: impl AsRef<std::path::Path>) -> std::io::Result<HashVal> {
+    let mut hasher = blake3::Hasher::new();
+    #[cfg(feature = "rayon")]
+    hasher.update_mmap_rayon(path)?;
+    #[cfg(not(feature = "rayon"))]
+    hasher.update_mmap(path)?;
+    Ok(HashVal(*hasher.finalize().as_bytes()))
+}
+
+/// Derives a key from some key material using blake3's key derivation mode. The context string should be a hardcoded, globally unique description of the purpose, such as `"themelio-node 2024-01-01 session keys"`; keys derived under different contexts are independent, so subsystems are domain-separated by construction.
+///
+/// ```rust
+/// use tmelcrypt::{derive_key, hashval};
+///
+/// // from the official BLAKE3 test vectors
+/// let context = "BLAKE3 2019-12-27 16:29:52 test vectors context";
+/// assert_eq!(
+///     derive_key(context, b""),
+///     hashval!("2cc39783c223154fea8dfb7c1b1660f2ac2dcbd1c1de8277b0b0dd39b7e50d7d")
+/// );
+/// assert_ne!(derive_key("another context", b""), derive_key(context, b""));
+/// ```
+pub fn derive_key(context: &str, material: &[u8]) -> HashVal {
+    HashVal(blake3::derive_key(context, material))
+}
+
+/// The hash signed by [Ed25519SK::sign_with_aux], committing to both the message and the auxiliary data.
+pub fn aux_signed_hash(msg: &[u8], aux: &[u8]) -> HashVal {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(&hash_single(aux).0);
+    buf[32..].copy_from_slice(&hash_single(msg).0);
+    hash_keyed(b"tmelcrypt-sign-with-aux", buf)
+}
+
+/// The hash signed by [Ed25519SK::sign_with_context]. Signatures made for one domain never verify for another, nor as plain signatures of the same bytes.
+pub fn context_signed_hash(domain: &[u8], msg: &[u8]) -> HashVal {
+    let mut key = b"tmelcrypt-sign-with-context/".to_vec();
+    key.extend_from_slice(domain);
+    hash_keyed(key, msg)
+}
+
+/// XORs data with the blake3 keystream for the given key. Applying it twice with the same key is a no-op.
+pub(crate) fn apply_keystream(key: &[u8; 32], data: &mut [u8]) {
+    let mut keystream = vec![0u8; data.len()];
+    blake3::Hasher::new_keyed(key)
+        .finalize_xof()
+        .fill(&mut keystream);
+    for (b, k) in data.iter_mut().zip(keystream) {
+        *b ^= k;
+    }
+}
+
 /// Generates an ed25519 keypair.
 #[deprecated = "Use Ed25519SK::generate instead"]
 pub fn ed25519_keygen() -> (Ed25519PK, Ed25519SK) {
@@ -184,25 +511,92 @@ impl FromStr for Ed25519PK {
     }
 }
 
+/// Why an ed25519 signature failed to verify.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The signature is not 64 bytes long.
+    BadSignatureLength(usize),
+    /// The public key bytes do not encode a valid curve point.
+    InvalidPublicKey,
+    /// The signature is well-formed, but is not a valid signature of the message by the key.
+    Mismatch,
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::BadSignatureLength(len) => {
+                write!(f, "signature is {} bytes long, expected 64", len)
+            }
+            VerifyError::InvalidPublicKey => "public key is not a valid ed25519 point".fmt(f),
+            VerifyError::Mismatch => "signature does not match the message and key".fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+impl VerifyError {
+    /// A short, fixed label for the failure cause, suitable as a metrics label or structured log field.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            VerifyError::BadSignatureLength(_) => "bad_signature_length",
+            VerifyError::InvalidPublicKey => "invalid_public_key",
+            VerifyError::Mismatch => "mismatch",
+        }
+    }
+}
+
 impl Ed25519PK {
     pub fn verify(&self, msg: &[u8], sig: &[u8]) -> bool {
+        self.verify_detailed(msg, sig).is_ok()
+    }
+
+    /// Like [Ed25519PK::verify], but says why verification failed.
+    pub fn verify_detailed(&self, msg: &[u8], sig: &[u8]) -> Result<(), VerifyError> {
         if sig.len() != 64 {
-            return false;
+            return Err(VerifyError::BadSignatureLength(sig.len()));
         }
-        let sig = Signature::from(*array_ref![sig, 0, 64]);
-        VerificationKey::try_from(self.0)
-            .and_then(|vk| vk.verify(&sig, msg))
-            .is_ok()
+        let sig = ed25519_consensus::Signature::from(*array_ref![sig, 0, 64]);
+        let vk = VerificationKey::try_from(self.0).map_err(|_| VerifyError::InvalidPublicKey)?;
+        vk.verify(&sig, msg).map_err(|_| VerifyError::Mismatch)
+    }
+
+    /// Returns the standard signature-checking covenant for this key, identical to `Covenant::std_ed25519_pk_new` in themelio-stf.
+    pub fn std_covenant(&self) -> Vec<u8> {
+        // LoadImm(HADDR_SPENDER_INDEX), PushI(6), LoadImm(HADDR_SPENDER_TX), VRef, VRef, PushB(pk)
+        const PREFIX: &str =
+            "420009f100000000000000000000000000000000000000000000000000000000000000064200005050f020";
+        // LoadImm(1), SigEOk(32)
+        const SUFFIX: &str = "420001320020";
+        let mut toret = hex::decode(PREFIX).unwrap();
+        toret.extend_from_slice(&self.0);
+        toret.extend_from_slice(&hex::decode(SUFFIX).unwrap());
+        toret
+    }
+
+    /// Returns the address (covenant hash) of [Ed25519PK::std_covenant].
+    pub fn std_address(&self) -> HashVal {
+        hash_single(self.std_covenant())
+    }
+
+    /// Verifies a signature produced by [Ed25519SK::sign_with_aux].
+    pub fn verify_with_aux(&self, msg: &[u8], aux: &[u8], sig: &[u8]) -> bool {
+        self.verify(&aux_signed_hash(msg, aux), sig)
+    }
+
+    /// Verifies a signature produced by [Ed25519SK::sign_with_context] with the same domain.
+    pub fn verify_with_context(&self, domain: &[u8], msg: &[u8], sig: &[u8]) -> bool {
+        self.verify(&context_signed_hash(domain, msg), sig)
     }
 
     pub fn from_bytes(bts: &[u8]) -> Option<Self> {
-        if bts.len() != 32 {
-            log::trace!("In a call to from_bytes(), the input length was not 32.");
-            None
-        } else {
-            let mut buf = [0; 32];
-            buf.copy_from_slice(bts);
-            Some(Ed25519PK(buf))
+        match Self::try_from_bytes(bts) {
+            Ok(pk) => Some(pk),
+            Err(_) => {
+                log::trace!("In a call to from_bytes(), the input length was not 32.");
+                None
+            }
         }
     }
 }
@@ -219,20 +613,28 @@ impl fmt::Debug for Ed25519PK {
     }
 }
 
-#[derive(Copy, Clone, Serialize, Deserialize)]
-/// An ed25519 secret key. Implements FromStr that converts from hexadecimal.
+#[derive(Clone, Serialize, Deserialize)]
+/// An ed25519 secret key. Implements FromStr that converts from hexadecimal, in constant time.
+///
+/// So that keys don't end up in logs, [Display] and [Debug] only show a fingerprint of the public key; use [Ed25519SK::reveal_hex] to deliberately get the hexadecimal form that FromStr accepts.
+///
+/// The key bytes are zeroed out when the key is dropped, or explicitly with [Ed25519SK::erase]. For this reason, the key is deliberately not [Copy].
 pub struct Ed25519SK(#[serde(with = "BigArray")] pub [u8; 64]);
 
 impl Display for Ed25519SK {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        hex::encode(self.0).fmt(f)
+        write!(f, "<redacted:{}>", hex::encode(&self.0[32..37]))
     }
 }
 
 impl FromStr for Ed25519SK {
     type Err = hex::FromHexError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let vv = hex::decode(s)?;
+        let vv = match ct_hex_decode(s) {
+            Some(vv) => vv,
+            // the input isn't a valid key anyway, so find out what's wrong the usual way
+            None => return Err(hex::decode(s).unwrap_err()),
+        };
         Ok(Ed25519SK(
             vv.try_into()
                 .map_err(|_| hex::FromHexError::InvalidStringLength)?,
@@ -241,10 +643,9 @@ impl FromStr for Ed25519SK {
 }
 
 impl PartialEq for Ed25519SK {
+    /// Compares the keys in constant time.
     fn eq(&self, other: &Self) -> bool {
-        let x = &self.0[0..];
-        let y = &other.0[0..];
-        x == y
+        ct_eq(&self.0, &other.0)
     }
 }
 
@@ -258,10 +659,32 @@ impl Hash for Ed25519SK {
     }
 }
 
+impl Drop for Ed25519SK {
+    fn drop(&mut self) {
+        self.erase()
+    }
+}
+
 impl Ed25519SK {
     pub fn generate() -> Self {
-        let mut csprng = OsRng {};
-        let key = SigningKey::new(&mut csprng);
+        Self::generate_with_rng(&mut EntropyRng)
+    }
+
+    /// Generates a key from the given RNG instead of the crate's entropy source, for deterministic simulations or platforms with their own RNG.
+    ///
+    /// ```rust
+    /// use rand::{rngs::StdRng, SeedableRng};
+    /// use tmelcrypt::Ed25519SK;
+    ///
+    /// let sk = Ed25519SK::generate_with_rng(&mut StdRng::seed_from_u64(42));
+    /// assert_eq!(sk, Ed25519SK::generate_with_rng(&mut StdRng::seed_from_u64(42)));
+    /// assert!(sk.to_public().verify(b"hello", &sk.sign(b"hello")));
+    /// ```
+    pub fn generate_with_rng(rng: &mut (impl RngCore + CryptoRng)) -> Self {
+        Self::from_signing_key(SigningKey::new(rng))
+    }
+
+    pub(crate) fn from_signing_key(key: SigningKey) -> Self {
         let pure_sk = key.to_bytes();
         let pure_pk = VerificationKey::from(&key).to_bytes();
         let mut vv = Vec::with_capacity(64);
@@ -271,25 +694,81 @@ impl Ed25519SK {
     }
 
     pub fn sign(&self, msg: &[u8]) -> Vec<u8> {
-        let key = SigningKey::from(*array_ref![self.0, 0, 32]);
-        key.sign(msg).to_bytes().to_vec()
+        self.sign_to_signature(msg).into()
+    }
+
+    /// Signs a message into an existing buffer, without allocating. [Ed25519SK::sign_to_signature] also doesn't allocate.
+    pub fn sign_into(&self, msg: &[u8], out: &mut [u8; 64]) {
+        *out = self.sign_to_signature(msg).0;
+    }
+
+    /// Signs a message together with auxiliary metadata (such as a chain ID or an expiry) that the verifier must supply identically. Returns the signature and the hash that was actually signed.
+    ///
+    /// ```rust
+    /// use tmelcrypt::{aux_signed_hash, Ed25519SK};
+    ///
+    /// let sk = Ed25519SK::generate();
+    /// let pk = sk.to_public();
+    /// let (sig, signed_hash) = sk.sign_with_aux(b"transfer", b"chain 1");
+    /// assert_eq!(signed_hash, aux_signed_hash(b"transfer", b"chain 1"));
+    /// assert!(pk.verify_with_aux(b"transfer", b"chain 1", &sig));
+    /// assert!(!pk.verify_with_aux(b"transfer", b"chain 2", &sig));
+    /// assert!(!pk.verify_with_aux(b"transfer2", b"chain 1", &sig));
+    /// assert!(!pk.verify(b"transfer", &sig));
+    /// ```
+    pub fn sign_with_aux(&self, msg: &[u8], aux: &[u8]) -> (Vec<u8>, HashVal) {
+        let signed_hash = aux_signed_hash(msg, aux);
+        (self.sign(&signed_hash), signed_hash)
+    }
+
+    /// Signs a message for use in one particular protocol, named by `domain` (such as `b"myprotocol-vote"`). The signature only verifies with [Ed25519PK::verify_with_context] and the same domain, so it can't be replayed in another protocol that signs the same bytes.
+    ///
+    /// ```rust
+    /// use tmelcrypt::Ed25519SK;
+    ///
+    /// let sk = Ed25519SK::generate();
+    /// let pk = sk.to_public();
+    /// let sig = sk.sign_with_context(b"myprotocol-vote", b"yes");
+    /// assert!(pk.verify_with_context(b"myprotocol-vote", b"yes", &sig));
+    /// assert!(!pk.verify_with_context(b"otherprotocol-vote", b"yes", &sig));
+    /// assert!(!pk.verify_with_context(b"myprotocol-vote", b"no", &sig));
+    /// assert!(!pk.verify(b"yes", &sig));
+    /// ```
+    pub fn sign_with_context(&self, domain: &[u8], msg: &[u8]) -> Vec<u8> {
+        self.sign(&context_signed_hash(domain, msg))
     }
 
     pub fn from_bytes(bts: &[u8]) -> Option<Self> {
-        if bts.len() != 64 {
-            None
-        } else {
-            Some(Self(*array_ref![bts, 0, 64]))
-        }
+        Self::try_from_bytes(bts).ok()
     }
 
     pub fn to_public(&self) -> Ed25519PK {
         Ed25519PK(*array_ref![self.0, 32, 32])
     }
+
+    /// The whole secret key in hexadecimal, for intentionally exporting it. [Display] and [Debug](fmt::Debug) only show part of the public key.
+    ///
+    /// ```rust
+    /// use tmelcrypt::Ed25519SK;
+    ///
+    /// let sk = Ed25519SK::generate();
+    /// assert_eq!(sk.reveal_hex().parse::<Ed25519SK>().unwrap(), sk);
+    /// let seed = hex::encode(&sk.0[..32]);
+    /// assert!(!sk.to_string().contains(&seed[..10]));
+    /// assert!(!format!("{:?}", sk).contains(&seed[..10]));
+    /// ```
+    pub fn reveal_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// Overwrites the key bytes with zeros, in a way that won't be optimized away. The key is unusable afterwards.
+    pub fn erase(&mut self) {
+        self.0.zeroize();
+    }
 }
 
 impl fmt::Debug for Ed25519SK {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_fmt(format_args!("EdSK({})", hex::encode(self.0.as_ref())))
+        write!(f, "EdSK({})", self)
     }
 }
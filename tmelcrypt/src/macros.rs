@@ -0,0 +1,104 @@
+/// Implements equality, hashing, hex parsing and formatting for a public newtype over a byte array, such as a public key or a signature.
+macro_rules! bytes_impls {
+    ($ty:ident, $len:expr, $debug:expr) => {
+        impl PartialEq for $ty {
+            fn eq(&self, other: &Self) -> bool {
+                self.0[..] == other.0[..]
+            }
+        }
+
+        impl Eq for $ty {}
+
+        impl std::hash::Hash for $ty {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.0[..].hash(state)
+            }
+        }
+
+        impl std::str::FromStr for $ty {
+            type Err = hex::FromHexError;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let vv = hex::decode(s)?;
+                let arr: [u8; $len] = std::convert::TryInto::try_into(vv)
+                    .map_err(|_| hex::FromHexError::InvalidStringLength)?;
+                Ok($ty(arr))
+            }
+        }
+
+        impl std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                std::fmt::Display::fmt(&hex::encode(self.0), f)
+            }
+        }
+
+        impl std::fmt::Debug for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, $debug, hex::encode(&self.0[..5]))
+            }
+        }
+    };
+}
+
+/// Implements the traits shared by secret keys that wrap 32 bytes and can only be constructed through a validating `from_bytes(bts: [u8; 32]) -> Option<Self>`: constant-time equality, zeroing on drop, hex parsing, an explicit `reveal_hex` for exporting the key in that form, a Display and Debug that show only the public key (like [crate::Ed25519SK]'s), and a Deserialize that rejects invalid keys. `$deserialize` reads the 32 bytes, matching how the key serializes.
+macro_rules! secret_key_impls {
+    ($ty:ident, $deserialize:expr) => {
+        impl PartialEq for $ty {
+            fn eq(&self, other: &Self) -> bool {
+                $crate::ct_eq(&self.0, &other.0)
+            }
+        }
+
+        impl Eq for $ty {}
+
+        impl Drop for $ty {
+            fn drop(&mut self) {
+                zeroize::Zeroize::zeroize(&mut self.0);
+            }
+        }
+
+        impl std::str::FromStr for $ty {
+            type Err = $crate::Error;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let mut vv = $crate::ct_hex_decode(s).ok_or($crate::Error::InvalidEncoding)?;
+                let res = match std::convert::TryInto::<[u8; 32]>::try_into(&vv[..]) {
+                    Ok(arr) => Self::from_bytes(arr).ok_or($crate::Error::InvalidSecretKey),
+                    Err(_) => Err($crate::Error::InvalidLength {
+                        expected: 32,
+                        actual: vv.len(),
+                    }),
+                };
+                zeroize::Zeroize::zeroize(&mut vv);
+                res
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let mut bts: [u8; 32] = $deserialize(deserializer)?;
+                let sk = Self::from_bytes(bts)
+                    .ok_or_else(|| serde::de::Error::custom($crate::Error::InvalidSecretKey));
+                zeroize::Zeroize::zeroize(&mut bts);
+                sk
+            }
+        }
+
+        impl $ty {
+            /// The whole secret key in hexadecimal, for intentionally exporting it. FromStr accepts this form, while [Display](std::fmt::Display) and [Debug](std::fmt::Debug) only show part of the public key.
+            pub fn reveal_hex(&self) -> String {
+                hex::encode(self.0)
+            }
+        }
+
+        impl std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "<redacted:{}>", hex::encode(&self.to_public().0[..5]))
+            }
+        }
+
+        impl std::fmt::Debug for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, concat!(stringify!($ty), "({:?})"), self.to_public())
+            }
+        }
+    };
+}
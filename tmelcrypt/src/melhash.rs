@@ -0,0 +1,151 @@
+use crate::{hash_keyed, Ed25519PK, HashVal};
+
+/// Canonical, domain-separated hashing of structured values.
+///
+/// Unlike hashing the stdcode encoding of a value, the result doesn't depend on the details of the serialization format. It's usually implemented through `#[derive(MelHash)]` (with the `derive` feature), which hashes a struct or enum variant through a [MelHasher] whose domain is the type's name (or the `#[mel_hash(domain = "...")]` attribute, which should be changed to version the hash of a type), feeding in every field in declaration order.
+pub trait MelHash {
+    fn mel_hash(&self) -> HashVal;
+
+    /// Hashes a slice of values. By default this is a list of their hashes; `u8` overrides it so that byte strings are hashed directly, like `Hash::hash_slice` in std.
+    fn mel_hash_slice(slice: &[Self]) -> HashVal
+    where
+        Self: Sized,
+    {
+        let mut hasher = MelHasher::new("list");
+        for elem in slice {
+            hasher.field(elem);
+        }
+        hasher.finish()
+    }
+}
+
+/// Builds the hash of a composite value out of its fields.
+///
+/// The result is `hash_keyed("melhash/v1/" || domain, fields)`, where every field is written as its length as a little-endian u64, followed by its contents. A nested value is written as its 32-byte [MelHash].
+#[derive(Clone, Debug)]
+pub struct MelHasher {
+    domain: String,
+    buf: Vec<u8>,
+}
+
+impl MelHasher {
+    /// Starts hashing a value with the given domain.
+    pub fn new(domain: &str) -> Self {
+        Self {
+            domain: format!("melhash/v1/{}", domain),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Writes a nested value.
+    pub fn field(&mut self, val: &(impl MelHash + ?Sized)) -> &mut Self {
+        self.raw(&val.mel_hash())
+    }
+
+    /// Writes raw bytes.
+    pub fn raw(&mut self, bytes: &[u8]) -> &mut Self {
+        self.buf
+            .extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        self.buf.extend_from_slice(bytes);
+        self
+    }
+
+    /// Computes the final hash.
+    pub fn finish(&self) -> HashVal {
+        hash_keyed(&self.domain, &self.buf)
+    }
+}
+
+macro_rules! melhash_int {
+    ($($ty:ty),*) => {
+        $(
+            impl MelHash for $ty {
+                fn mel_hash(&self) -> HashVal {
+                    MelHasher::new(stringify!($ty)).raw(&self.to_le_bytes()).finish()
+                }
+            }
+        )*
+    };
+}
+
+melhash_int!(u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl MelHash for u8 {
+    fn mel_hash(&self) -> HashVal {
+        MelHasher::new("u8").raw(&[*self]).finish()
+    }
+
+    fn mel_hash_slice(slice: &[Self]) -> HashVal {
+        MelHasher::new("bytes").raw(slice).finish()
+    }
+}
+
+/// Hashed as a u64, so that the hash doesn't depend on the platform.
+impl MelHash for usize {
+    fn mel_hash(&self) -> HashVal {
+        (*self as u64).mel_hash()
+    }
+}
+
+impl MelHash for bool {
+    fn mel_hash(&self) -> HashVal {
+        MelHasher::new("bool").raw(&[*self as u8]).finish()
+    }
+}
+
+impl MelHash for str {
+    fn mel_hash(&self) -> HashVal {
+        MelHasher::new("str").raw(self.as_bytes()).finish()
+    }
+}
+
+impl MelHash for String {
+    fn mel_hash(&self) -> HashVal {
+        self.as_str().mel_hash()
+    }
+}
+
+impl MelHash for HashVal {
+    fn mel_hash(&self) -> HashVal {
+        MelHasher::new("HashVal").raw(&self.0).finish()
+    }
+}
+
+impl MelHash for Ed25519PK {
+    fn mel_hash(&self) -> HashVal {
+        MelHasher::new("Ed25519PK").raw(&self.0).finish()
+    }
+}
+
+impl<T: MelHash> MelHash for [T] {
+    fn mel_hash(&self) -> HashVal {
+        T::mel_hash_slice(self)
+    }
+}
+
+impl<T: MelHash> MelHash for Vec<T> {
+    fn mel_hash(&self) -> HashVal {
+        self.as_slice().mel_hash()
+    }
+}
+
+impl<T: MelHash> MelHash for Option<T> {
+    fn mel_hash(&self) -> HashVal {
+        match self {
+            None => MelHasher::new("Option::None").finish(),
+            Some(val) => MelHasher::new("Option::Some").field(val).finish(),
+        }
+    }
+}
+
+impl<T: MelHash + ?Sized> MelHash for &T {
+    fn mel_hash(&self) -> HashVal {
+        (**self).mel_hash()
+    }
+}
+
+impl<T: MelHash + ?Sized> MelHash for Box<T> {
+    fn mel_hash(&self) -> HashVal {
+        (**self).mel_hash()
+    }
+}
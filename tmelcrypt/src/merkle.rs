@@ -0,0 +1,171 @@
+//! Binary Merkle trees over [HashVal] leaves, with compact inclusion proofs.
+//!
+//! Leaves and interior nodes are hashed with different keys, so a proof for an interior node can never pass as a proof for a leaf. When a level has an odd number of nodes, the last one is promoted to the next level unchanged rather than paired with a copy of itself, so no two different leaf lists have the same root. The root also commits to the number of leaves, so a proof cannot be replayed against a different tree shape by rewriting its leaf count.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{hash_keyed, HashVal, Hashable};
+
+/// A Merkle tree, with every level kept in memory so that proofs can be produced.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleTree {
+    levels: Vec<Vec<HashVal>>,
+}
+
+impl MerkleTree {
+    /// Builds a tree whose leaves are the given hashes.
+    pub fn new(leaves: &[HashVal]) -> Self {
+        let mut levels = vec![leaves.iter().map(|l| hash_leaf(*l)).collect::<Vec<_>>()];
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [l, r] => hash_node(*l, *r),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        Self { levels }
+    }
+
+    /// Builds a tree whose leaves are the hashes of the given items.
+    pub fn from_items<T: Hashable>(items: &[T]) -> Self {
+        let leaves: Vec<HashVal> = items.iter().map(|i| i.hash()).collect();
+        Self::new(&leaves)
+    }
+
+    /// The root of the tree, which commits to both the leaves and their number. An empty tree has an all-zero root.
+    pub fn root(&self) -> HashVal {
+        match self.levels.last().unwrap().as_slice() {
+            [top] => hash_root(*top, self.len() as u64),
+            _ => HashVal::default(),
+        }
+    }
+
+    /// The number of leaves.
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Produces a proof that the leaf at `index` is in the tree.
+    pub fn proof(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut siblings = Vec::new();
+        let mut idx = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(idx ^ 1) {
+                siblings.push(*sibling);
+            }
+            idx /= 2;
+        }
+        Some(MerkleProof {
+            index: index as u64,
+            leaf_count: self.len() as u64,
+            siblings,
+        })
+    }
+}
+
+/// A proof that a leaf is at a particular position in a [MerkleTree] with a particular root.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub index: u64,
+    pub leaf_count: u64,
+    /// The sibling hashes on the path from the leaf to the root, bottom-up. Levels where the node has no sibling are skipped.
+    pub siblings: Vec<HashVal>,
+}
+
+impl MerkleProof {
+    /// Checks that `leaf` is at position [MerkleProof::index] of a tree with the given root.
+    pub fn verify(&self, root: HashVal, leaf: HashVal) -> bool {
+        if self.index >= self.leaf_count {
+            return false;
+        }
+        let mut node = hash_leaf(leaf);
+        let mut siblings = self.siblings.iter();
+        let (mut idx, mut count) = (self.index, self.leaf_count);
+        while count > 1 {
+            if idx % 2 == 1 {
+                match siblings.next() {
+                    Some(sibling) => node = hash_node(*sibling, node),
+                    None => return false,
+                }
+            } else if idx + 1 < count {
+                match siblings.next() {
+                    Some(sibling) => node = hash_node(node, *sibling),
+                    None => return false,
+                }
+            }
+            idx /= 2;
+            count = count.div_ceil(2);
+        }
+        siblings.next().is_none() && hash_root(node, self.leaf_count) == root
+    }
+}
+
+fn hash_leaf(leaf: HashVal) -> HashVal {
+    hash_keyed(b"tmelcrypt-merkle-leaf", leaf)
+}
+
+fn hash_node(left: HashVal, right: HashVal) -> HashVal {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(&left);
+    buf[32..].copy_from_slice(&right);
+    hash_keyed(b"tmelcrypt-merkle-node", buf)
+}
+
+fn hash_root(top: HashVal, leaf_count: u64) -> HashVal {
+    let mut buf = [0u8; 40];
+    buf[..32].copy_from_slice(&top);
+    buf[32..].copy_from_slice(&leaf_count.to_le_bytes());
+    hash_keyed(b"tmelcrypt-merkle-root", buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merkle_proofs() {
+        for n in 0..20usize {
+            let items: Vec<Vec<u8>> = (0..n).map(|i| vec![i as u8; 3]).collect();
+            let tree = MerkleTree::from_items(&items);
+            assert_eq!(tree.len(), n);
+            for (i, item) in items.iter().enumerate() {
+                let proof = tree.proof(i).unwrap();
+                assert!(proof.verify(tree.root(), item.hash()));
+                assert!(!proof.verify(tree.root(), HashVal::default()));
+                let proof: MerkleProof =
+                    stdcode::deserialize(&stdcode::serialize(&proof).unwrap()).unwrap();
+                assert!(proof.verify(tree.root(), item.hash()));
+            }
+            assert!(tree.proof(n).is_none());
+        }
+    }
+
+    #[test]
+    fn merkle_leaf_count_bound() {
+        // In a 3-leaf tree the last leaf is promoted unchanged, so its proof has the same
+        // siblings as a proof for index 1 of a 2-leaf tree. The root must tell them apart.
+        let items: Vec<Vec<u8>> = (0..3u8).map(|i| vec![i; 3]).collect();
+        let tree = MerkleTree::from_items(&items);
+        let proof = tree.proof(2).unwrap();
+        assert!(proof.verify(tree.root(), items[2].hash()));
+        let forged = MerkleProof {
+            index: 1,
+            leaf_count: 2,
+            siblings: proof.siblings.clone(),
+        };
+        assert!(!forged.verify(tree.root(), items[2].hash()));
+    }
+}
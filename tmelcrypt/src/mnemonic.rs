@@ -0,0 +1,122 @@
+//! BIP39 mnemonic phrases for backing up ed25519 keys.
+//!
+//! Keys are derived the same way as in other ed25519 wallets that use BIP39: the phrase and passphrase are stretched into a 64-byte BIP39 seed, from which the SLIP-0010 master key is derived. That way, a phrase generated here recovers the same key in any compatible wallet, and vice versa.
+
+use std::fmt;
+
+use crate::{fill_random, DerivationPath, Ed25519PK, Ed25519SK, ExtendedKey, KeyPair};
+
+/// How many words a generated phrase has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MnemonicLength {
+    /// 12 words, encoding 128 bits of entropy.
+    Words12,
+    /// 24 words, encoding 256 bits of entropy.
+    Words24,
+}
+
+/// Error returned when a mnemonic phrase is invalid, such as when it has an unknown word or a bad checksum.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MnemonicError(String);
+
+impl fmt::Display for MnemonicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid mnemonic phrase: {}", self.0)
+    }
+}
+
+impl std::error::Error for MnemonicError {}
+
+/// Generates a new random English mnemonic phrase.
+pub fn generate_phrase(length: MnemonicLength) -> String {
+    let mut entropy = [0u8; 32];
+    let entropy = match length {
+        MnemonicLength::Words12 => &mut entropy[..16],
+        MnemonicLength::Words24 => &mut entropy[..],
+    };
+    fill_random(entropy);
+    bip39::Mnemonic::from_entropy(entropy)
+        .expect("entropy has a valid length")
+        .to_string()
+}
+
+/// Checks that a phrase is a well-formed English BIP39 mnemonic.
+pub fn validate_phrase(phrase: &str) -> Result<(), MnemonicError> {
+    parse(phrase).map(|_| ())
+}
+
+/// Computes the 64-byte BIP39 seed for a phrase and an optional passphrase (use `""` for none).
+pub fn phrase_to_seed(phrase: &str, passphrase: &str) -> Result<[u8; 64], MnemonicError> {
+    Ok(parse(phrase)?.to_seed(passphrase))
+}
+
+/// Derives the secret key of a phrase and passphrase. This is the SLIP-0010 master key of the BIP39 seed.
+pub fn sk_from_phrase(phrase: &str, passphrase: &str) -> Result<Ed25519SK, MnemonicError> {
+    Ok(ExtendedKey::master(&phrase_to_seed(phrase, passphrase)?).secret_key())
+}
+
+/// Derives the secret key at a SLIP-0010 path (such as `m/44'/990'/0'`) below the master key of a phrase and passphrase.
+pub fn sk_from_phrase_path(
+    phrase: &str,
+    passphrase: &str,
+    path: &DerivationPath,
+) -> Result<Ed25519SK, MnemonicError> {
+    Ok(ExtendedKey::master(&phrase_to_seed(phrase, passphrase)?)
+        .derive(path)
+        .secret_key())
+}
+
+/// Checks that a backed-up phrase and passphrase recover the key with the given public key, for instance right after the user writes the phrase down.
+pub fn verify_backup(phrase: &str, passphrase: &str, pk: &Ed25519PK) -> bool {
+    sk_from_phrase(phrase, passphrase)
+        .map(|sk| sk.to_public() == *pk)
+        .unwrap_or(false)
+}
+
+fn parse(phrase: &str) -> Result<bip39::Mnemonic, MnemonicError> {
+    bip39::Mnemonic::parse_in_normalized(bip39::Language::English, phrase)
+        .map_err(|e| MnemonicError(e.to_string()))
+}
+
+impl Ed25519SK {
+    /// Recovers a secret key from a BIP39 phrase and passphrase. See [crate::mnemonic].
+    pub fn from_mnemonic(phrase: &str, passphrase: &str) -> Result<Self, MnemonicError> {
+        sk_from_phrase(phrase, passphrase)
+    }
+}
+
+impl KeyPair {
+    /// Recovers a keypair from a BIP39 phrase and passphrase. See [crate::mnemonic].
+    pub fn from_mnemonic(phrase: &str, passphrase: &str) -> Result<Self, MnemonicError> {
+        Ok(Self::from_secret(sk_from_phrase(phrase, passphrase)?)
+            .expect("derived keys are consistent"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mnemonic_roundtrip() {
+        let phrase = generate_phrase(MnemonicLength::Words24);
+        assert_eq!(phrase.split_whitespace().count(), 24);
+        assert_eq!(
+            generate_phrase(MnemonicLength::Words12)
+                .split_whitespace()
+                .count(),
+            12
+        );
+        let sk = Ed25519SK::from_mnemonic(&phrase, "hunter2").unwrap();
+        assert!(verify_backup(&phrase, "hunter2", &sk.to_public()));
+        assert!(!verify_backup(&phrase, "", &sk.to_public()));
+        assert!(validate_phrase("abandon abandon abandon").is_err());
+
+        // from the BIP39 test vectors
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        assert_eq!(
+            hex::encode(phrase_to_seed(phrase, "TREZOR").unwrap()),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+    }
+}
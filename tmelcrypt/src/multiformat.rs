@@ -0,0 +1,210 @@
+use std::{convert::TryInto, fmt};
+
+use crate::HashVal;
+
+/// The multicodec code for 256-bit blake3.
+pub const MULTIHASH_BLAKE3: u64 = 0x1e;
+
+/// Why some bytes are not the multihash of a [HashVal].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultihashError {
+    /// The bytes end in the middle of a varint, or a varint is too long.
+    Truncated,
+    /// The hash function is not blake3.
+    WrongCode(u64),
+    /// The digest is not 32 bytes.
+    WrongLength(u64),
+}
+
+impl fmt::Display for MultihashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultihashError::Truncated => "truncated multihash".fmt(f),
+            MultihashError::WrongCode(code) => {
+                write!(f, "multihash code is {:#x} instead of blake3", code)
+            }
+            MultihashError::WrongLength(len) => {
+                write!(f, "multihash digest is {} bytes instead of 32", len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MultihashError {}
+
+impl HashVal {
+    /// Encodes the hash as a blake3-256 multihash: the varint code `0x1e`, the varint length `32`, then the hash itself. This is how IPFS and IPLD tooling stores self-describing hashes.
+    pub fn to_multihash(&self) -> Vec<u8> {
+        let mut toret = Vec::with_capacity(34);
+        toret.push(MULTIHASH_BLAKE3 as u8);
+        toret.push(32);
+        toret.extend_from_slice(&self.0);
+        toret
+    }
+
+    /// Decodes a multihash produced by [HashVal::to_multihash], checking that it is a blake3-256 hash with nothing after it.
+    pub fn from_multihash(bytes: &[u8]) -> Result<Self, MultihashError> {
+        let (code, rest) = read_varint(bytes)?;
+        if code != MULTIHASH_BLAKE3 {
+            return Err(MultihashError::WrongCode(code));
+        }
+        let (len, rest) = read_varint(rest)?;
+        if len != 32 {
+            return Err(MultihashError::WrongLength(len));
+        }
+        Ok(HashVal(rest.try_into().map_err(|_| {
+            MultihashError::WrongLength(rest.len() as u64)
+        })?))
+    }
+}
+
+/// A base that [HashVal::to_multibase] can encode in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Multibase {
+    /// Lowercase hexadecimal, prefixed with `f`.
+    Base16,
+    /// Lowercase RFC 4648 base32 without padding, prefixed with `b`. This is what CIDv1 uses by default.
+    Base32,
+    /// Bitcoin's base58 alphabet, prefixed with `z`.
+    Base58Btc,
+}
+
+impl Multibase {
+    /// The prefix character that identifies this base.
+    pub fn prefix(&self) -> char {
+        match self {
+            Multibase::Base16 => 'f',
+            Multibase::Base32 => 'b',
+            Multibase::Base58Btc => 'z',
+        }
+    }
+}
+
+/// Why a string is not a multibase-encoded [HashVal].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultibaseError {
+    /// The string is empty.
+    Empty,
+    /// The prefix is not one of the supported bases.
+    UnsupportedBase(char),
+    /// The rest of the string isn't valid in its base.
+    InvalidEncoding,
+    /// The decoded bytes aren't 32 bytes long.
+    WrongLength(usize),
+}
+
+impl fmt::Display for MultibaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultibaseError::Empty => "empty multibase string".fmt(f),
+            MultibaseError::UnsupportedBase(c) => write!(f, "unsupported multibase prefix {:?}", c),
+            MultibaseError::InvalidEncoding => "invalid multibase encoding".fmt(f),
+            MultibaseError::WrongLength(len) => {
+                write!(f, "multibase payload is {} bytes instead of 32", len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MultibaseError {}
+
+impl HashVal {
+    /// Encodes the hash in a self-describing multibase string, which tools in the IPFS ecosystem can decode without being told the base.
+    pub fn to_multibase(&self, base: Multibase) -> String {
+        let encoded = match base {
+            Multibase::Base16 => hex::encode(self.0),
+            Multibase::Base32 => {
+                base32::encode(base32::Alphabet::RFC4648 { padding: false }, &self.0)
+                    .to_ascii_lowercase()
+            }
+            Multibase::Base58Btc => bs58::encode(self.0).into_string(),
+        };
+        format!("{}{}", base.prefix(), encoded)
+    }
+
+    /// Decodes a multibase string in any of the bases of [Multibase], including the uppercase variants `F` and `B` of base16 and base32.
+    pub fn from_multibase(s: &str) -> Result<Self, MultibaseError> {
+        let mut chars = s.chars();
+        let prefix = chars.next().ok_or(MultibaseError::Empty)?;
+        let rest = chars.as_str();
+        let bytes = match prefix {
+            'f' | 'F' => hex::decode(rest).ok(),
+            'b' | 'B' => base32::decode(
+                base32::Alphabet::RFC4648 { padding: false },
+                &rest.to_ascii_uppercase(),
+            ),
+            'z' => bs58::decode(rest).into_vec().ok(),
+            c => return Err(MultibaseError::UnsupportedBase(c)),
+        }
+        .ok_or(MultibaseError::InvalidEncoding)?;
+        let len = bytes.len();
+        Ok(HashVal(
+            bytes
+                .try_into()
+                .map_err(|_| MultibaseError::WrongLength(len))?,
+        ))
+    }
+}
+
+/// Reads an unsigned LEB128 varint, as used by multiformats, which limits them to 9 bytes.
+fn read_varint(bytes: &[u8]) -> Result<(u64, &[u8]), MultihashError> {
+    let mut value = 0u64;
+    for (i, byte) in bytes.iter().enumerate().take(9) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, &bytes[i + 1..]));
+        }
+    }
+    Err(MultihashError::Truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multihash() {
+        let hash = crate::hash_single(b"hello");
+        let mh = hash.to_multihash();
+        assert_eq!(&mh[..2], &[0x1e, 0x20]);
+        assert_eq!(HashVal::from_multihash(&mh), Ok(hash));
+        // sha2-256
+        let mut sha = mh.clone();
+        sha[0] = 0x12;
+        assert_eq!(
+            HashVal::from_multihash(&sha),
+            Err(MultihashError::WrongCode(0x12))
+        );
+        assert_eq!(
+            HashVal::from_multihash(&mh[..20]),
+            Err(MultihashError::WrongLength(18))
+        );
+        assert_eq!(
+            HashVal::from_multihash(&[0x80]),
+            Err(MultihashError::Truncated)
+        );
+    }
+
+    #[test]
+    fn multibase() {
+        let hash = crate::hash_single(b"hello");
+        for base in [Multibase::Base16, Multibase::Base32, Multibase::Base58Btc] {
+            let s = hash.to_multibase(base);
+            assert!(s.starts_with(base.prefix()));
+            assert_eq!(HashVal::from_multibase(&s), Ok(hash));
+        }
+        assert_eq!(
+            HashVal::from_multibase(&hash.to_multibase(Multibase::Base32).to_uppercase()),
+            Ok(hash)
+        );
+        assert_eq!(HashVal::from_multibase(&format!("f{}", hash)), Ok(hash));
+        assert_eq!(
+            HashVal::from_multibase("m"),
+            Err(MultibaseError::UnsupportedBase('m'))
+        );
+        assert_eq!(
+            HashVal::from_multibase("fab"),
+            Err(MultibaseError::WrongLength(1))
+        );
+    }
+}
@@ -0,0 +1,301 @@
+//! MuSig2 multisignatures for ed25519.
+//!
+//! `n` signers, each with an ordinary [Ed25519SK], aggregate their public keys into a single [Ed25519PK] with [AggregateKey::new], and then jointly produce a single ordinary ed25519 signature that verifies under it with [Ed25519PK::verify]. Unlike [crate::frost], every signer must take part.
+//!
+//! Signing is a two-round state machine. [MusigRound1::new] produces a [Round1Message] with fresh nonce commitments to send to everyone. Once every signer's round-1 message has arrived, [MusigRound1::finish] produces a [Round2Message] with a partial signature, and once every partial signature has arrived, [MusigRound2::finish] checks them and produces the final signature. The round messages are serializable, so they can be sent over any transport.
+
+use std::fmt;
+
+use curve25519_dalek_ng::{
+    constants::ED25519_BASEPOINT_TABLE,
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+    traits::Identity,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use zeroize::Zeroize;
+
+use crate::{expanded::ed25519_challenge, random_scalar, Ed25519PK, Ed25519SK};
+
+/// An error in a MuSig2 protocol run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MusigError {
+    /// The same public key appears twice among the signers.
+    DuplicateSigner(Ed25519PK),
+    /// A message came from, or a secret key belongs to, someone who isn't one of the signers.
+    UnknownSigner(Ed25519PK),
+    /// No message arrived from this signer.
+    MissingSigner(Ed25519PK),
+    /// A public key or nonce wasn't a valid curve point.
+    InvalidPoint(Ed25519PK),
+    /// This signer's partial signature didn't verify.
+    BadPartialSignature(Ed25519PK),
+}
+
+impl fmt::Display for MusigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MusigError::DuplicateSigner(pk) => write!(f, "duplicate signer {}", pk),
+            MusigError::UnknownSigner(pk) => write!(f, "unknown signer {}", pk),
+            MusigError::MissingSigner(pk) => write!(f, "no message from signer {}", pk),
+            MusigError::InvalidPoint(pk) => write!(f, "invalid curve point from signer {}", pk),
+            MusigError::BadPartialSignature(pk) => {
+                write!(f, "bad partial signature from signer {}", pk)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MusigError {}
+
+/// The aggregate of a set of public keys. Signers are sorted, so the aggregate key doesn't depend on the order they are given in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AggregateKey {
+    signers: Vec<(Ed25519PK, Scalar, EdwardsPoint)>,
+    pk: Ed25519PK,
+}
+
+impl AggregateKey {
+    /// Aggregates the public keys of all the signers.
+    pub fn new(signers: &[Ed25519PK]) -> Result<Self, MusigError> {
+        let mut sorted = signers.to_vec();
+        sorted.sort_unstable_by_key(|pk| pk.0);
+        for pair in sorted.windows(2) {
+            if pair[0] == pair[1] {
+                return Err(MusigError::DuplicateSigner(pair[0]));
+            }
+        }
+        let mut list_hasher = Sha512::new().chain_update(b"tmelcrypt-musig-keylist");
+        for pk in sorted.iter() {
+            list_hasher.update(pk.0);
+        }
+        let list_hash = list_hasher.finalize();
+
+        let mut aggregate = EdwardsPoint::identity();
+        let mut toret = Vec::with_capacity(sorted.len());
+        for pk in sorted {
+            let point = CompressedEdwardsY(pk.0)
+                .decompress()
+                .ok_or(MusigError::InvalidPoint(pk))?;
+            let coeff = hash_to_scalar(&[b"tmelcrypt-musig-keyagg", &list_hash, &pk.0]);
+            aggregate += coeff * point;
+            toret.push((pk, coeff, point));
+        }
+        Ok(Self {
+            signers: toret,
+            pk: Ed25519PK(aggregate.compress().to_bytes()),
+        })
+    }
+
+    /// The aggregate public key, under which the final signature verifies.
+    pub fn public_key(&self) -> Ed25519PK {
+        self.pk
+    }
+
+    /// The signers' public keys, in sorted order.
+    pub fn signers(&self) -> impl Iterator<Item = Ed25519PK> + '_ {
+        self.signers.iter().map(|s| s.0)
+    }
+
+    fn signer(&self, pk: Ed25519PK) -> Result<&(Ed25519PK, Scalar, EdwardsPoint), MusigError> {
+        self.signers
+            .iter()
+            .find(|s| s.0 == pk)
+            .ok_or(MusigError::UnknownSigner(pk))
+    }
+}
+
+/// A signer's first-round message: commitments to two fresh nonces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Round1Message {
+    pub signer: Ed25519PK,
+    pub nonce1: CompressedEdwardsY,
+    pub nonce2: CompressedEdwardsY,
+}
+
+/// A signer's second-round message: their partial signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Round2Message {
+    pub signer: Ed25519PK,
+    pub partial: Scalar,
+}
+
+/// A signer waiting for everyone's [Round1Message]. Holds secret nonces, which are zeroed out on drop and can only be used once.
+pub struct MusigRound1 {
+    key: AggregateKey,
+    msg: Vec<u8>,
+    me: Ed25519PK,
+    secret: Scalar,
+    nonces: (Scalar, Scalar),
+}
+
+impl Drop for MusigRound1 {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+        self.nonces.0.zeroize();
+        self.nonces.1.zeroize();
+    }
+}
+
+impl MusigRound1 {
+    /// Starts signing `msg` as one of the signers of `key`, returning the message to send to the other signers.
+    pub fn new(
+        sk: &Ed25519SK,
+        key: &AggregateKey,
+        msg: &[u8],
+    ) -> Result<(Self, Round1Message), MusigError> {
+        let me = sk.to_public();
+        key.signer(me)?;
+        let nonces = (random_scalar(), random_scalar());
+        let round1 = Round1Message {
+            signer: me,
+            nonce1: (&nonces.0 * &ED25519_BASEPOINT_TABLE).compress(),
+            nonce2: (&nonces.1 * &ED25519_BASEPOINT_TABLE).compress(),
+        };
+        let state = Self {
+            key: key.clone(),
+            msg: msg.to_vec(),
+            me,
+            secret: sk.signing_scalar(),
+            nonces,
+        };
+        Ok((state, round1))
+    }
+
+    /// Given every signer's round-1 message (including our own), computes our partial signature.
+    pub fn finish(
+        self,
+        round1: &[Round1Message],
+    ) -> Result<(MusigRound2, Round2Message), MusigError> {
+        let mut nonces = Vec::with_capacity(self.key.signers.len());
+        for (pk, _, _) in self.key.signers.iter() {
+            let mut from_pk = round1.iter().filter(|m| m.signer == *pk);
+            let m = from_pk.next().ok_or(MusigError::MissingSigner(*pk))?;
+            if from_pk.next().is_some() {
+                return Err(MusigError::DuplicateSigner(*pk));
+            }
+            let r1 = m.nonce1.decompress().ok_or(MusigError::InvalidPoint(*pk))?;
+            let r2 = m.nonce2.decompress().ok_or(MusigError::InvalidPoint(*pk))?;
+            nonces.push((*pk, r1, r2));
+        }
+        if let Some(m) = round1.iter().find(|m| self.key.signer(m.signer).is_err()) {
+            return Err(MusigError::UnknownSigner(m.signer));
+        }
+
+        let agg1 = nonces.iter().fold(EdwardsPoint::identity(), |a, n| a + n.1);
+        let agg2 = nonces.iter().fold(EdwardsPoint::identity(), |a, n| a + n.2);
+        let b = hash_to_scalar(&[
+            b"tmelcrypt-musig-noncecoef",
+            &self.key.pk.0,
+            agg1.compress().as_bytes(),
+            agg2.compress().as_bytes(),
+            &self.msg,
+        ]);
+        let r = (agg1 + b * agg2).compress();
+        let c = ed25519_challenge(r.as_bytes(), &self.key.pk.0, &self.msg);
+        let (_, my_coeff, _) = self.key.signer(self.me)?;
+        let partial = self.nonces.0 + b * self.nonces.1 + c * my_coeff * self.secret;
+        let round2 = MusigRound2 {
+            key: self.key.clone(),
+            nonces,
+            b,
+            r,
+            c,
+        };
+        Ok((
+            round2,
+            Round2Message {
+                signer: self.me,
+                partial,
+            },
+        ))
+    }
+}
+
+/// A signer (or anyone else who saw the round-1 messages) waiting for everyone's [Round2Message].
+pub struct MusigRound2 {
+    key: AggregateKey,
+    nonces: Vec<(Ed25519PK, EdwardsPoint, EdwardsPoint)>,
+    b: Scalar,
+    r: CompressedEdwardsY,
+    c: Scalar,
+}
+
+impl MusigRound2 {
+    /// Given every signer's partial signature, checks each one and combines them into an ordinary 64-byte ed25519 signature.
+    pub fn finish(self, round2: &[Round2Message]) -> Result<Vec<u8>, MusigError> {
+        let mut s = Scalar::zero();
+        for (pk, r1, r2) in self.nonces.iter() {
+            let mut from_pk = round2.iter().filter(|m| m.signer == *pk);
+            let m = from_pk.next().ok_or(MusigError::MissingSigner(*pk))?;
+            if from_pk.next().is_some() {
+                return Err(MusigError::DuplicateSigner(*pk));
+            }
+            let (_, coeff, point) = self.key.signer(*pk)?;
+            if &m.partial * &ED25519_BASEPOINT_TABLE != r1 + self.b * r2 + (self.c * coeff) * point
+            {
+                return Err(MusigError::BadPartialSignature(*pk));
+            }
+            s += m.partial;
+        }
+        let mut sig = self.r.to_bytes().to_vec();
+        sig.extend_from_slice(s.as_bytes());
+        Ok(sig)
+    }
+}
+
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    let hash: [u8; 64] = hasher.finalize().into();
+    Scalar::from_bytes_mod_order_wide(&hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn musig_roundtrip() {
+        let sks: Vec<Ed25519SK> = (0..3).map(|_| Ed25519SK::generate()).collect();
+        let pks: Vec<Ed25519PK> = sks.iter().map(|sk| sk.to_public()).collect();
+        let key = AggregateKey::new(&pks).unwrap();
+        let mut reversed = pks.clone();
+        reversed.reverse();
+        assert_eq!(AggregateKey::new(&reversed).unwrap(), key);
+
+        let (states, round1): (Vec<_>, Vec<_>) = sks
+            .iter()
+            .map(|sk| MusigRound1::new(sk, &key, b"spend").unwrap())
+            .unzip();
+        let round1: Vec<Round1Message> = round1
+            .iter()
+            .map(|m| stdcode::deserialize(&stdcode::serialize(m).unwrap()).unwrap())
+            .collect();
+        let (states, round2): (Vec<_>, Vec<_>) = states
+            .into_iter()
+            .map(|s| s.finish(&round1).unwrap())
+            .unzip();
+        let mut states = states;
+        let mut bad = round2.clone();
+        bad[1].partial += Scalar::one();
+        assert_eq!(
+            states.pop().unwrap().finish(&bad),
+            Err(MusigError::BadPartialSignature(bad[1].signer))
+        );
+        let mut sigs = states.into_iter().map(|s| s.finish(&round2).unwrap());
+        let sig = sigs.next().unwrap();
+        assert!(sigs.all(|s| s == sig));
+        assert!(key.public_key().verify(b"spend", &sig));
+
+        let (state, _) = MusigRound1::new(&sks[0], &key, b"spend").unwrap();
+        assert_eq!(
+            state.finish(&round1[..2]).err(),
+            Some(MusigError::MissingSigner(round1[2].signer))
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! The Poseidon hash function, which is cheap to compute inside zero-knowledge circuits, with outputs as [HashVal]s.
+//!
+//! This uses the BN254 scalar field with the circomlib parameters, so results match circomlib's `Poseidon(n)` template and anything compatible with it. Inputs and outputs are field elements encoded as 32-byte big-endian integers.
+//!
+//! Only BN254 is supported for now: there's no vetted, widely used parameter set for BLS12-381 among the Poseidon implementations this crate can depend on, and using made-up round constants would defeat the purpose of matching circuits.
+
+use std::fmt;
+
+use ark_bn254::Fr;
+use light_poseidon::{Poseidon, PoseidonBytesHasher};
+
+use crate::HashVal;
+
+/// The maximum number of inputs to [poseidon_bn254].
+pub const MAX_INPUTS: usize = 12;
+
+/// Error returned when the inputs to [poseidon_bn254] are not valid field elements, or there are too few or too many of them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoseidonError(String);
+
+impl fmt::Display for PoseidonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid poseidon input: {}", self.0)
+    }
+}
+
+impl std::error::Error for PoseidonError {}
+
+/// Hashes between 1 and [MAX_INPUTS] BN254 field elements. Each input must be less than the field modulus, which arbitrary 32-byte values such as blake3 hashes often aren't; use [poseidon_bn254_hashval] for those.
+pub fn poseidon_bn254(inputs: &[HashVal]) -> Result<HashVal, PoseidonError> {
+    if inputs.is_empty() || inputs.len() > MAX_INPUTS {
+        return Err(PoseidonError(format!(
+            "{} inputs given, expected 1 to {}",
+            inputs.len(),
+            MAX_INPUTS
+        )));
+    }
+    let mut hasher =
+        Poseidon::<Fr>::new_circom(inputs.len()).map_err(|e| PoseidonError(e.to_string()))?;
+    let inputs: Vec<&[u8]> = inputs.iter().map(|h| &h.0[..]).collect();
+    hasher
+        .hash_bytes_be(&inputs)
+        .map(HashVal)
+        .map_err(|e| PoseidonError(e.to_string()))
+}
+
+/// Hashes an arbitrary 32-byte value, by splitting it into two 128-bit big-endian halves that are always valid field elements. A circuit commits to the same value by hashing the same two halves.
+pub fn poseidon_bn254_hashval(val: HashVal) -> HashVal {
+    let mut hi = [0u8; 32];
+    let mut lo = [0u8; 32];
+    hi[16..].copy_from_slice(&val.0[..16]);
+    lo[16..].copy_from_slice(&val.0[16..]);
+    poseidon_bn254(&[HashVal(hi), HashVal(lo)]).expect("128-bit inputs are always valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small(n: u8) -> HashVal {
+        let mut bytes = [0u8; 32];
+        bytes[31] = n;
+        HashVal(bytes)
+    }
+
+    #[test]
+    fn circomlib_compatible() {
+        // poseidon([1, 2]) from circomlib's test suite
+        assert_eq!(
+            poseidon_bn254(&[small(1), small(2)]).unwrap().to_string(),
+            "115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a"
+        );
+        assert!(poseidon_bn254(&[HashVal([0xff; 32])]).is_err());
+        assert!(poseidon_bn254(&[]).is_err());
+        assert_ne!(
+            poseidon_bn254_hashval(HashVal([0xff; 32])),
+            poseidon_bn254_hashval(HashVal([0xfe; 32]))
+        );
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{context_signed_hash, Ed25519PK, Ed25519SK, Signature};
+
+/// The statement signed by a proof of possession: the key itself, and the context it's being registered in.
+fn possession_statement(pk: &Ed25519PK, context: &[u8]) -> Vec<u8> {
+    let mut statement = pk.0.to_vec();
+    statement.extend_from_slice(context);
+    statement
+}
+
+const POSSESSION_DOMAIN: &[u8] = b"tmelcrypt-proof-of-possession";
+
+impl Ed25519SK {
+    /// Proves that the holder of this key knows its secret, by signing a domain-separated statement about the public key itself. `context` should identify where the key is being registered, such as a staking contract and epoch, so that a proof can't be replayed elsewhere.
+    ///
+    /// Requiring such a proof when registering keys that will later be aggregated prevents rogue-key attacks, where an attacker registers a key computed from others' keys so that the aggregate is one they control.
+    pub fn prove_possession(&self, context: &[u8]) -> Signature {
+        let statement = possession_statement(&self.to_public(), context);
+        self.sign_to_signature(&context_signed_hash(POSSESSION_DOMAIN, &statement))
+    }
+}
+
+impl Ed25519PK {
+    /// Verifies a proof from [Ed25519SK::prove_possession] for the same `context`. Keys that aren't [safe](Ed25519PK::is_safe) are always rejected, since a small-order key has no secret to possess. A proof is never a valid ordinary signature, nor a proof for another context.
+    pub fn verify_possession(&self, context: &[u8], proof: &Signature) -> bool {
+        self.is_safe()
+            && self.verify_with_context(
+                POSSESSION_DOMAIN,
+                &possession_statement(self, context),
+                proof,
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proof_of_possession() {
+        let sk = Ed25519SK::generate();
+        let pk = sk.to_public();
+        let proof = sk.prove_possession(b"staking epoch 5");
+        assert!(pk.verify_possession(b"staking epoch 5", &proof));
+        assert!(!pk.verify_possession(b"staking epoch 6", &proof));
+        assert!(!Ed25519SK::generate()
+            .to_public()
+            .verify_possession(b"staking epoch 5", &proof));
+        assert!(!pk.verify(b"staking epoch 5", &proof));
+    }
+}
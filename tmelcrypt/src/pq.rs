@@ -0,0 +1,250 @@
+//! Post-quantum signatures, using ML-DSA-65 (Dilithium, FIPS 204).
+//!
+//! These are meant for dual-signing alongside ed25519 ahead of any post-quantum migration, not as a replacement yet. Keys and signatures are much larger than ed25519's: 1952-byte public keys and 3309-byte signatures. Secret keys are stored as their 32-byte seed, and expanded when signing.
+//!
+//! Like the ed25519 types, public keys and signatures serialize as hex in human-readable formats and as raw bytes in binary formats.
+//!
+//! [HybridSK], [HybridPK] and [HybridSig] bundle an ed25519 key or signature with an ML-DSA one, so that a service can start requiring both today and later drop ed25519 without changing its message format again.
+
+use std::{convert::TryInto, fmt, str::FromStr};
+
+use fips204::{
+    ml_dsa_65,
+    traits::{KeyGen, SerDes, Signer, Verifier},
+};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::{ct_eq, fill_random, Ed25519PK, Ed25519SK, EntropyRng, Signature};
+
+/// The length of an ML-DSA-65 public key, in bytes.
+pub const MLDSA_PK_LEN: usize = ml_dsa_65::PK_LEN;
+/// The length of an ML-DSA-65 signature, in bytes.
+pub const MLDSA_SIG_LEN: usize = ml_dsa_65::SIG_LEN;
+
+/// An ML-DSA-65 secret key, stored as its 32-byte seed. Zeroed out on drop, and deliberately not [Copy].
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MlDsaSK(#[serde(with = "stdcode::hex32")] pub [u8; 32]);
+
+/// An ML-DSA-65 public key.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MlDsaPK(#[serde(with = "stdcode::hex")] pub Vec<u8>);
+
+/// An ML-DSA-65 signature.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MlDsaSig(#[serde(with = "stdcode::hex")] pub Vec<u8>);
+
+/// A hybrid secret key, pairing an ed25519 key with an ML-DSA-65 key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HybridSK {
+    pub ed25519: Ed25519SK,
+    pub mldsa: MlDsaSK,
+}
+
+/// A hybrid public key, pairing an ed25519 key with an ML-DSA-65 key.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HybridPK {
+    pub ed25519: Ed25519PK,
+    pub mldsa: MlDsaPK,
+}
+
+/// A hybrid signature: an ed25519 signature and an ML-DSA-65 signature over the same message. It is valid only if both are, so forging one takes breaking both schemes.
+///
+/// Since the ed25519 half is an ordinary signature on the message, it still verifies on its own with [Ed25519PK::verify]. Verifiers that accept hybrid signatures should therefore not also accept bare ed25519 signatures from the same key, or an attacker who can break ed25519 could simply strip the ML-DSA half.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct HybridSig {
+    pub ed25519: Signature,
+    pub mldsa: MlDsaSig,
+}
+
+impl MlDsaSK {
+    /// Generates a new random key.
+    pub fn generate() -> Self {
+        let mut seed = [0u8; 32];
+        fill_random(&mut seed);
+        Self(seed)
+    }
+
+    /// The public key.
+    pub fn to_public(&self) -> MlDsaPK {
+        let (pk, _) = ml_dsa_65::KG::keygen_from_seed(&self.0);
+        MlDsaPK(pk.into_bytes().to_vec())
+    }
+
+    /// Signs a message. Signing is hedged: it mixes fresh randomness from the crate's entropy source into the deterministic nonce.
+    pub fn sign(&self, msg: &[u8]) -> MlDsaSig {
+        let (_, sk) = ml_dsa_65::KG::keygen_from_seed(&self.0);
+        let sig = sk
+            .try_sign_with_rng(&mut EntropyRng, msg, b"")
+            .expect("ML-DSA signing failed");
+        MlDsaSig(sig.to_vec())
+    }
+}
+
+impl MlDsaPK {
+    /// Verifies a signature. Malformed keys and signatures are simply invalid.
+    pub fn verify(&self, msg: &[u8], sig: &MlDsaSig) -> bool {
+        let pk: [u8; MLDSA_PK_LEN] = match self.0.as_slice().try_into() {
+            Ok(pk) => pk,
+            Err(_) => return false,
+        };
+        let sig: [u8; MLDSA_SIG_LEN] = match sig.0.as_slice().try_into() {
+            Ok(sig) => sig,
+            Err(_) => return false,
+        };
+        match ml_dsa_65::PublicKey::try_from_bytes(pk) {
+            Ok(pk) => pk.verify(msg, &sig, b""),
+            Err(_) => false,
+        }
+    }
+}
+
+impl HybridSK {
+    /// Generates a new random key pair.
+    pub fn generate() -> Self {
+        Self {
+            ed25519: Ed25519SK::generate(),
+            mldsa: MlDsaSK::generate(),
+        }
+    }
+
+    /// The public key.
+    pub fn to_public(&self) -> HybridPK {
+        HybridPK {
+            ed25519: self.ed25519.to_public(),
+            mldsa: self.mldsa.to_public(),
+        }
+    }
+
+    /// Signs a message with both keys.
+    pub fn sign(&self, msg: &[u8]) -> HybridSig {
+        HybridSig {
+            ed25519: self.ed25519.sign_to_signature(msg),
+            mldsa: self.mldsa.sign(msg),
+        }
+    }
+}
+
+impl HybridPK {
+    /// Verifies a hybrid signature, which requires both halves to be valid.
+    pub fn verify(&self, msg: &[u8], sig: &HybridSig) -> bool {
+        self.ed25519.verify(msg, &sig.ed25519) && self.mldsa.verify(msg, &sig.mldsa)
+    }
+}
+
+impl PartialEq for MlDsaSK {
+    /// Compares the keys in constant time.
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for MlDsaSK {}
+
+impl Drop for MlDsaSK {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for MlDsaSK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MlDsaSK({:?})", self.to_public())
+    }
+}
+
+impl fmt::Debug for MlDsaPK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#<MlDsaPK:{}>",
+            hex::encode(&self.0[..self.0.len().min(5)])
+        )
+    }
+}
+
+impl fmt::Debug for MlDsaSig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#<MlDsaSig:{}>",
+            hex::encode(&self.0[..self.0.len().min(5)])
+        )
+    }
+}
+
+impl fmt::Display for MlDsaPK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::encode(&self.0).fmt(f)
+    }
+}
+
+impl FromStr for MlDsaPK {
+    type Err = hex::FromHexError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(MlDsaPK(hex::decode(s)?))
+    }
+}
+
+impl fmt::Display for MlDsaSig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::encode(&self.0).fmt(f)
+    }
+}
+
+impl FromStr for MlDsaSig {
+    type Err = hex::FromHexError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(MlDsaSig(hex::decode(s)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mldsa_roundtrip() {
+        let sk = MlDsaSK::generate();
+        let pk = sk.to_public();
+        assert_eq!(pk.0.len(), MLDSA_PK_LEN);
+        let sig = sk.sign(b"checkpoint");
+        assert_eq!(sig.0.len(), MLDSA_SIG_LEN);
+        assert!(pk.verify(b"checkpoint", &sig));
+        assert!(!pk.verify(b"other", &sig));
+        assert!(!MlDsaSK::generate().to_public().verify(b"checkpoint", &sig));
+
+        let json = serde_json::to_string(&pk).unwrap();
+        assert_eq!(json, format!("\"{}\"", pk));
+        assert_eq!(serde_json::from_str::<MlDsaPK>(&json).unwrap(), pk);
+        let sig: MlDsaSig = stdcode::deserialize(&stdcode::serialize(&sig).unwrap()).unwrap();
+        assert!(pk.verify(b"checkpoint", &sig));
+    }
+
+    #[test]
+    fn hybrid_roundtrip() {
+        let sk = HybridSK::generate();
+        let pk = sk.to_public();
+        let sig = sk.sign(b"checkpoint");
+        assert!(pk.verify(b"checkpoint", &sig));
+        assert!(!pk.verify(b"other", &sig));
+        let sig: HybridSig = stdcode::deserialize(&stdcode::serialize(&sig).unwrap()).unwrap();
+        assert!(pk.verify(b"checkpoint", &sig));
+
+        // both halves must be valid
+        let other = HybridSK::generate().sign(b"checkpoint");
+        let mixed = HybridSig {
+            ed25519: sig.ed25519,
+            mldsa: other.mldsa.clone(),
+        };
+        assert!(!pk.verify(b"checkpoint", &mixed));
+        let mixed = HybridSig {
+            ed25519: other.ed25519,
+            mldsa: sig.mldsa,
+        };
+        assert!(!pk.verify(b"checkpoint", &mixed));
+    }
+}
@@ -0,0 +1,121 @@
+use std::{convert::TryInto, io};
+
+use arrayref::array_ref;
+use curve25519_dalek_ng::{
+    constants::ED25519_BASEPOINT_TABLE,
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+};
+use sha2::{Digest, Sha512};
+use zeroize::Zeroize;
+
+use crate::{Ed25519PK, Ed25519SK};
+
+/// Computes the SHA-512 prehash of everything read from `reader`, for [Ed25519SK::sign_prehashed] and [Ed25519PK::verify_prehashed]. Only a small buffer is held in memory at a time, no matter how long the input is.
+pub fn prehash_reader(mut reader: impl io::Read) -> io::Result<[u8; 64]> {
+    let mut hasher = Sha512::new();
+    io::copy(&mut reader, &mut hasher)?;
+    Ok(hasher.finalize().into())
+}
+
+/// The `dom2` prefix of RFC 8032 for Ed25519ph, binding the prehash flag and the context.
+fn dom2(context: &[u8]) -> Sha512 {
+    assert!(context.len() <= 255, "context must be at most 255 bytes");
+    Sha512::new()
+        .chain_update(b"SigEd25519 no Ed25519 collisions")
+        .chain_update([1, context.len() as u8])
+        .chain_update(context)
+}
+
+fn scalar_from_hash(hasher: Sha512) -> Scalar {
+    let hash: [u8; 64] = hasher.finalize().into();
+    Scalar::from_bytes_mod_order_wide(&hash)
+}
+
+impl Ed25519SK {
+    /// Signs a message in the Ed25519ph mode of RFC 8032, given the SHA-512 hash of the message rather than the message itself, so that huge messages can be signed without holding them in memory. See [prehash_reader].
+    ///
+    /// The context, which may be empty, is an optional domain separator of up to 255 bytes; the verifier must use the same context. Ed25519ph signatures never verify as ordinary ed25519 signatures, or vice versa. Panics if the context is too long.
+    pub fn sign_prehashed(&self, prehash: &[u8; 64], context: &[u8]) -> Vec<u8> {
+        let mut expanded: [u8; 64] = Sha512::digest(&self.0[..32]).into();
+        let secret = self.signing_scalar();
+        let r = scalar_from_hash(
+            dom2(context)
+                .chain_update(&expanded[32..])
+                .chain_update(prehash),
+        );
+        expanded.zeroize();
+        let big_r = (&r * &ED25519_BASEPOINT_TABLE).compress();
+        // the public key is derived from the seed, never read from the stored public half, which a caller could have tampered with to extract the secret scalar
+        let public = (&secret * &ED25519_BASEPOINT_TABLE).compress();
+        let k = scalar_from_hash(
+            dom2(context)
+                .chain_update(big_r.as_bytes())
+                .chain_update(public.as_bytes())
+                .chain_update(prehash),
+        );
+        let s = r + k * secret;
+        let mut sig = big_r.to_bytes().to_vec();
+        sig.extend_from_slice(s.as_bytes());
+        sig
+    }
+}
+
+impl Ed25519PK {
+    /// Verifies an Ed25519ph signature produced by [Ed25519SK::sign_prehashed] with the same context.
+    pub fn verify_prehashed(&self, prehash: &[u8; 64], context: &[u8], sig: &[u8]) -> bool {
+        if sig.len() != 64 || context.len() > 255 {
+            return false;
+        }
+        let pk = match CompressedEdwardsY(self.0).decompress() {
+            Some(pk) => pk,
+            None => return false,
+        };
+        let s = match Scalar::from_canonical_bytes(*array_ref![sig, 32, 32]) {
+            Some(s) => s,
+            None => return false,
+        };
+        let k = scalar_from_hash(
+            dom2(context)
+                .chain_update(&sig[..32])
+                .chain_update(self.0)
+                .chain_update(prehash),
+        );
+        let expected_r = EdwardsPoint::vartime_double_scalar_mul_basepoint(&k, &-pk, &s);
+        let r: [u8; 32] = sig[..32].try_into().unwrap();
+        expected_r.compress().to_bytes() == r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc8032_ed25519ph() {
+        // the Ed25519ph test vector from RFC 8032, section 7.3
+        let seed = hex::decode("833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42")
+            .unwrap();
+        let sk = Ed25519SK::from_seed(seed.try_into().unwrap());
+        let prehash = prehash_reader(&b"abc"[..]).unwrap();
+        let sig = sk.sign_prehashed(&prehash, b"");
+        assert_eq!(hex::encode(&sig), "98a70222f0b8121aa9d30f813d683f809e462b469c7ff87639499bb94e6dae4131f85042463c2a355a2003d062adf5aaa10b8c61e636062aaad11c2a26083406");
+        let pk = sk.to_public();
+        assert!(pk.verify_prehashed(&prehash, b"", &sig));
+        assert!(!pk.verify_prehashed(&prehash, b"ctx", &sig));
+        assert!(!pk.verify(b"abc", &sig));
+    }
+
+    #[test]
+    fn tampered_public_half_ignored() {
+        let sk = Ed25519SK::generate();
+        let prehash = prehash_reader(&b"abc"[..]).unwrap();
+        let mut bytes = sk.0;
+        bytes[32..].copy_from_slice(&Ed25519SK::generate().0[32..]);
+        let tampered = Ed25519SK(bytes);
+        // the nonce only depends on the seed and the prehash, so a signature bound to the wrong public key would leak the secret
+        let sig = tampered.sign_prehashed(&prehash, b"");
+        assert_eq!(sig, sk.sign_prehashed(&prehash, b""));
+        assert!(sk.to_public().verify_prehashed(&prehash, b"", &sig));
+    }
+}
@@ -0,0 +1,218 @@
+//! Ring signatures over ed25519 keys.
+//!
+//! A [RingSignature] proves that a message was signed by the secret key of *one* of a set of ordinary [Ed25519PK]s (the "ring"), without revealing which one. Anyone who knows the ring can verify it.
+//!
+//! Signatures can optionally be made linkable within a *scope*, such as the identifier of a vote. A linkable signature carries a [LinkTag] that depends only on the signer and the scope: two signatures by the same key in the same scope have the same tag, even under different rings and messages, while tags in different scopes are unrelated. This lets a tally detect double votes without learning who voted.
+//!
+//! The construction is the linkable spontaneous anonymous group signature of Liu, Wei and Wong, with the tag base derived from the scope rather than the signer's key.
+
+use std::fmt;
+
+use arrayref::array_ref;
+use curve25519_dalek_ng::{
+    constants::ED25519_BASEPOINT_TABLE,
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+use crate::{random_scalar, Ed25519PK, Ed25519SK};
+
+/// A tag identifying the signer of a linkable [RingSignature] within one scope.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LinkTag(pub [u8; 32]);
+
+/// A signature by an unknown member of a ring of public keys. Its size is linear in the size of the ring.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RingSignature {
+    c0: Scalar,
+    responses: Vec<Scalar>,
+    tag: Option<LinkTag>,
+}
+
+impl RingSignature {
+    /// Signs `msg` as a member of `ring`, which must contain the public key of `sk`. If `link_scope` is given, the signature carries a [LinkTag] for that scope.
+    ///
+    /// Returns `None` if the signer isn't in the ring, or if some key in the ring isn't a valid curve point.
+    pub fn sign(
+        sk: &Ed25519SK,
+        ring: &[Ed25519PK],
+        msg: &[u8],
+        link_scope: Option<&[u8]>,
+    ) -> Option<Self> {
+        let me = ring.iter().position(|pk| *pk == sk.to_public())?;
+        let points = decompress_ring(ring)?;
+        let secret = sk.signing_scalar();
+        let base = link_scope.map(tag_base);
+        let tag_point = base.map(|base| secret * base);
+        let tag = tag_point.map(|p| LinkTag(p.compress().to_bytes()));
+        let prefix = prefix_hasher(ring, msg, link_scope, tag);
+
+        let n = ring.len();
+        let mut challenges = vec![Scalar::zero(); n];
+        let mut responses = vec![Scalar::zero(); n];
+        let alpha = random_scalar();
+        let mut c = next_challenge(
+            &prefix,
+            &(&alpha * &ED25519_BASEPOINT_TABLE),
+            base.map(|base| alpha * base),
+        );
+        for i in (me + 1..n).chain(0..me) {
+            challenges[i] = c;
+            responses[i] = random_scalar();
+            let (l, r) = commitments(&points[i], responses[i], c, base, tag_point);
+            c = next_challenge(&prefix, &l, r);
+        }
+        challenges[me] = c;
+        responses[me] = alpha - c * secret;
+        Some(Self {
+            c0: challenges[0],
+            responses,
+            tag,
+        })
+    }
+
+    /// Verifies that this signature was made over `msg` by a member of `ring`, in the scope `link_scope`. The ring must be in the same order as when signing.
+    pub fn verify(&self, ring: &[Ed25519PK], msg: &[u8], link_scope: Option<&[u8]>) -> bool {
+        if ring.is_empty() || ring.len() != self.responses.len() {
+            return false;
+        }
+        let points = match decompress_ring(ring) {
+            Some(points) => points,
+            None => return false,
+        };
+        let (base, tag_point) = match (link_scope, self.tag) {
+            (None, None) => (None, None),
+            (Some(scope), Some(tag)) => match decompress_canonical(&tag.0) {
+                Some(p) if p.is_torsion_free() => (Some(tag_base(scope)), Some(p)),
+                _ => return false,
+            },
+            _ => return false,
+        };
+        let prefix = prefix_hasher(ring, msg, link_scope, self.tag);
+        let mut c = self.c0;
+        for (point, response) in points.iter().zip(self.responses.iter()) {
+            let (l, r) = commitments(point, *response, c, base, tag_point);
+            c = next_challenge(&prefix, &l, r);
+        }
+        c == self.c0
+    }
+
+    /// The link tag, if this signature is linkable.
+    pub fn link_tag(&self) -> Option<LinkTag> {
+        self.tag
+    }
+}
+
+fn decompress_ring(ring: &[Ed25519PK]) -> Option<Vec<EdwardsPoint>> {
+    ring.iter().map(|pk| decompress_canonical(&pk.0)).collect()
+}
+
+/// Decompresses a point, rejecting non-canonical encodings so that every point has exactly one valid encoding.
+fn decompress_canonical(bytes: &[u8; 32]) -> Option<EdwardsPoint> {
+    let point = CompressedEdwardsY(*bytes).decompress()?;
+    if point.compress().as_bytes() != bytes {
+        return None;
+    }
+    Some(point)
+}
+
+/// The point that linkable signers multiply by their secret to get their tag, found by try-and-increment.
+fn tag_base(scope: &[u8]) -> EdwardsPoint {
+    (0..=u32::MAX)
+        .find_map(|ctr| {
+            let hash = Sha512::new()
+                .chain_update(b"tmelcrypt-ring-tagbase")
+                .chain_update((scope.len() as u64).to_le_bytes())
+                .chain_update(scope)
+                .chain_update(ctr.to_le_bytes())
+                .finalize();
+            CompressedEdwardsY(*array_ref![hash, 0, 32])
+                .decompress()
+                .map(|p| p.mul_by_cofactor())
+                .filter(|p| !p.is_small_order())
+        })
+        .expect("no tag base found")
+}
+
+fn prefix_hasher(
+    ring: &[Ed25519PK],
+    msg: &[u8],
+    link_scope: Option<&[u8]>,
+    tag: Option<LinkTag>,
+) -> Sha512 {
+    let mut hasher = Sha512::new()
+        .chain_update(b"tmelcrypt-ring")
+        .chain_update((ring.len() as u64).to_le_bytes());
+    for pk in ring {
+        hasher.update(pk.0);
+    }
+    hasher.update((msg.len() as u64).to_le_bytes());
+    hasher.update(msg);
+    if let (Some(scope), Some(tag)) = (link_scope, tag) {
+        hasher.update((scope.len() as u64).to_le_bytes());
+        hasher.update(scope);
+        hasher.update(tag.0);
+    }
+    hasher
+}
+
+fn commitments(
+    point: &EdwardsPoint,
+    response: Scalar,
+    challenge: Scalar,
+    base: Option<EdwardsPoint>,
+    tag_point: Option<EdwardsPoint>,
+) -> (EdwardsPoint, Option<EdwardsPoint>) {
+    let l = &response * &ED25519_BASEPOINT_TABLE + challenge * point;
+    let r = base
+        .zip(tag_point)
+        .map(|(base, tag)| response * base + challenge * tag);
+    (l, r)
+}
+
+fn next_challenge(prefix: &Sha512, l: &EdwardsPoint, r: Option<EdwardsPoint>) -> Scalar {
+    let mut hasher = prefix.clone().chain_update(l.compress().as_bytes());
+    if let Some(r) = r {
+        hasher.update(r.compress().as_bytes());
+    }
+    let hash: [u8; 64] = hasher.finalize().into();
+    Scalar::from_bytes_mod_order_wide(&hash)
+}
+
+impl fmt::Debug for LinkTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#<LinkTag:{}>", hex::encode(&self.0[..5]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ring_roundtrip() {
+        let sks: Vec<Ed25519SK> = (0..4).map(|_| Ed25519SK::generate()).collect();
+        let ring: Vec<Ed25519PK> = sks.iter().map(|sk| sk.to_public()).collect();
+
+        let sig = RingSignature::sign(&sks[2], &ring, b"hello", None).unwrap();
+        assert!(sig.verify(&ring, b"hello", None));
+        assert!(!sig.verify(&ring, b"other", None));
+        assert!(!sig.verify(&ring[..3], b"hello", None));
+        assert!(!sig.verify(&ring, b"hello", Some(b"vote")));
+        assert!(RingSignature::sign(&Ed25519SK::generate(), &ring, b"hello", None).is_none());
+
+        let vote1 = RingSignature::sign(&sks[1], &ring, b"yes", Some(b"vote")).unwrap();
+        let vote2 = RingSignature::sign(&sks[1], &ring[..2], b"no", Some(b"vote")).unwrap();
+        let vote3 = RingSignature::sign(&sks[3], &ring, b"yes", Some(b"vote")).unwrap();
+        let other = RingSignature::sign(&sks[1], &ring, b"yes", Some(b"poll")).unwrap();
+        assert!(vote1.verify(&ring, b"yes", Some(b"vote")));
+        assert!(vote2.verify(&ring[..2], b"no", Some(b"vote")));
+        assert!(!vote1.verify(&ring, b"yes", Some(b"poll")));
+        assert!(!vote1.verify(&ring, b"yes", None));
+        assert_eq!(vote1.link_tag(), vote2.link_tag());
+        assert_ne!(vote1.link_tag(), vote3.link_tag());
+        assert_ne!(vote1.link_tag(), other.link_tag());
+    }
+}
@@ -0,0 +1,185 @@
+use std::convert::{TryFrom, TryInto};
+
+use k256::{
+    elliptic_curve::{ops::Reduce, point::DecompactPoint, Field, PrimeField},
+    schnorr::{Signature, SigningKey, VerifyingKey},
+    AffinePoint, ProjectivePoint, Scalar, U256,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{fill_random, EntropyRng};
+
+/// A BIP340 Schnorr public key over secp256k1: the 32-byte x-coordinate of a point with even y, as used by Taproot.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct SchnorrPK(#[serde(with = "stdcode::hex32")] pub [u8; 32]);
+
+/// A BIP340 Schnorr secret key. Zeroed out on drop. It can only hold a valid key: parsing and deserializing reject zero and values not less than the curve order.
+#[derive(Clone, Serialize)]
+pub struct SchnorrSK(#[serde(serialize_with = "stdcode::hex32::serialize")] [u8; 32]);
+
+/// A 64-byte BIP340 Schnorr signature.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct SchnorrSig(#[serde(with = "stdcode::hexarray")] pub [u8; 64]);
+
+impl SchnorrSK {
+    pub fn generate() -> Self {
+        Self(SigningKey::random(&mut EntropyRng).to_bytes().into())
+    }
+
+    /// Creates a secret key from its 32 bytes, returning `None` if they are zero or not less than the curve order.
+    pub fn from_bytes(bts: [u8; 32]) -> Option<Self> {
+        SigningKey::from_bytes(&bts).ok()?;
+        Some(Self(bts))
+    }
+
+    /// The secret key's 32 bytes, for intentionally exporting it.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    pub fn to_public(&self) -> SchnorrPK {
+        SchnorrPK(self.inner().verifying_key().to_bytes().into())
+    }
+
+    /// Signs a message, exactly as BIP340 specifies: the message is not hashed first.
+    pub fn sign(&self, msg: &[u8]) -> SchnorrSig {
+        let mut aux_rand = [0u8; 32];
+        fill_random(&mut aux_rand);
+        self.sign_with_aux_rand(msg, &aux_rand)
+    }
+
+    /// Signs a message with caller-supplied auxiliary randomness. This is deterministic, which is mostly useful for test vectors.
+    pub fn sign_with_aux_rand(&self, msg: &[u8], aux_rand: &[u8; 32]) -> SchnorrSig {
+        let sig = self
+            .inner()
+            .sign_raw(msg, aux_rand)
+            .expect("BIP340 signing cannot fail");
+        SchnorrSig(sig.to_bytes())
+    }
+
+    fn inner(&self) -> SigningKey {
+        SigningKey::from_bytes(&self.0).expect("secret keys are validated on construction")
+    }
+}
+
+secret_key_impls!(SchnorrSK, stdcode::hex32::deserialize);
+
+impl SchnorrPK {
+    /// Verifies a BIP340 signature.
+    pub fn verify(&self, msg: &[u8], sig: &SchnorrSig) -> bool {
+        let vk = match VerifyingKey::from_bytes(&self.0) {
+            Ok(vk) => vk,
+            Err(_) => return false,
+        };
+        match Signature::try_from(&sig.0[..]) {
+            Ok(sig) => vk.verify_raw(msg, &sig).is_ok(),
+            Err(_) => false,
+        }
+    }
+}
+
+/// Verifies many BIP340 signatures at once, using the batch verification algorithm from the BIP. Returns true only if every signature is valid; an empty batch is valid.
+pub fn verify_batch_schnorr(items: &[(SchnorrPK, &[u8], SchnorrSig)]) -> bool {
+    // checks that (sum a_i s_i) G = sum a_i R_i + sum a_i e_i P_i, with a_0 = 1 and the other a_i random
+    let mut lhs = Scalar::ZERO;
+    let mut rhs = ProjectivePoint::IDENTITY;
+    for (i, (pk, msg, sig)) in items.iter().enumerate() {
+        let pk_point = match lift_x(&pk.0) {
+            Some(p) => p,
+            None => return false,
+        };
+        let r_bytes: [u8; 32] = sig.0[..32].try_into().unwrap();
+        let r_point = match lift_x(&r_bytes) {
+            Some(p) => p,
+            None => return false,
+        };
+        let s: Option<Scalar> =
+            Scalar::from_repr(*k256::FieldBytes::from_slice(&sig.0[32..])).into();
+        let s = match s {
+            Some(s) => s,
+            None => return false,
+        };
+        let e = <Scalar as Reduce<U256>>::reduce_bytes(
+            &challenge_hasher()
+                .chain_update(r_bytes)
+                .chain_update(pk.0)
+                .chain_update(msg)
+                .finalize(),
+        );
+        let a = if i == 0 {
+            Scalar::ONE
+        } else {
+            Scalar::random(&mut EntropyRng)
+        };
+        lhs += a * s;
+        rhs += r_point * a + pk_point * (a * e);
+    }
+    ProjectivePoint::GENERATOR * lhs == rhs
+}
+
+fn lift_x(x: &[u8; 32]) -> Option<ProjectivePoint> {
+    let point: Option<AffinePoint> = AffinePoint::decompact(x.into()).into();
+    point.map(ProjectivePoint::from)
+}
+
+fn challenge_hasher() -> Sha256 {
+    let tag_hash = Sha256::digest(b"BIP0340/challenge");
+    Sha256::new().chain_update(tag_hash).chain_update(tag_hash)
+}
+
+bytes_impls!(SchnorrPK, 32, "#<SchnorrPK:{}>");
+bytes_impls!(SchnorrSig, 64, "#<SchnorrSig:{}>");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bip340() {
+        // test vector 0 from BIP340
+        let mut three = [0u8; 32];
+        three[31] = 3;
+        let sk = SchnorrSK::from_bytes(three).unwrap();
+        assert_eq!(
+            sk.to_public().to_string(),
+            "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9"
+        );
+        assert_eq!(
+            sk.sign_with_aux_rand(&[0; 32], &[0; 32]).to_string(),
+            "e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca821525f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0"
+        );
+
+        let sks: Vec<SchnorrSK> = (0..5).map(|_| SchnorrSK::generate()).collect();
+        let mut items: Vec<(SchnorrPK, &[u8], SchnorrSig)> = sks
+            .iter()
+            .map(|sk| (sk.to_public(), &b"taproot"[..], sk.sign(b"taproot")))
+            .collect();
+        assert!(items.iter().all(|(pk, msg, sig)| pk.verify(msg, sig)));
+        assert!(verify_batch_schnorr(&items));
+        items[2].1 = b"other";
+        assert!(!verify_batch_schnorr(&items));
+
+        // hex in JSON, like the other key and signature types
+        let sig = items[0].2;
+        let json = serde_json::to_string(&sig).unwrap();
+        assert_eq!(json, format!("\"{}\"", sig));
+        assert_eq!(serde_json::from_str::<SchnorrSig>(&json).unwrap(), sig);
+        assert_eq!(sig.to_string().parse::<SchnorrSig>().unwrap(), sig);
+        assert_eq!(stdcode::serialize(&sig).unwrap(), sig.0.to_vec());
+
+        assert_eq!(sk.reveal_hex().parse::<SchnorrSK>().unwrap(), sk);
+        assert!(!sk.to_string().contains(&sk.reveal_hex()[..10]));
+        assert!(!format!("{:?}", sk).contains(&sk.reveal_hex()[..10]));
+        assert_eq!(
+            serde_json::from_str::<SchnorrSK>(&serde_json::to_string(&sk).unwrap()).unwrap(),
+            sk
+        );
+        assert!(serde_json::from_str::<SchnorrSK>(&format!("{:?}", "00".repeat(32))).is_err());
+        assert!(stdcode::deserialize::<SchnorrSK>(&[0; 32]).is_err());
+        assert_eq!(
+            "00".repeat(32).parse::<SchnorrSK>(),
+            Err(crate::Error::InvalidSecretKey)
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::convert::TryInto;
+
+use k256::ecdsa::{
+    signature::{
+        hazmat::{PrehashSigner, PrehashVerifier},
+        Signer, Verifier,
+    },
+    RecoveryId, Signature, SigningKey, VerifyingKey,
+};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::EntropyRng;
+
+/// A secp256k1 public key, in 33-byte compressed SEC1 form.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Secp256k1PK(#[serde(with = "stdcode::hexarray")] pub [u8; 33]);
+
+/// A secp256k1 secret key. Zeroed out on drop. It can only hold a valid key: parsing and deserializing reject zero and values not less than the curve order.
+#[derive(Clone, Serialize)]
+pub struct Secp256k1SK([u8; 32]);
+
+/// A secp256k1 ECDSA signature, as `r || s` with a low `s`.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Secp256k1Sig(#[serde(with = "stdcode::hexarray")] pub [u8; 64]);
+
+/// A recoverable secp256k1 ECDSA signature, as `r || s || v` with `v` the recovery ID (0 or 1), as used by Ethereum.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Secp256k1RecoverableSig(#[serde(with = "stdcode::hexarray")] pub [u8; 65]);
+
+impl Secp256k1SK {
+    pub fn generate() -> Self {
+        Self(SigningKey::random(&mut EntropyRng).to_bytes().into())
+    }
+
+    /// Creates a secret key from its 32 bytes, returning `None` if they are zero or not less than the curve order.
+    pub fn from_bytes(bts: [u8; 32]) -> Option<Self> {
+        SigningKey::from_bytes(&bts.into()).ok()?;
+        Some(Self(bts))
+    }
+
+    /// The secret key's 32 bytes, for intentionally exporting it.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+
+    pub fn to_public(&self) -> Secp256k1PK {
+        Secp256k1PK::from_verifying_key(self.inner().verifying_key())
+    }
+
+    /// Signs the SHA-256 hash of a message.
+    pub fn sign(&self, msg: &[u8]) -> Secp256k1Sig {
+        let sig: Signature = self.inner().sign(msg);
+        Secp256k1Sig(sig.to_bytes().into())
+    }
+
+    /// Signs a 32-byte hash computed by the caller, such as a Keccak-256 hash for Ethereum.
+    pub fn sign_prehash(&self, hash: &[u8; 32]) -> Secp256k1Sig {
+        let sig: Signature = self
+            .inner()
+            .sign_prehash(hash)
+            .expect("signing a 32-byte hash cannot fail");
+        Secp256k1Sig(sig.to_bytes().into())
+    }
+
+    /// Like [Secp256k1SK::sign_prehash], but includes the recovery ID, so that the public key can be recovered from the signature.
+    pub fn sign_prehash_recoverable(&self, hash: &[u8; 32]) -> Secp256k1RecoverableSig {
+        let (sig, recid) = self
+            .inner()
+            .sign_prehash_recoverable(hash)
+            .expect("signing a 32-byte hash cannot fail");
+        let mut out = [0u8; 65];
+        out[..64].copy_from_slice(&sig.to_bytes());
+        out[64] = recid.to_byte();
+        Secp256k1RecoverableSig(out)
+    }
+
+    fn inner(&self) -> SigningKey {
+        SigningKey::from_bytes(&self.0.into()).expect("secret keys are validated on construction")
+    }
+}
+
+impl Secp256k1PK {
+    fn from_verifying_key(vk: &VerifyingKey) -> Self {
+        Self(
+            vk.to_encoded_point(true)
+                .as_bytes()
+                .try_into()
+                .expect("compressed points are 33 bytes"),
+        )
+    }
+
+    /// Verifies a signature on the SHA-256 hash of a message.
+    pub fn verify(&self, msg: &[u8], sig: &Secp256k1Sig) -> bool {
+        match (self.inner(), sig.inner()) {
+            (Some(vk), Some(sig)) => vk.verify(msg, &sig).is_ok(),
+            _ => false,
+        }
+    }
+
+    /// Verifies a signature on a 32-byte hash computed by the caller.
+    pub fn verify_prehash(&self, hash: &[u8; 32], sig: &Secp256k1Sig) -> bool {
+        match (self.inner(), sig.inner()) {
+            (Some(vk), Some(sig)) => vk.verify_prehash(hash, &sig).is_ok(),
+            _ => false,
+        }
+    }
+
+    /// Recovers the public key that produced a recoverable signature of a hash.
+    pub fn recover_prehash(hash: &[u8; 32], sig: &Secp256k1RecoverableSig) -> Option<Self> {
+        let recid = RecoveryId::from_byte(sig.0[64])?;
+        let signature = Signature::from_slice(&sig.0[..64]).ok()?;
+        let vk = VerifyingKey::recover_from_prehash(hash, &signature, recid).ok()?;
+        Some(Self::from_verifying_key(&vk))
+    }
+
+    /// The 65-byte uncompressed SEC1 encoding of the key.
+    pub fn to_uncompressed(&self) -> Option<[u8; 65]> {
+        self.inner()?
+            .to_encoded_point(false)
+            .as_bytes()
+            .try_into()
+            .ok()
+    }
+
+    /// The Ethereum address of this key: the last 20 bytes of the Keccak-256 hash of the uncompressed key (without its `0x04` prefix).
+    pub fn eth_address(&self) -> Option<[u8; 20]> {
+        let uncompressed = self.to_uncompressed()?;
+        let hash = Keccak256::digest(&uncompressed[1..]);
+        hash[12..].try_into().ok()
+    }
+
+    fn inner(&self) -> Option<VerifyingKey> {
+        VerifyingKey::from_sec1_bytes(&self.0).ok()
+    }
+}
+
+impl Secp256k1Sig {
+    fn inner(&self) -> Option<Signature> {
+        Signature::from_slice(&self.0).ok()
+    }
+}
+
+bytes_impls!(Secp256k1PK, 33, "#<SecpPK:{}>");
+bytes_impls!(Secp256k1Sig, 64, "#<SecpSig:{}>");
+bytes_impls!(Secp256k1RecoverableSig, 65, "#<SecpRSig:{}>");
+
+secret_key_impls!(Secp256k1SK, <[u8; 32]>::deserialize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secp256k1() {
+        let sk = Secp256k1SK::generate();
+        let pk = sk.to_public();
+        assert!(pk.verify(b"hello", &sk.sign(b"hello")));
+        assert!(!pk.verify(b"world", &sk.sign(b"hello")));
+        // hex in JSON, like the other key and signature types
+        let sig = sk.sign(b"hello");
+        assert_eq!(serde_json::to_string(&pk).unwrap(), format!("\"{}\"", pk));
+        assert_eq!(
+            serde_json::from_str::<Secp256k1Sig>(&serde_json::to_string(&sig).unwrap()).unwrap(),
+            sig
+        );
+        assert_eq!(stdcode::serialize(&sig).unwrap(), sig.0.to_vec());
+
+        let hash = [42u8; 32];
+        let rsig = sk.sign_prehash_recoverable(&hash);
+        assert_eq!(Secp256k1PK::recover_prehash(&hash, &rsig), Some(pk));
+        assert_eq!(
+            serde_json::to_string(&rsig).unwrap(),
+            format!("\"{}\"", rsig)
+        );
+        assert_eq!(
+            serde_json::from_str::<Secp256k1RecoverableSig>(&serde_json::to_string(&rsig).unwrap())
+                .unwrap(),
+            rsig
+        );
+        assert_eq!(
+            rsig.to_string().parse::<Secp256k1RecoverableSig>().unwrap(),
+            rsig
+        );
+
+        // well-known key 1, whose Ethereum address is 0x7e5f...
+        let mut one = [0u8; 32];
+        one[31] = 1;
+        assert_eq!(
+            hex::encode(
+                Secp256k1SK::from_bytes(one)
+                    .unwrap()
+                    .to_public()
+                    .eth_address()
+                    .unwrap()
+            ),
+            "7e5f4552091a69125d5dfcb7b8c2659029395bdf"
+        );
+        assert!(Secp256k1SK::from_bytes([0; 32]).is_none());
+        assert!(stdcode::deserialize::<Secp256k1SK>(&[0; 32]).is_err());
+        assert_eq!(
+            "00".repeat(32).parse::<Secp256k1SK>(),
+            Err(crate::Error::InvalidSecretKey)
+        );
+        assert_eq!(sk.reveal_hex().parse::<Secp256k1SK>().unwrap(), sk);
+        assert!(!sk.to_string().contains(&sk.reveal_hex()[..10]));
+        assert!(!format!("{:?}", sk).contains(&sk.reveal_hex()[..10]));
+        assert_eq!(
+            stdcode::deserialize::<Secp256k1SK>(&stdcode::serialize(&sk).unwrap()).unwrap(),
+            sk
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::{ct_eq, fill_random, Ed25519PK, Ed25519SK};
+
+/// The current version of the [Share] format.
+pub const SHARE_VERSION: u8 = 1;
+
+/// One share of an [Ed25519SK] split with [Ed25519SK::split], for distributing key backups across several operators.
+///
+/// Each share is a byte-wise Shamir share over GF(256) of the key's 32-byte seed. It also records the format version, the threshold, and the public key, so that [Ed25519SK::combine] can tell when shares are incompatible or belong to different keys. A share on its own reveals nothing about the secret key.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Share {
+    pub version: u8,
+    /// How many shares are needed to recover the key.
+    pub threshold: u8,
+    /// The x-coordinate of this share. Always nonzero.
+    pub index: u8,
+    /// The public key of the split secret key.
+    pub pk: Ed25519PK,
+    pub data: [u8; 32],
+}
+
+impl PartialEq for Share {
+    /// Compares the share data in constant time. The other fields are public.
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version
+            && self.threshold == other.threshold
+            && self.index == other.index
+            && self.pk == other.pk
+            && ct_eq(&self.data, &other.data)
+    }
+}
+
+impl Eq for Share {}
+
+impl Drop for Share {
+    fn drop(&mut self) {
+        self.data.zeroize();
+    }
+}
+
+impl fmt::Debug for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Share")
+            .field("version", &self.version)
+            .field("threshold", &self.threshold)
+            .field("index", &self.index)
+            .field("pk", &self.pk)
+            .finish()
+    }
+}
+
+/// An error when recombining [Share]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareError {
+    /// A share has a format version this library doesn't understand.
+    UnsupportedVersion(u8),
+    /// Fewer shares were given than the threshold.
+    NotEnoughShares { needed: u8, found: usize },
+    /// Two shares have the same index.
+    DuplicateIndex(u8),
+    /// The shares don't all come from the same split, or some are corrupted.
+    Mismatched,
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareError::UnsupportedVersion(v) => write!(f, "unsupported share version {}", v),
+            ShareError::NotEnoughShares { needed, found } => {
+                write!(f, "{} shares are needed, only {} given", needed, found)
+            }
+            ShareError::DuplicateIndex(i) => write!(f, "duplicate share index {}", i),
+            ShareError::Mismatched => "shares do not belong to the same key".fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+impl Ed25519SK {
+    /// Splits this key into `n` shares, any `threshold` of which can recover it with [Ed25519SK::combine]. Shares have indices `1..=n`. Panics unless `0 < threshold <= n`.
+    pub fn split(&self, n: u8, threshold: u8) -> Vec<Share> {
+        assert!(
+            threshold > 0 && threshold <= n,
+            "threshold must be between 1 and the number of shares"
+        );
+        let pk = self.to_public();
+        let mut shares: Vec<Share> = (1..=n)
+            .map(|index| Share {
+                version: SHARE_VERSION,
+                threshold,
+                index,
+                pk,
+                data: [0; 32],
+            })
+            .collect();
+        let mut coeffs = vec![0u8; threshold as usize];
+        for (byte, secret) in self.0[..32].iter().enumerate() {
+            coeffs[0] = *secret;
+            fill_random(&mut coeffs[1..]);
+            for share in shares.iter_mut() {
+                share.data[byte] = coeffs
+                    .iter()
+                    .rev()
+                    .fold(0, |acc, c| gf_mul(acc, share.index) ^ c);
+            }
+        }
+        coeffs.zeroize();
+        shares
+    }
+
+    /// Recovers a key from at least as many shares as its threshold, checking that they agree with each other and with the recorded public key.
+    pub fn combine(shares: &[Share]) -> Result<Self, ShareError> {
+        let first = shares.first().ok_or(ShareError::NotEnoughShares {
+            needed: 1,
+            found: 0,
+        })?;
+        if let Some(s) = shares.iter().find(|s| s.version != SHARE_VERSION) {
+            return Err(ShareError::UnsupportedVersion(s.version));
+        }
+        if shares
+            .iter()
+            .any(|s| s.threshold != first.threshold || s.pk != first.pk || s.index == 0)
+        {
+            return Err(ShareError::Mismatched);
+        }
+        for (i, s) in shares.iter().enumerate() {
+            if shares[..i].iter().any(|t| t.index == s.index) {
+                return Err(ShareError::DuplicateIndex(s.index));
+            }
+        }
+        if shares.len() < first.threshold as usize {
+            return Err(ShareError::NotEnoughShares {
+                needed: first.threshold,
+                found: shares.len(),
+            });
+        }
+
+        // Lagrange interpolation at zero
+        let mut seed = [0u8; 32];
+        for s in shares {
+            let mut num = 1u8;
+            let mut den = 1u8;
+            for t in shares.iter().filter(|t| t.index != s.index) {
+                num = gf_mul(num, t.index);
+                den = gf_mul(den, t.index ^ s.index);
+            }
+            let basis = gf_mul(num, gf_inv(den));
+            for (acc, y) in seed.iter_mut().zip(s.data.iter()) {
+                *acc ^= gf_mul(*y, basis);
+            }
+        }
+        let sk = Ed25519SK::from_seed(seed);
+        seed.zeroize();
+        if sk.to_public() != first.pk {
+            return Err(ShareError::Mismatched);
+        }
+        Ok(sk)
+    }
+}
+
+/// Multiplication in GF(2^8) modulo the AES polynomial, without secret-dependent branches.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+    }
+    product
+}
+
+fn gf_inv(a: u8) -> u8 {
+    // a^254 = a^-1
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_combine() {
+        let sk = Ed25519SK::generate();
+        let shares = sk.split(5, 3);
+        assert_eq!(Ed25519SK::combine(&shares[..3]).unwrap(), sk);
+        assert_eq!(Ed25519SK::combine(&shares[2..]).unwrap(), sk);
+        assert_eq!(
+            Ed25519SK::combine(&shares[..2]),
+            Err(ShareError::NotEnoughShares {
+                needed: 3,
+                found: 2
+            })
+        );
+
+        let roundtrip: Vec<Share> = shares
+            .iter()
+            .map(|s| stdcode::deserialize(&stdcode::serialize(s).unwrap()).unwrap())
+            .collect();
+        assert_eq!(roundtrip, shares);
+
+        let mut corrupted = shares[..3].to_vec();
+        corrupted[1].data[7] ^= 1;
+        assert_eq!(Ed25519SK::combine(&corrupted), Err(ShareError::Mismatched));
+        let mut future = shares[..3].to_vec();
+        future[0].version = 2;
+        assert_eq!(
+            Ed25519SK::combine(&future),
+            Err(ShareError::UnsupportedVersion(2))
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use std::fmt;
+
+use curve25519_dalek_ng::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::CompressedRistretto, traits::Identity,
+};
+pub use curve25519_dalek_ng::{ristretto::RistrettoPoint, scalar::Scalar};
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::fill_random;
+
+/// One share of a secret scalar split with [split_secret]. Zeroed out on drop.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretShare {
+    /// The x-coordinate of this share. Always nonzero.
+    pub index: u32,
+    /// The value of the sharing polynomial at `index`.
+    pub value: Scalar,
+}
+
+impl Zeroize for SecretShare {
+    fn zeroize(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+impl Drop for SecretShare {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SecretShare {}
+
+impl fmt::Debug for SecretShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretShare")
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Generates a uniformly random scalar from the crate's entropy source.
+pub fn random_scalar() -> Scalar {
+    let mut wide = [0u8; 64];
+    fill_random(&mut wide);
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+/// Splits a secret into `n` Shamir shares, any `threshold` of which can recover it with [combine_shares]. Shares have indices `1..=n`. Panics unless `0 < threshold <= n`.
+pub fn split_secret(secret: Scalar, threshold: u32, n: u32) -> Vec<SecretShare> {
+    split_secret_with_coeffs(secret, threshold, n).1
+}
+
+/// Like [split_secret], but also returns the coefficients of the sharing polynomial, starting with the secret itself.
+pub(crate) fn split_secret_with_coeffs(
+    secret: Scalar,
+    threshold: u32,
+    n: u32,
+) -> (Vec<Scalar>, Vec<SecretShare>) {
+    assert!(
+        threshold > 0 && threshold <= n,
+        "threshold must be between 1 and the number of shares"
+    );
+    let coeffs: Vec<Scalar> = std::iter::once(secret)
+        .chain((1..threshold).map(|_| random_scalar()))
+        .collect();
+    let shares = (1..=n)
+        .map(|index| SecretShare {
+            index,
+            value: eval_poly(&coeffs, Scalar::from(index)),
+        })
+        .collect();
+    (coeffs, shares)
+}
+
+/// Splits a secret like [split_secret], but also returns a Feldman commitment to the sharing polynomial, which should be published so that every recipient can check their share with [FeldmanCommitment::verify_share].
+pub fn split_secret_verifiable(
+    secret: Scalar,
+    threshold: u32,
+    n: u32,
+) -> (FeldmanCommitment, Vec<SecretShare>) {
+    let (coeffs, shares) = split_secret_with_coeffs(secret, threshold, n);
+    let commitment = FeldmanCommitment(
+        coeffs
+            .iter()
+            .map(|c| (c * RISTRETTO_BASEPOINT_POINT).compress())
+            .collect(),
+    );
+    (commitment, shares)
+}
+
+/// A Feldman VSS commitment: the coefficients of a sharing polynomial, each multiplied by the Ristretto basepoint.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeldmanCommitment(pub Vec<CompressedRistretto>);
+
+impl FeldmanCommitment {
+    /// The number of shares needed to recover the secret.
+    pub fn threshold(&self) -> u32 {
+        self.0.len() as u32
+    }
+
+    /// The commitment to the secret itself, i.e. `secret * G`.
+    pub fn public_secret(&self) -> Option<CompressedRistretto> {
+        self.0.first().copied()
+    }
+
+    /// Checks that a share is consistent with the committed polynomial, i.e. that `share.value * G` equals the commitment evaluated at `share.index`.
+    pub fn verify_share(&self, share: &SecretShare) -> bool {
+        if share.index == 0 || self.0.is_empty() {
+            return false;
+        }
+        let x = Scalar::from(share.index);
+        let mut expected = RistrettoPoint::identity();
+        // Horner's method, in the exponent
+        for coeff in self.0.iter().rev() {
+            let coeff = match coeff.decompress() {
+                Some(coeff) => coeff,
+                None => return false,
+            };
+            expected = expected * x + coeff;
+        }
+        expected == share.value * RISTRETTO_BASEPOINT_POINT
+    }
+}
+
+/// Recovers a secret from at least `threshold` shares. Returns `None` if there are no shares, or if any index is zero or repeated. With fewer than `threshold` shares, the result is garbage.
+pub fn combine_shares(shares: &[SecretShare]) -> Option<Scalar> {
+    if shares.is_empty() {
+        return None;
+    }
+    let indices: Vec<u32> = shares.iter().map(|s| s.index).collect();
+    let mut total = Scalar::zero();
+    for share in shares {
+        total += lagrange_at_zero(&indices, share.index)? * share.value;
+    }
+    Some(total)
+}
+
+/// Computes the Lagrange coefficient of the share at `index`, for interpolating at zero over `indices`. Returns `None` if any index is zero or repeated.
+pub fn lagrange_at_zero(indices: &[u32], index: u32) -> Option<Scalar> {
+    if index == 0 {
+        return None;
+    }
+    let xi = Scalar::from(index);
+    let mut num = Scalar::one();
+    let mut den = Scalar::one();
+    let mut seen_self = false;
+    for &j in indices {
+        if j == index {
+            if seen_self {
+                return None;
+            }
+            seen_self = true;
+            continue;
+        }
+        if j == 0 {
+            return None;
+        }
+        let xj = Scalar::from(j);
+        num *= xj;
+        den *= xj - xi;
+    }
+    Some(num * den.invert())
+}
+
+/// Interpolates the points `(index, value)` at zero, "in the exponent".
+pub(crate) fn combine_points(points: &[(u32, RistrettoPoint)]) -> Option<RistrettoPoint> {
+    if points.is_empty() {
+        return None;
+    }
+    let indices: Vec<u32> = points.iter().map(|p| p.0).collect();
+    let mut total = RistrettoPoint::identity();
+    for (index, point) in points {
+        total += lagrange_at_zero(&indices, *index)? * point;
+    }
+    Some(total)
+}
+
+fn eval_poly(coeffs: &[Scalar], x: Scalar) -> Scalar {
+    // Horner's method
+    coeffs
+        .iter()
+        .rev()
+        .fold(Scalar::zero(), |acc, coeff| acc * x + coeff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_and_combine() {
+        let secret = random_scalar();
+        let shares = split_secret(secret, 3, 5);
+        assert_eq!(combine_shares(&shares[..3]), Some(secret));
+        assert_eq!(combine_shares(&shares[2..]), Some(secret));
+        assert_ne!(combine_shares(&shares[..2]), Some(secret));
+        assert_eq!(
+            combine_shares(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]),
+            None
+        );
+
+        let (commitment, shares) = split_secret_verifiable(secret, 3, 5);
+        assert!(shares.iter().all(|s| commitment.verify_share(s)));
+        let mut bad = shares[0].clone();
+        bad.value += Scalar::one();
+        assert!(!commitment.verify_share(&bad));
+        let value = hex::encode(bad.value.as_bytes());
+        assert!(!format!("{:?}", bad).contains(&value[..10]));
+        assert_eq!(
+            commitment.public_secret(),
+            Some((secret * RISTRETTO_BASEPOINT_POINT).compress())
+        );
+        let commitment: FeldmanCommitment =
+            stdcode::deserialize(&stdcode::serialize(&commitment).unwrap()).unwrap();
+        assert_eq!(commitment.threshold(), 3);
+    }
+}
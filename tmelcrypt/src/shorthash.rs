@@ -0,0 +1,174 @@
+use std::{convert::TryInto, fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{hash_keyed, HashVal};
+
+const CROCKFORD: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// A 20-byte truncation of a [HashVal], for contexts like QR codes and NFC tags where a full address is too long. It is written as a 35-character `s...` string: 32 Crockford base32 digits followed by a 2-digit checksum.
+///
+/// **Short hashes are not addresses.** Truncating to 160 bits leaves about 2^80 work to find *two* hashes that share a short hash. That is feasible for a well-funded attacker, who could then present a harmless covenant and a malicious one that look the same in short form. Finding a hash that collides with a *given* short hash still takes about 2^160 work, so a short hash is safe for looking up, or visually confirming, a full address obtained from a trusted source. It must never be the only thing a payment is checked against; compare the full [HashVal] with [ShortHashVal::matches] before sending funds.
+///
+/// For the same reason, there is no [From] conversion: truncation must be asked for explicitly with [ShortHashVal::truncate].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Default)]
+pub struct ShortHashVal(pub [u8; 20]);
+
+/// Why a string is not a valid [ShortHashVal].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShortHashParseError {
+    /// The string doesn't start with `s`.
+    BadPrefix,
+    /// The string has the wrong number of characters.
+    WrongLength(usize),
+    /// The string contains characters outside the Crockford base32 alphabet.
+    InvalidBase32,
+    /// The checksum digits don't match, so the string was probably mistyped.
+    BadChecksum,
+}
+
+impl fmt::Display for ShortHashParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShortHashParseError::BadPrefix => write!(f, "short hash does not start with 's'"),
+            ShortHashParseError::WrongLength(len) => {
+                write!(f, "short hash has {} characters instead of 35", len)
+            }
+            ShortHashParseError::InvalidBase32 => write!(f, "short hash is not valid base32"),
+            ShortHashParseError::BadChecksum => {
+                write!(f, "short hash checksum is wrong; it was probably mistyped")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShortHashParseError {}
+
+impl ShortHashVal {
+    /// Truncates a hash to its first 20 bytes. See the type-level documentation for when this is safe.
+    pub fn truncate(hash: &HashVal) -> Self {
+        Self(hash.0[..20].try_into().unwrap())
+    }
+
+    /// Whether this is the truncation of `hash`.
+    pub fn matches(&self, hash: &HashVal) -> bool {
+        *self == Self::truncate(hash)
+    }
+
+    /// The two checksum digits, as values in `0..32`.
+    fn checksum(&self) -> [u8; 2] {
+        let h = hash_keyed(b"short-hash-checksum", self.0).0;
+        [h[0] >> 3, h[1] >> 3]
+    }
+}
+
+fn crockford_value(c: u8) -> Option<u8> {
+    let c = match c.to_ascii_uppercase() {
+        b'O' => b'0',
+        b'I' | b'L' => b'1',
+        c => c,
+    };
+    CROCKFORD.iter().position(|d| *d == c).map(|v| v as u8)
+}
+
+impl FromStr for ShortHashVal {
+    type Err = ShortHashParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.replace('-', "");
+        if !s.starts_with(['s', 'S']) {
+            return Err(ShortHashParseError::BadPrefix);
+        }
+        if !s.is_ascii() {
+            return Err(ShortHashParseError::InvalidBase32);
+        }
+        if s.len() != 35 {
+            return Err(ShortHashParseError::WrongLength(s.len()));
+        }
+        let short = ShortHashVal(
+            base32::decode(base32::Alphabet::Crockford, &s[1..33])
+                .ok_or(ShortHashParseError::InvalidBase32)?
+                .try_into()
+                .map_err(|_| ShortHashParseError::InvalidBase32)?,
+        );
+        let found = [
+            crockford_value(s.as_bytes()[33]).ok_or(ShortHashParseError::InvalidBase32)?,
+            crockford_value(s.as_bytes()[34]).ok_or(ShortHashParseError::InvalidBase32)?,
+        ];
+        if found != short.checksum() {
+            return Err(ShortHashParseError::BadChecksum);
+        }
+        Ok(short)
+    }
+}
+
+impl fmt::Display for ShortHashVal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [c1, c2] = self.checksum();
+        let s = format!(
+            "S{}{}{}",
+            base32::encode(base32::Alphabet::Crockford, &self.0),
+            CROCKFORD[c1 as usize] as char,
+            CROCKFORD[c2 as usize] as char
+        );
+        s.to_ascii_lowercase().fmt(f)
+    }
+}
+
+impl fmt::Debug for ShortHashVal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ShortHashVal({})", self)
+    }
+}
+
+impl Serialize for ShortHashVal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ShortHashVal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            s.parse().map_err(de::Error::custom)
+        } else {
+            Ok(ShortHashVal(<[u8; 20]>::deserialize(deserializer)?))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_hash_roundtrip() {
+        let hash = crate::hash_single(b"covenant");
+        let short = ShortHashVal::truncate(&hash);
+        assert!(short.matches(&hash));
+        let s = short.to_string();
+        assert_eq!(s.len(), 35);
+        assert_eq!(s.parse::<ShortHashVal>(), Ok(short));
+        assert_eq!(s.to_uppercase().parse::<ShortHashVal>(), Ok(short));
+
+        let mut typo = s.into_bytes();
+        typo[5] = if typo[5] == b'0' { b'1' } else { b'0' };
+        assert_eq!(
+            String::from_utf8(typo).unwrap().parse::<ShortHashVal>(),
+            Err(ShortHashParseError::BadChecksum)
+        );
+        assert_eq!(
+            serde_json::from_str::<ShortHashVal>(&serde_json::to_string(&short).unwrap()).unwrap(),
+            short
+        );
+        // a multibyte character can't be sliced into
+        assert_eq!(
+            format!("s{}é0", "a".repeat(31)).parse::<ShortHashVal>(),
+            Err(ShortHashParseError::InvalidBase32)
+        );
+    }
+}
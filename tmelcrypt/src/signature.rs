@@ -0,0 +1,146 @@
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt,
+    ops::Deref,
+    str::FromStr,
+};
+
+use arbitrary::Arbitrary;
+use arrayref::array_ref;
+use ed25519_consensus::SigningKey;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Ed25519PK, Ed25519SK, VerifyError};
+
+/// A 64-byte ed25519 signature.
+///
+/// The older APIs that pass signatures around as `Vec<u8>` and `&[u8]` keep working: a [Signature] dereferences to a byte slice, so it can be passed straight to [Ed25519PK::verify], and converts to and from byte vectors. It serializes as hex in human-readable formats, and in binary formats exactly like a `Vec<u8>` holding the same bytes, so it can replace such fields without changing their encoding.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Arbitrary)]
+pub struct Signature(pub [u8; 64]);
+
+impl Ed25519SK {
+    /// Signs a message, like [Ed25519SK::sign], but returns a [Signature] without allocating.
+    pub fn sign_to_signature(&self, msg: &[u8]) -> Signature {
+        let key = SigningKey::from(*array_ref![self.0, 0, 32]);
+        Signature(key.sign(msg).to_bytes())
+    }
+}
+
+impl Ed25519PK {
+    /// Verifies a [Signature]. Since it's always 64 bytes, the only possible errors are an invalid public key and a mismatch.
+    pub fn verify_signature(&self, msg: &[u8], sig: &Signature) -> Result<(), VerifyError> {
+        self.verify_detailed(msg, &sig.0)
+    }
+}
+
+impl Deref for Signature {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Signature {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<[u8; 64]> for Signature {
+    fn from(bytes: [u8; 64]) -> Self {
+        Signature(bytes)
+    }
+}
+
+impl From<Signature> for Vec<u8> {
+    fn from(sig: Signature) -> Self {
+        sig.0.to_vec()
+    }
+}
+
+impl TryFrom<&[u8]> for Signature {
+    type Error = VerifyError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Ok(Signature(bytes.try_into().map_err(|_| {
+            VerifyError::BadSignatureLength(bytes.len())
+        })?))
+    }
+}
+
+impl TryFrom<Vec<u8>> for Signature {
+    type Error = VerifyError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Signature::try_from(bytes.as_slice())
+    }
+}
+
+impl FromStr for Signature {
+    type Err = hex::FromHexError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut inner = [0u8; 64];
+        hex::decode_to_slice(s, &mut inner)?;
+        Ok(Signature(inner))
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::encode(self.0).fmt(f)
+    }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#<Signature:{}>", hex::encode(&self.0[..5]))
+    }
+}
+
+impl Serialize for Signature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        stdcode::hex::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = stdcode::hex::deserialize(deserializer)?;
+        Signature::try_from(bytes).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_compat() {
+        let sk = Ed25519SK::generate();
+        let sig = sk.sign_to_signature(b"hello");
+        let pk = sk.to_public();
+        assert!(pk.verify(b"hello", &sig));
+        assert_eq!(pk.verify_signature(b"hello", &sig), Ok(()));
+        assert_eq!(
+            pk.verify_signature(b"other", &sig).unwrap_err().reason(),
+            "mismatch"
+        );
+        assert_eq!(Vec::from(sig), sk.sign(b"hello"));
+        let mut buf = [0u8; 64];
+        sk.sign_into(b"hello", &mut buf);
+        assert_eq!(buf, sig.0);
+        assert_eq!(sig.to_string().parse::<Signature>(), Ok(sig));
+        assert_eq!(
+            Signature::try_from(&[0u8; 10][..]),
+            Err(VerifyError::BadSignatureLength(10))
+        );
+        assert_eq!(
+            stdcode::serialize(&sig).unwrap(),
+            stdcode::serialize(&sk.sign(b"hello")).unwrap()
+        );
+        let json = serde_json::to_string(&sig).unwrap();
+        assert_eq!(json, format!("\"{}\"", sig));
+        assert_eq!(serde_json::from_str::<Signature>(&json).unwrap(), sig);
+    }
+}
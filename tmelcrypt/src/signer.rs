@@ -0,0 +1,129 @@
+use std::{io, sync::Arc};
+
+use async_trait::async_trait;
+
+use crate::{Ed25519PK, Ed25519SK, ExpandedSK, KeyPair};
+
+/// Something that can produce ed25519 signatures, such as an in-memory key, a remote signing service, or a hardware security module.
+///
+/// Code that needs to sign should take a `&dyn Signer` or `impl Signer` rather than an [Ed25519SK], so that it works with any kind of key. Signing returns an error if the backend fails, for example when a remote signer is unreachable; in-memory keys never fail.
+pub trait Signer: Send + Sync {
+    /// The public key that signatures will verify under.
+    fn public_key(&self) -> Ed25519PK;
+
+    /// Signs a message, returning a 64-byte ed25519 signature.
+    fn sign(&self, msg: &[u8]) -> io::Result<Vec<u8>>;
+}
+
+impl Signer for Ed25519SK {
+    fn public_key(&self) -> Ed25519PK {
+        self.to_public()
+    }
+
+    fn sign(&self, msg: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(Ed25519SK::sign(self, msg))
+    }
+}
+
+impl Signer for ExpandedSK {
+    fn public_key(&self) -> Ed25519PK {
+        self.to_public()
+    }
+
+    fn sign(&self, msg: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(ExpandedSK::sign(self, msg))
+    }
+}
+
+impl Signer for KeyPair {
+    fn public_key(&self) -> Ed25519PK {
+        self.public()
+    }
+
+    fn sign(&self, msg: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(KeyPair::sign(self, msg))
+    }
+}
+
+impl<T: Signer + ?Sized> Signer for &T {
+    fn public_key(&self) -> Ed25519PK {
+        (**self).public_key()
+    }
+
+    fn sign(&self, msg: &[u8]) -> io::Result<Vec<u8>> {
+        (**self).sign(msg)
+    }
+}
+
+impl<T: Signer + ?Sized> Signer for Box<T> {
+    fn public_key(&self) -> Ed25519PK {
+        (**self).public_key()
+    }
+
+    fn sign(&self, msg: &[u8]) -> io::Result<Vec<u8>> {
+        (**self).sign(msg)
+    }
+}
+
+impl<T: Signer + ?Sized> Signer for Arc<T> {
+    fn public_key(&self) -> Ed25519PK {
+        (**self).public_key()
+    }
+
+    fn sign(&self, msg: &[u8]) -> io::Result<Vec<u8>> {
+        (**self).sign(msg)
+    }
+}
+
+/// The asynchronous version of [Signer], for backends that sign over the network, such as a remote signing service for a validator.
+///
+/// Every [Signer] is also an [AsyncSigner], so code that takes an `impl AsyncSigner` works with in-memory keys too.
+#[async_trait]
+pub trait AsyncSigner: Send + Sync {
+    /// The public key that signatures will verify under.
+    fn public_key(&self) -> Ed25519PK;
+
+    /// Signs a message, returning a 64-byte ed25519 signature.
+    async fn sign(&self, msg: &[u8]) -> io::Result<Vec<u8>>;
+}
+
+#[async_trait]
+impl<T: Signer + ?Sized> AsyncSigner for T {
+    fn public_key(&self) -> Ed25519PK {
+        Signer::public_key(self)
+    }
+
+    async fn sign(&self, msg: &[u8]) -> io::Result<Vec<u8>> {
+        Signer::sign(self, msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign_with(signer: &dyn Signer, msg: &[u8]) -> (Ed25519PK, Vec<u8>) {
+        (signer.public_key(), signer.sign(msg).unwrap())
+    }
+
+    async fn sign_async(signer: &dyn AsyncSigner, msg: &[u8]) -> (Ed25519PK, Vec<u8>) {
+        (signer.public_key(), signer.sign(msg).await.unwrap())
+    }
+
+    #[test]
+    fn signers_interchangeable() {
+        let sk = Ed25519SK::generate();
+        let signers: Vec<Box<dyn Signer>> = vec![
+            Box::new(sk.clone()),
+            Box::new(sk.expand()),
+            Box::new(KeyPair::from_secret(sk.clone()).unwrap()),
+        ];
+        for signer in signers.iter() {
+            let (pk, sig) = sign_with(signer, b"tx");
+            assert_eq!(pk, sk.to_public());
+            assert!(pk.verify(b"tx", &sig));
+            let (pk, sig) = futures_lite::future::block_on(sign_async(signer, b"tx"));
+            assert!(pk.verify(b"tx", &sig));
+        }
+    }
+}
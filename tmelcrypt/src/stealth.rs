@@ -0,0 +1,249 @@
+//! Stealth addresses: one-time addresses that only the recipient can link to them.
+//!
+//! A recipient publishes a [StealthPK], made of a scan key and a spend key. To pay them, a sender calls [StealthPK::pay], which picks a fresh ephemeral key and derives a [StealthPayment]: a one-time public key, whose [address](StealthPayment::address) the funds are sent to, and the ephemeral public key, which the sender publishes alongside the transaction. Nobody else can tell that two such addresses belong to the same recipient.
+//!
+//! The recipient checks each published payment with [StealthKeys::owns]. This needs only the scan secret key, so scanning can be delegated to a less trusted machine holding just a [StealthViewKey]. For payments that are theirs, [StealthKeys::recover] gives a [StealthSK] that signs for the one-time key.
+//!
+//! The one-time key is `P = H(8 r S) B + K`, where `S` and `K` are the scan and spend public keys, `r` is the ephemeral secret and `B` is the ed25519 base point. The recipient computes the same `H(8 s R)` from the ephemeral public key `R`.
+
+use std::{fmt, io};
+
+use curve25519_dalek_ng::{
+    constants::ED25519_BASEPOINT_TABLE,
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use zeroize::Zeroize;
+
+use crate::{expanded::ed25519_challenge, random_scalar, Ed25519PK, Ed25519SK, HashVal, Signer};
+
+/// A recipient's stealth keys: a scan key that finds incoming payments, and a spend key that spends them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StealthKeys {
+    pub scan: Ed25519SK,
+    pub spend: Ed25519SK,
+}
+
+/// The public half of [StealthKeys], which the recipient publishes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StealthPK {
+    pub scan: Ed25519PK,
+    pub spend: Ed25519PK,
+}
+
+/// The part of [StealthKeys] needed to find incoming payments, but not to spend them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StealthViewKey {
+    pub scan: Ed25519SK,
+    pub spend: Ed25519PK,
+}
+
+/// A payment to a stealth address: the one-time key that receives the funds, and the ephemeral key the recipient needs to find and spend them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StealthPayment {
+    pub ephemeral: Ed25519PK,
+    pub one_time: Ed25519PK,
+}
+
+/// The secret key for a one-time key. Unlike an [Ed25519SK], it is a bare scalar rather than a seed, since it's derived by adding scalars. It produces ordinary ed25519 signatures. Zeroed out on drop.
+#[derive(Clone)]
+pub struct StealthSK {
+    scalar: Scalar,
+    nonce_prefix: [u8; 32],
+    pk: Ed25519PK,
+}
+
+impl StealthKeys {
+    /// Generates new random stealth keys.
+    pub fn generate() -> Self {
+        Self {
+            scan: Ed25519SK::generate(),
+            spend: Ed25519SK::generate(),
+        }
+    }
+
+    /// The public stealth keys.
+    pub fn to_public(&self) -> StealthPK {
+        StealthPK {
+            scan: self.scan.to_public(),
+            spend: self.spend.to_public(),
+        }
+    }
+
+    /// The view key, which can find payments to these keys but not spend them.
+    pub fn view_key(&self) -> StealthViewKey {
+        StealthViewKey {
+            scan: self.scan.clone(),
+            spend: self.spend.to_public(),
+        }
+    }
+
+    /// Whether a payment is to these keys.
+    pub fn owns(&self, payment: &StealthPayment) -> bool {
+        self.view_key().owns(payment)
+    }
+
+    /// Recovers the secret key for a payment's one-time key, or returns [None] if the payment isn't to these keys.
+    pub fn recover(&self, payment: &StealthPayment) -> Option<StealthSK> {
+        if !self.owns(payment) {
+            return None;
+        }
+        let mut spend = self.spend.signing_scalar();
+        let scalar = tweak(&self.scan, &payment.ephemeral)? + spend;
+        spend.zeroize();
+        let mut buf = scalar.to_bytes();
+        let nonce_prefix = crate::hash_keyed(b"tmelcrypt-stealth-nonce", buf).0;
+        buf.zeroize();
+        Some(StealthSK {
+            scalar,
+            nonce_prefix,
+            pk: payment.one_time,
+        })
+    }
+}
+
+impl StealthViewKey {
+    /// Whether a payment is to the stealth keys this view key belongs to.
+    pub fn owns(&self, payment: &StealthPayment) -> bool {
+        let spend = match decompress(&self.spend) {
+            Some(spend) => spend,
+            None => return false,
+        };
+        match tweak(&self.scan, &payment.ephemeral) {
+            Some(tweak) => one_time_key(&tweak, &spend) == payment.one_time,
+            None => false,
+        }
+    }
+}
+
+impl StealthPK {
+    /// Derives a fresh one-time payment to this recipient. Returns [None] if the recipient's keys aren't valid curve points.
+    pub fn pay(&self) -> Option<StealthPayment> {
+        let scan = decompress(&self.scan)?;
+        let spend = decompress(&self.spend)?;
+        let mut r = random_scalar();
+        let ephemeral = Ed25519PK((&r * &ED25519_BASEPOINT_TABLE).compress().to_bytes());
+        let tweak = shared_tweak(&(r * scan), &ephemeral);
+        r.zeroize();
+        Some(StealthPayment {
+            ephemeral,
+            one_time: one_time_key(&tweak, &spend),
+        })
+    }
+}
+
+impl StealthPayment {
+    /// The address to send the funds to: the standard address of the one-time key.
+    pub fn address(&self) -> HashVal {
+        self.one_time.std_address()
+    }
+}
+
+impl StealthSK {
+    /// The one-time public key.
+    pub fn to_public(&self) -> Ed25519PK {
+        self.pk
+    }
+
+    /// Signs a message, producing an ordinary ed25519 signature that verifies under the one-time key. Nonces are derived deterministically, like ed25519's.
+    pub fn sign(&self, msg: &[u8]) -> Vec<u8> {
+        let hash: [u8; 64] = Sha512::new()
+            .chain_update(self.nonce_prefix)
+            .chain_update(msg)
+            .finalize()
+            .into();
+        let mut r = Scalar::from_bytes_mod_order_wide(&hash);
+        let big_r = (&r * &ED25519_BASEPOINT_TABLE).compress();
+        let c = ed25519_challenge(big_r.as_bytes(), &self.pk.0, msg);
+        let s = r + c * self.scalar;
+        r.zeroize();
+        let mut sig = big_r.to_bytes().to_vec();
+        sig.extend_from_slice(s.as_bytes());
+        sig
+    }
+}
+
+impl Signer for StealthSK {
+    fn public_key(&self) -> Ed25519PK {
+        self.to_public()
+    }
+
+    fn sign(&self, msg: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(StealthSK::sign(self, msg))
+    }
+}
+
+fn decompress(pk: &Ed25519PK) -> Option<EdwardsPoint> {
+    CompressedEdwardsY(pk.0).decompress()
+}
+
+/// The recipient's side of the tweak, computed from the scan secret key and the ephemeral public key.
+fn tweak(scan: &Ed25519SK, ephemeral: &Ed25519PK) -> Option<Scalar> {
+    let mut scan = scan.signing_scalar();
+    let shared = scan * decompress(ephemeral)?;
+    scan.zeroize();
+    Some(shared_tweak(&shared, ephemeral))
+}
+
+/// Hashes the Diffie-Hellman point, with the cofactor cleared so that small-order components of the ephemeral key can't change it.
+fn shared_tweak(shared: &EdwardsPoint, ephemeral: &Ed25519PK) -> Scalar {
+    let hash: [u8; 64] = Sha512::new()
+        .chain_update(b"tmelcrypt-stealth")
+        .chain_update(shared.mul_by_cofactor().compress().as_bytes())
+        .chain_update(ephemeral.0)
+        .finalize()
+        .into();
+    Scalar::from_bytes_mod_order_wide(&hash)
+}
+
+fn one_time_key(tweak: &Scalar, spend: &EdwardsPoint) -> Ed25519PK {
+    Ed25519PK(
+        (tweak * &ED25519_BASEPOINT_TABLE + spend)
+            .compress()
+            .to_bytes(),
+    )
+}
+
+impl Drop for StealthSK {
+    fn drop(&mut self) {
+        self.scalar.zeroize();
+        self.nonce_prefix.zeroize();
+    }
+}
+
+impl fmt::Debug for StealthSK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "StealthSK({:?})", self.pk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stealth_roundtrip() {
+        let keys = StealthKeys::generate();
+        let pk = keys.to_public();
+        let payment = pk.pay().unwrap();
+        let other = pk.pay().unwrap();
+        assert_ne!(payment.address(), other.address());
+
+        assert!(keys.owns(&payment));
+        assert!(keys.view_key().owns(&payment));
+        assert!(!StealthKeys::generate().owns(&payment));
+        let sk = keys.recover(&payment).unwrap();
+        assert_eq!(sk.to_public(), payment.one_time);
+        let sig = sk.sign(b"spend");
+        assert!(payment.one_time.verify(b"spend", &sig));
+        assert!(!other.one_time.verify(b"spend", &sig));
+
+        let forged = StealthPayment {
+            ephemeral: other.ephemeral,
+            one_time: payment.one_time,
+        };
+        assert!(keys.recover(&forged).is_none());
+    }
+}
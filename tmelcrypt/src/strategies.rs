@@ -0,0 +1,46 @@
+//! [proptest] strategies that generate valid tmelcrypt values, for property tests in downstream crates.
+
+use proptest::prelude::*;
+
+use crate::{Address, Ed25519PK, Ed25519SK, HashVal};
+
+/// Arbitrary hash values.
+pub fn hashval() -> impl Strategy<Value = HashVal> {
+    any::<[u8; 32]>().prop_map(HashVal)
+}
+
+/// Valid secret keys, derived from arbitrary seeds.
+pub fn ed25519_sk() -> impl Strategy<Value = Ed25519SK> {
+    any::<[u8; 32]>().prop_map(Ed25519SK::from_seed)
+}
+
+/// Public keys that are actually on the curve, derived from arbitrary secret keys.
+pub fn ed25519_pk() -> impl Strategy<Value = Ed25519PK> {
+    ed25519_sk().prop_map(|sk| sk.to_public())
+}
+
+/// Valid addresses, with correct checksums.
+pub fn address() -> impl Strategy<Value = Address> {
+    hashval().prop_map(Address)
+}
+
+/// A public key, an arbitrary message, and a valid signature of that message under the key.
+pub fn signed_message() -> impl Strategy<Value = (Ed25519PK, Vec<u8>, Vec<u8>)> {
+    (ed25519_sk(), any::<Vec<u8>>()).prop_map(|(sk, msg)| {
+        let sig = sk.sign(&msg);
+        (sk.to_public(), msg, sig)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn strategies_are_valid(addr in address(), (pk, msg, sig) in signed_message()) {
+            prop_assert_eq!(addr.to_string().parse::<Address>().unwrap(), addr);
+            prop_assert!(pk.verify(&msg, &sig));
+        }
+    }
+}
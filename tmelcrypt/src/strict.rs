@@ -0,0 +1,68 @@
+use arrayref::array_ref;
+use curve25519_dalek_ng::{
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+};
+
+use crate::{expanded::ed25519_challenge, Ed25519PK};
+
+impl Ed25519PK {
+    /// Verifies a signature under the permissive [ZIP215](https://zips.z.cash/zip-0215) rules that consensus uses. This is exactly [Ed25519PK::verify], under a name that says which rules apply.
+    ///
+    /// ZIP215 accepts non-canonical point encodings and small-order keys, and checks the cofactored equation, so that every implementation, including batch verification, agrees on which signatures are valid.
+    pub fn verify_zip215(&self, msg: &[u8], sig: &[u8]) -> bool {
+        self.verify(msg, sig)
+    }
+
+    /// Verifies a signature under strict RFC 8032 rules, for protocols that need them outside of consensus. Compared to [Ed25519PK::verify_zip215], this rejects non-canonical encodings of the key and of `R`, small-order keys and `R`s, and checks the cofactorless equation `sB = R + kA`.
+    ///
+    /// Never use this where nodes must agree on validity: some signatures that consensus accepts are rejected here.
+    pub fn verify_rfc8032(&self, msg: &[u8], sig: &[u8]) -> bool {
+        if sig.len() != 64 {
+            return false;
+        }
+        let r_bytes = array_ref![sig, 0, 32];
+        let (a, r) = match (decompress_strict(&self.0), decompress_strict(r_bytes)) {
+            (Some(a), Some(r)) => (a, r),
+            _ => return false,
+        };
+        let s = match Scalar::from_canonical_bytes(*array_ref![sig, 32, 32]) {
+            Some(s) => s,
+            None => return false,
+        };
+        let k = ed25519_challenge(r_bytes, &self.0, msg);
+        EdwardsPoint::vartime_double_scalar_mul_basepoint(&k, &-a, &s) == r
+    }
+}
+
+/// Decompresses a point, rejecting non-canonical encodings and small-order points.
+fn decompress_strict(bytes: &[u8; 32]) -> Option<EdwardsPoint> {
+    let point = CompressedEdwardsY(*bytes).decompress()?;
+    if point.is_small_order() || point.compress().as_bytes() != bytes {
+        return None;
+    }
+    Some(point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ed25519SK;
+
+    #[test]
+    fn zip215_vs_rfc8032() {
+        let sk = Ed25519SK::generate();
+        let sig = sk.sign(b"hello");
+        assert!(sk.to_public().verify_zip215(b"hello", &sig));
+        assert!(sk.to_public().verify_rfc8032(b"hello", &sig));
+        assert!(!sk.to_public().verify_rfc8032(b"other", &sig));
+
+        // the identity key "signs" anything with R = identity and s = 0, which only ZIP215 accepts
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        let mut forged = identity.to_vec();
+        forged.extend_from_slice(&[0; 32]);
+        assert!(Ed25519PK(identity).verify_zip215(b"anything", &forged));
+        assert!(!Ed25519PK(identity).verify_rfc8032(b"anything", &forged));
+    }
+}
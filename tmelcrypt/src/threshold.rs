@@ -0,0 +1,115 @@
+use curve25519_dalek_ng::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::{CompressedRistretto, RistrettoPoint},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{apply_keystream, combine_points, random_scalar, split_secret, HashVal, SecretShare};
+
+/// The public key of a committee that can jointly decrypt ciphertexts with `threshold` out of `n` key shares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdPK(pub CompressedRistretto);
+
+/// Generates a fresh threshold key, returning the public key and `n` key shares, to be distributed to the committee members. The dealer learns the whole secret key and must be trusted to forget it. Panics unless `0 < threshold <= n`.
+pub fn threshold_keygen(threshold: u32, n: u32) -> (ThresholdPK, Vec<SecretShare>) {
+    let secret = random_scalar();
+    let pk = ThresholdPK((secret * RISTRETTO_BASEPOINT_POINT).compress());
+    (pk, split_secret(secret, threshold, n))
+}
+
+/// A message encrypted to a [ThresholdPK].
+///
+/// The scheme is hashed ElGamal over Ristretto: the encryptor picks a random `r`, publishes `R = r*G`, and derives the symmetric key from `r*PK`. Committee members each contribute `share*R`, which are combined by Lagrange interpolation into `r*PK`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThresholdCiphertext {
+    pub ephemeral: CompressedRistretto,
+    #[serde(with = "stdcode::hex")]
+    pub body: Vec<u8>,
+    pub tag: HashVal,
+}
+
+/// A committee member's contribution towards decrypting a particular [ThresholdCiphertext].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecryptionShare {
+    pub index: u32,
+    pub point: CompressedRistretto,
+}
+
+impl ThresholdPK {
+    /// Encrypts a message to the committee. Returns `None` if the public key isn't a valid point, as may happen with a key from an untrusted source.
+    pub fn encrypt(&self, msg: &[u8]) -> Option<ThresholdCiphertext> {
+        let pk = self.0.decompress()?;
+        let r = random_scalar();
+        let ephemeral = (r * RISTRETTO_BASEPOINT_POINT).compress();
+        let (enc_key, mac_key) = derive_keys(&(r * pk), &ephemeral);
+        let mut body = msg.to_vec();
+        apply_keystream(&enc_key, &mut body);
+        let tag = HashVal(*blake3::keyed_hash(&mac_key, &body).as_bytes());
+        Some(ThresholdCiphertext {
+            ephemeral,
+            body,
+            tag,
+        })
+    }
+}
+
+impl SecretShare {
+    /// Computes this member's decryption share for a ciphertext. Returns `None` if the ciphertext is malformed.
+    pub fn partial_decrypt(&self, ct: &ThresholdCiphertext) -> Option<DecryptionShare> {
+        let ephemeral = ct.ephemeral.decompress()?;
+        Some(DecryptionShare {
+            index: self.index,
+            point: (self.value * ephemeral).compress(),
+        })
+    }
+}
+
+impl ThresholdCiphertext {
+    /// Decrypts the ciphertext given decryption shares from at least `threshold` distinct members. Returns `None` if decryption fails, whether because there were too few shares, some shares were bogus, or the ciphertext was tampered with.
+    pub fn combine(&self, shares: &[DecryptionShare]) -> Option<Vec<u8>> {
+        let points = shares
+            .iter()
+            .map(|share| Some((share.index, share.point.decompress()?)))
+            .collect::<Option<Vec<_>>>()?;
+        let shared = combine_points(&points)?;
+        let (enc_key, mac_key) = derive_keys(&shared, &self.ephemeral);
+        if blake3::keyed_hash(&mac_key, &self.body) != blake3::Hash::from(self.tag.0) {
+            return None;
+        }
+        let mut msg = self.body.clone();
+        apply_keystream(&enc_key, &mut msg);
+        Some(msg)
+    }
+}
+
+/// Derives the encryption and MAC keys from the shared point.
+fn derive_keys(shared: &RistrettoPoint, ephemeral: &CompressedRistretto) -> ([u8; 32], [u8; 32]) {
+    let mut material = shared.compress().to_bytes().to_vec();
+    material.extend_from_slice(ephemeral.as_bytes());
+    (
+        blake3::derive_key("tmelcrypt threshold encryption v1 enc", &material),
+        blake3::derive_key("tmelcrypt threshold encryption v1 mac", &material),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threshold_roundtrip() {
+        let (pk, shares) = threshold_keygen(3, 5);
+        let ct = pk.encrypt(b"sealed bid").unwrap();
+        let dec: Vec<DecryptionShare> = shares
+            .iter()
+            .map(|s| s.partial_decrypt(&ct).unwrap())
+            .collect();
+        assert_eq!(ct.combine(&dec[1..4]).unwrap(), b"sealed bid");
+        assert_eq!(ct.combine(&dec[..2]), None);
+        // not a valid ristretto encoding
+        assert_eq!(
+            ThresholdPK(CompressedRistretto([0xff; 32])).encrypt(b"x"),
+            None
+        );
+    }
+}
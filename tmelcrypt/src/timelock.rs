@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{apply_keystream, ct_eq, hash_keyed, rand_hashval, HashVal};
+
+/// A time-lock puzzle, whose solution can only be found by computing a long chain of sequential hashes.
+///
+/// The chain is cut into segments of `iterations_per_segment` hashes each, where a segment maps a seed `s` to `H^n(s)` with `H` being blake3. Every segment starts from an independent random seed, so the creator can compute all segments in parallel, but each segment's seed is only revealed by XORing the end of the previous segment with the corresponding entry of `locked_seeds`, so a solver must compute them one after another. The solution is the end of the last segment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelockPuzzle {
+    pub start: HashVal,
+    pub iterations_per_segment: u64,
+    /// Entry `i` is the seed of segment `i + 1`, XORed with the end of segment `i`.
+    pub locked_seeds: Vec<HashVal>,
+    /// `hash_keyed("tmelcrypt-timelock-commit", solution)`, allowing cheap verification of solutions.
+    pub commitment: HashVal,
+}
+
+impl TimelockPuzzle {
+    /// Generates a new puzzle with the given number of segments, returning it along with its solution. Generation uses all cores if the `rayon` feature is on, while solving is inherently sequential. Panics if `segments` is zero.
+    pub fn generate(segments: usize, iterations_per_segment: u64) -> (Self, HashVal) {
+        assert!(segments > 0, "a timelock puzzle needs at least one segment");
+        let seeds: Vec<HashVal> = (0..segments).map(|_| rand_hashval()).collect();
+        #[cfg(feature = "rayon")]
+        let ends: Vec<HashVal> = {
+            use rayon::prelude::*;
+            seeds
+                .par_iter()
+                .map(|s| hash_chain(*s, iterations_per_segment))
+                .collect()
+        };
+        #[cfg(not(feature = "rayon"))]
+        let ends: Vec<HashVal> = seeds
+            .iter()
+            .map(|s| hash_chain(*s, iterations_per_segment))
+            .collect();
+        let locked_seeds = seeds[1..]
+            .iter()
+            .zip(ends.iter())
+            .map(|(next, end)| xor(*next, *end))
+            .collect();
+        let solution = ends[segments - 1];
+        let puzzle = Self {
+            start: seeds[0],
+            iterations_per_segment,
+            locked_seeds,
+            commitment: solution_commitment(solution),
+        };
+        (puzzle, solution)
+    }
+
+    /// The total number of sequential hashes needed to solve the puzzle, saturating at [u64::MAX] for puzzles too long to ever solve.
+    pub fn total_iterations(&self) -> u64 {
+        self.iterations_per_segment
+            .saturating_mul((self.locked_seeds.len() as u64).saturating_add(1))
+    }
+
+    /// Solves the puzzle, calling `progress(done, total)` with the number of hashes computed after every segment.
+    pub fn solve(&self, mut progress: impl FnMut(u64, u64)) -> HashVal {
+        let total = self.total_iterations();
+        let mut seed = self.start;
+        let mut done: u64 = 0;
+        for locked in self.locked_seeds.iter() {
+            let end = hash_chain(seed, self.iterations_per_segment);
+            done = done.saturating_add(self.iterations_per_segment);
+            progress(done, total);
+            seed = xor(*locked, end);
+        }
+        let solution = hash_chain(seed, self.iterations_per_segment);
+        progress(total, total);
+        solution
+    }
+
+    /// Cheaply checks a claimed solution against the puzzle's commitment.
+    pub fn verify_solution(&self, solution: HashVal) -> bool {
+        solution_commitment(solution) == self.commitment
+    }
+}
+
+/// Data encrypted so that it can only be decrypted after solving a [TimelockPuzzle].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelockCiphertext {
+    pub puzzle: TimelockPuzzle,
+    #[serde(with = "stdcode::hex")]
+    pub body: Vec<u8>,
+    pub tag: HashVal,
+}
+
+impl TimelockCiphertext {
+    /// Encrypts a message under a fresh puzzle. See [TimelockPuzzle::generate].
+    pub fn encrypt(msg: &[u8], segments: usize, iterations_per_segment: u64) -> Self {
+        let (puzzle, solution) = TimelockPuzzle::generate(segments, iterations_per_segment);
+        let (enc_key, mac_key) = derive_keys(solution);
+        let mut body = msg.to_vec();
+        apply_keystream(&enc_key, &mut body);
+        let tag = hash_keyed(mac_key, &body);
+        Self { puzzle, body, tag }
+    }
+
+    /// Decrypts using the puzzle's solution. Returns `None` if the solution is wrong or the ciphertext was tampered with.
+    pub fn decrypt(&self, solution: HashVal) -> Option<Vec<u8>> {
+        let (enc_key, mac_key) = derive_keys(solution);
+        if !ct_eq(&hash_keyed(mac_key, &self.body), &self.tag) {
+            return None;
+        }
+        let mut msg = self.body.clone();
+        apply_keystream(&enc_key, &mut msg);
+        Some(msg)
+    }
+
+    /// Solves the puzzle, then decrypts. See [TimelockPuzzle::solve].
+    pub fn solve_and_decrypt(&self, progress: impl FnMut(u64, u64)) -> Option<Vec<u8>> {
+        self.decrypt(self.puzzle.solve(progress))
+    }
+}
+
+fn hash_chain(seed: HashVal, iterations: u64) -> HashVal {
+    let mut h = seed.0;
+    for _ in 0..iterations {
+        h = *blake3::hash(&h).as_bytes();
+    }
+    HashVal(h)
+}
+
+fn xor(a: HashVal, b: HashVal) -> HashVal {
+    let mut out = a.0;
+    for (o, b) in out.iter_mut().zip(b.0) {
+        *o ^= b;
+    }
+    HashVal(out)
+}
+
+fn solution_commitment(solution: HashVal) -> HashVal {
+    hash_keyed(b"tmelcrypt-timelock-commit", solution)
+}
+
+fn derive_keys(solution: HashVal) -> ([u8; 32], [u8; 32]) {
+    (
+        blake3::derive_key("tmelcrypt timelock v1 enc", &solution),
+        blake3::derive_key("tmelcrypt timelock v1 mac", &solution),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timelock_roundtrip() {
+        let ct = TimelockCiphertext::encrypt(b"revealed later", 4, 1000);
+        let mut calls = vec![];
+        let solution = ct.puzzle.solve(|done, total| calls.push((done, total)));
+        assert_eq!(calls.last(), Some(&(4000, 4000)));
+        assert_eq!(calls.len(), 4);
+        assert!(ct.puzzle.verify_solution(solution));
+        assert_eq!(ct.decrypt(solution).unwrap(), b"revealed later");
+        assert_eq!(ct.decrypt(HashVal::default()), None);
+
+        // an untrusted puzzle can't overflow the count
+        let huge = TimelockPuzzle {
+            iterations_per_segment: u64::MAX / 2,
+            locked_seeds: vec![HashVal::default(); 3],
+            ..ct.puzzle
+        };
+        assert_eq!(huge.total_iterations(), u64::MAX);
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+
+use curve25519_dalek_ng::scalar::Scalar;
+use zeroize::Zeroize;
+
+use crate::{fill_random, hash_single, HashVal};
+
+/// A running transcript of a protocol, from which challenges are derived, in the style of Merlin.
+///
+/// Both parties [append](Transcript::append) every protocol message to their transcript under a label, in the same order, and derive each [challenge](Transcript::challenge) from everything appended so far. Made non-interactive this way, a protocol binds every challenge to the exact messages before it, with no ad hoc byte concatenation. Labels and messages are length-prefixed, so no two different sequences of appends give the same transcript, and each protocol's domain string separates it from every other protocol.
+#[derive(Clone)]
+pub struct Transcript {
+    inner: blake3::Hasher,
+}
+
+const OP_APPEND: u8 = 0;
+const OP_CHALLENGE: u8 = 1;
+const OP_NONCE: u8 = 2;
+
+impl Transcript {
+    /// Starts a transcript for the protocol identified by `domain`, which should be a hardcoded, globally unique string.
+    pub fn new(domain: &[u8]) -> Self {
+        let mut toret = Self {
+            inner: blake3::Hasher::new_keyed(&hash_single(b"tmelcrypt-transcript").0),
+        };
+        toret.append(b"domain", domain);
+        toret
+    }
+
+    /// Appends a labeled message.
+    pub fn append(&mut self, label: &[u8], bytes: &[u8]) -> &mut Self {
+        self.absorb(OP_APPEND, label);
+        self.inner.update(&(bytes.len() as u64).to_le_bytes());
+        self.inner.update(bytes);
+        self
+    }
+
+    /// Derives a challenge from everything appended so far. The challenge is itself appended, so later challenges differ from it even with the same label.
+    pub fn challenge(&mut self, label: &[u8]) -> HashVal {
+        let mut challenge = [0u8; 32];
+        self.challenge_bytes(label, &mut challenge);
+        HashVal(challenge)
+    }
+
+    /// Like [Transcript::challenge], but fills a buffer of any length with challenge bytes.
+    pub fn challenge_bytes(&mut self, label: &[u8], out: &mut [u8]) {
+        self.absorb(OP_CHALLENGE, label);
+        self.inner.update(&(out.len() as u64).to_le_bytes());
+        self.inner.finalize_xof().fill(out);
+        self.inner.update(out);
+    }
+
+    /// Derives a challenge scalar, uniformly distributed modulo the order of the ed25519 and ristretto groups, for sigma protocols over those groups.
+    pub fn challenge_scalar(&mut self, label: &[u8]) -> Scalar {
+        let mut wide = [0u8; 64];
+        self.challenge_bytes(label, &mut wide);
+        Scalar::from_bytes_mod_order_wide(&wide)
+    }
+
+    /// Appends a scalar, such as a prover's response.
+    pub fn append_scalar(&mut self, label: &[u8], scalar: &Scalar) -> &mut Self {
+        self.append(label, scalar.as_bytes())
+    }
+
+    /// Derives a secret nonce scalar for the prover, bound to the transcript so far and to the prover's `witness`, and mixed with fresh randomness. Binding to the transcript means a nonce is never reused across different statements even if the entropy source fails, while the randomness protects against fault attacks on deterministic nonces. The transcript isn't modified, since the nonce is secret.
+    pub fn witness_scalar(&self, label: &[u8], witness: &[u8]) -> Scalar {
+        let mut rng = [0u8; 32];
+        fill_random(&mut rng);
+        let mut inner = self.inner.clone();
+        inner.update(&[OP_NONCE]);
+        inner.update(&(label.len() as u64).to_le_bytes());
+        inner.update(label);
+        inner.update(&(witness.len() as u64).to_le_bytes());
+        inner.update(witness);
+        inner.update(&rng);
+        let mut wide = [0u8; 64];
+        inner.finalize_xof().fill(&mut wide);
+        let nonce = Scalar::from_bytes_mod_order_wide(&wide);
+        wide.zeroize();
+        rng.zeroize();
+        nonce
+    }
+
+    fn absorb(&mut self, op: u8, label: &[u8]) {
+        self.inner.update(&[op]);
+        self.inner.update(&(label.len() as u64).to_le_bytes());
+        self.inner.update(label);
+    }
+}
+
+impl fmt::Debug for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#<Transcript:{}>",
+            hex::encode(&self.inner.finalize().as_bytes()[..5])
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transcript_binding() {
+        let mut a = Transcript::new(b"test protocol");
+        a.append(b"commitment", b"abc");
+        let mut b = a.clone();
+        assert_eq!(a.challenge(b"c"), b.challenge(b"c"));
+        assert_ne!(a.challenge(b"c"), b.challenge(b"d"));
+
+        let mut x = Transcript::new(b"test protocol");
+        x.append(b"a", b"bc");
+        let mut y = Transcript::new(b"test protocol");
+        y.append(b"ab", b"c");
+        assert_ne!(x.challenge(b"c"), y.challenge(b"c"));
+        let mut z = Transcript::new(b"other protocol");
+        z.append(b"a", b"bc");
+        let mut x = Transcript::new(b"test protocol");
+        x.append(b"a", b"bc");
+        assert_ne!(x.challenge(b"c"), z.challenge(b"c"));
+    }
+
+    #[test]
+    fn schnorr_proof() {
+        // a non-interactive proof of knowledge of a discrete log
+        use curve25519_dalek_ng::constants::RISTRETTO_BASEPOINT_TABLE;
+        let secret = crate::random_scalar();
+        let public = &secret * &RISTRETTO_BASEPOINT_TABLE;
+
+        let mut prover = Transcript::new(b"test dlog");
+        prover.append(b"public", public.compress().as_bytes());
+        let nonce = prover.witness_scalar(b"nonce", secret.as_bytes());
+        let commitment = &nonce * &RISTRETTO_BASEPOINT_TABLE;
+        prover.append(b"commitment", commitment.compress().as_bytes());
+        let response = nonce + prover.challenge_scalar(b"c") * secret;
+
+        let mut verifier = Transcript::new(b"test dlog");
+        verifier.append(b"public", public.compress().as_bytes());
+        verifier.append(b"commitment", commitment.compress().as_bytes());
+        let c = verifier.challenge_scalar(b"c");
+        assert_eq!(
+            &response * &RISTRETTO_BASEPOINT_TABLE,
+            commitment + c * public
+        );
+        assert_ne!(verifier.challenge_scalar(b"c"), c);
+    }
+}
@@ -0,0 +1,547 @@
+use std::fmt::Display;
+
+use serde::{ser, Deserialize, Serialize};
+
+use crate::{hash_keyed, HashVal};
+
+/// Computes the Merkleized "tree hash" of any serializable value.
+///
+/// Unlike hashing the whole stdcode encoding, the tree hash allows proving the value of any single field (see [tree_proof]) without revealing the rest of the structure. It's computed as follows:
+/// - Primitives (integers, strings, byte strings, etc) are leaves, hashed as `hash_keyed("tmelcrypt-tree-leaf", stdcode(val))`. Values are serialized in their compact, non-human-readable form, so for example a [HashVal] is hashed as its 32 bytes rather than as a hex string.
+/// - `()` and unit structs hash to `hash_keyed("tmelcrypt-tree-unit", "")`, and `None` to `hash_keyed("tmelcrypt-tree-none", "")`, so that neither is confused with an empty container.
+/// - Everything else is a container of children: structs and tuples contain their fields, sequences contain their elements, maps contain `(key, value)` tuples, enums contain the variant index followed by the fields of the variant, and `Some(x)` contains `x`. Newtypes are transparent.
+/// - The children's hashes are padded with zero hashes to a power of two and Merkleized into a binary tree, whose interior nodes are `hash_keyed("tmelcrypt-tree-node", left || right)`. The container's hash is `hash_keyed("tmelcrypt-tree-container", merkle_root || count)`, with the number of children as a little-endian u64.
+///
+/// Fails only if `val`'s [Serialize] implementation fails.
+pub fn tree_hash<T: Serialize + ?Sized>(val: &T) -> Result<HashVal, TreeError> {
+    Ok(build_tree(val)?.hash)
+}
+
+/// Generates a proof that the field at `path` has a certain tree hash within `val`. Each element of the path is either the name of a struct field, or the index of a child in decimal. Returns `Ok(None)` if the path doesn't exist.
+///
+/// Since tree hashing is compositional, the tree hash of the field is simply `tree_hash(&field)`, and the proof can be checked with [TreeProof::verify].
+pub fn tree_proof<T: Serialize + ?Sized>(
+    val: &T,
+    path: &[&str],
+) -> Result<Option<TreeProof>, TreeError> {
+    let root = build_tree(val)?;
+    let mut node = &root;
+    let mut levels = vec![];
+    for elem in path {
+        let index = match node
+            .names
+            .iter()
+            .position(|name| name.as_deref() == Some(elem))
+            .or_else(|| elem.parse().ok())
+        {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        let child = match node.children.get(index) {
+            Some(child) => child,
+            None => return Ok(None),
+        };
+        levels.push(ProofLevel {
+            index: index as u64,
+            count: node.children.len() as u64,
+            siblings: merkle_siblings(node.children.iter().map(|c| c.hash).collect(), index),
+        });
+        node = child;
+    }
+    levels.reverse();
+    Ok(Some(TreeProof { levels }))
+}
+
+/// A proof that some value sits at a certain position within a structure with a given [tree_hash].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeProof {
+    /// One level for every container between the field and the root, innermost first.
+    pub levels: Vec<ProofLevel>,
+}
+
+/// The part of a [TreeProof] that goes through a single container.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofLevel {
+    /// Index of the child we're going through.
+    pub index: u64,
+    /// Number of children in the container.
+    pub count: u64,
+    /// Sibling hashes in the container's Merkle tree, from the bottom up.
+    pub siblings: Vec<HashVal>,
+}
+
+impl TreeProof {
+    /// Checks that the field with tree hash `field` sits within a structure with tree hash `root`, at the position given by the indices in the proof.
+    pub fn verify(&self, root: HashVal, field: HashVal) -> bool {
+        let mut hash = field;
+        for level in self.levels.iter() {
+            // counts above 2^63 have no next power of two, and could never be real anyway
+            let depth = match level.count.checked_next_power_of_two() {
+                Some(padded) => padded.trailing_zeros() as usize,
+                None => return false,
+            };
+            if level.index >= level.count || depth != level.siblings.len() {
+                return false;
+            }
+            let mut index = level.index;
+            for sibling in level.siblings.iter() {
+                hash = if index % 2 == 0 {
+                    node_hash(hash, *sibling)
+                } else {
+                    node_hash(*sibling, hash)
+                };
+                index /= 2;
+            }
+            hash = container_hash(hash, level.count);
+        }
+        hash == root
+    }
+}
+
+fn leaf_hash(bytes: &[u8]) -> HashVal {
+    hash_keyed(b"tmelcrypt-tree-leaf", bytes)
+}
+
+fn node_hash(left: HashVal, right: HashVal) -> HashVal {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(&left.0);
+    buf[32..].copy_from_slice(&right.0);
+    hash_keyed(b"tmelcrypt-tree-node", buf)
+}
+
+fn container_hash(merkle_root: HashVal, count: u64) -> HashVal {
+    let mut buf = [0u8; 40];
+    buf[..32].copy_from_slice(&merkle_root.0);
+    buf[32..].copy_from_slice(&count.to_le_bytes());
+    hash_keyed(b"tmelcrypt-tree-container", buf)
+}
+
+fn pad(mut layer: Vec<HashVal>) -> Vec<HashVal> {
+    layer.resize(layer.len().next_power_of_two(), HashVal::default());
+    layer
+}
+
+fn next_layer(layer: &[HashVal]) -> Vec<HashVal> {
+    layer
+        .chunks_exact(2)
+        .map(|pair| node_hash(pair[0], pair[1]))
+        .collect()
+}
+
+fn merkle_root(hashes: Vec<HashVal>) -> HashVal {
+    if hashes.is_empty() {
+        return HashVal::default();
+    }
+    let mut layer = pad(hashes);
+    while layer.len() > 1 {
+        layer = next_layer(&layer);
+    }
+    layer[0]
+}
+
+fn merkle_siblings(hashes: Vec<HashVal>, mut index: usize) -> Vec<HashVal> {
+    let mut layer = pad(hashes);
+    let mut siblings = vec![];
+    while layer.len() > 1 {
+        siblings.push(layer[index ^ 1]);
+        layer = next_layer(&layer);
+        index /= 2;
+    }
+    siblings
+}
+
+/// A fully hashed tree of a value.
+struct Node {
+    hash: HashVal,
+    children: Vec<Node>,
+    names: Vec<Option<String>>,
+}
+
+impl Node {
+    fn leaf(val: &impl Serialize) -> Result<Self, TreeError> {
+        let bytes = stdcode::serialize(val).map_err(|e| TreeError(e.to_string()))?;
+        Ok(Self::fixed(leaf_hash(&bytes)))
+    }
+
+    /// A node with no children and the given hash.
+    fn fixed(hash: HashVal) -> Self {
+        Self {
+            hash,
+            children: vec![],
+            names: vec![],
+        }
+    }
+
+    fn container(children: Vec<(Option<String>, Node)>) -> Self {
+        let count = children.len() as u64;
+        let (names, children): (Vec<_>, Vec<_>) = children.into_iter().unzip();
+        Self {
+            hash: container_hash(
+                merkle_root(children.iter().map(|c| c.hash).collect()),
+                count,
+            ),
+            children,
+            names,
+        }
+    }
+}
+
+fn build_tree<T: Serialize + ?Sized>(val: &T) -> Result<Node, TreeError> {
+    val.serialize(TreeBuilder)
+}
+
+/// An error from a value's [Serialize] implementation while computing its tree hash.
+#[derive(Debug)]
+pub struct TreeError(String);
+
+impl Display for TreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for TreeError {}
+
+impl ser::Error for TreeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        TreeError(msg.to_string())
+    }
+}
+
+struct TreeBuilder;
+
+/// Accumulates the children of a container.
+struct ContainerBuilder {
+    children: Vec<(Option<String>, Node)>,
+    pending_key: Option<Node>,
+}
+
+impl ContainerBuilder {
+    fn new(variant_index: Option<u32>) -> Result<Self, TreeError> {
+        Ok(Self {
+            children: variant_index
+                .map(|idx| Ok((None, Node::leaf(&idx)?)))
+                .into_iter()
+                .collect::<Result<_, TreeError>>()?,
+            pending_key: None,
+        })
+    }
+
+    fn push<T: Serialize + ?Sized>(
+        &mut self,
+        name: Option<&str>,
+        val: &T,
+    ) -> Result<(), TreeError> {
+        let node = val.serialize(TreeBuilder)?;
+        self.children.push((name.map(|s| s.to_string()), node));
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Node, TreeError> {
+        Ok(Node::container(self.children))
+    }
+}
+
+macro_rules! leaf_methods {
+    ($($method:ident: $ty:ty),*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Node, TreeError> {
+                Node::leaf(&v)
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for TreeBuilder {
+    type Ok = Node;
+    type Error = TreeError;
+    type SerializeSeq = ContainerBuilder;
+    type SerializeTuple = ContainerBuilder;
+    type SerializeTupleStruct = ContainerBuilder;
+    type SerializeTupleVariant = ContainerBuilder;
+    type SerializeMap = ContainerBuilder;
+    type SerializeStruct = ContainerBuilder;
+    type SerializeStructVariant = ContainerBuilder;
+
+    leaf_methods!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_i128: i128,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_u128: u128,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str
+    );
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Node, TreeError> {
+        Node::leaf(&serde_bytes_ref(v))
+    }
+
+    fn serialize_none(self) -> Result<Node, TreeError> {
+        Ok(Node::fixed(hash_keyed(b"tmelcrypt-tree-none", b"")))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Node, TreeError> {
+        Ok(Node::container(vec![(None, value.serialize(TreeBuilder)?)]))
+    }
+
+    fn serialize_unit(self) -> Result<Node, TreeError> {
+        Ok(Node::fixed(hash_keyed(b"tmelcrypt-tree-unit", b"")))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Node, TreeError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Node, TreeError> {
+        ContainerBuilder::new(Some(variant_index))?.finish()
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Node, TreeError> {
+        value.serialize(TreeBuilder)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<Node, TreeError> {
+        let mut builder = ContainerBuilder::new(Some(variant_index))?;
+        builder.push(None, value)?;
+        builder.finish()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<ContainerBuilder, TreeError> {
+        ContainerBuilder::new(None)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<ContainerBuilder, TreeError> {
+        ContainerBuilder::new(None)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<ContainerBuilder, TreeError> {
+        ContainerBuilder::new(None)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<ContainerBuilder, TreeError> {
+        ContainerBuilder::new(Some(variant_index))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<ContainerBuilder, TreeError> {
+        ContainerBuilder::new(None)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<ContainerBuilder, TreeError> {
+        ContainerBuilder::new(None)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<ContainerBuilder, TreeError> {
+        ContainerBuilder::new(Some(variant_index))
+    }
+}
+
+/// Byte strings are hashed with the same encoding as a `Vec<u8>`.
+fn serde_bytes_ref(v: &[u8]) -> impl Serialize + '_ {
+    struct Bytes<'a>(&'a [u8]);
+    impl Serialize for Bytes<'_> {
+        fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+    Bytes(v)
+}
+
+impl ser::SerializeSeq for ContainerBuilder {
+    type Ok = Node;
+    type Error = TreeError;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), TreeError> {
+        self.push(None, value)
+    }
+    fn end(self) -> Result<Node, TreeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for ContainerBuilder {
+    type Ok = Node;
+    type Error = TreeError;
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), TreeError> {
+        self.push(None, value)
+    }
+    fn end(self) -> Result<Node, TreeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for ContainerBuilder {
+    type Ok = Node;
+    type Error = TreeError;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), TreeError> {
+        self.push(None, value)
+    }
+    fn end(self) -> Result<Node, TreeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for ContainerBuilder {
+    type Ok = Node;
+    type Error = TreeError;
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), TreeError> {
+        self.push(None, value)
+    }
+    fn end(self) -> Result<Node, TreeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for ContainerBuilder {
+    type Ok = Node;
+    type Error = TreeError;
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), TreeError> {
+        self.pending_key = Some(key.serialize(TreeBuilder)?);
+        Ok(())
+    }
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), TreeError> {
+        let key = self
+            .pending_key
+            .take()
+            .ok_or_else(|| TreeError("map value without a key".into()))?;
+        let entry = Node::container(vec![(None, key), (None, value.serialize(TreeBuilder)?)]);
+        self.children.push((None, entry));
+        Ok(())
+    }
+    fn end(self) -> Result<Node, TreeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for ContainerBuilder {
+    type Ok = Node;
+    type Error = TreeError;
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), TreeError> {
+        self.push(Some(key), value)
+    }
+    fn end(self) -> Result<Node, TreeError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for ContainerBuilder {
+    type Ok = Node;
+    type Error = TreeError;
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), TreeError> {
+        self.push(Some(key), value)
+    }
+    fn end(self) -> Result<Node, TreeError> {
+        self.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Header {
+        height: u64,
+        prev: HashVal,
+        txx: Vec<(String, u32)>,
+    }
+
+    #[test]
+    fn prove_fields() {
+        let header = Header {
+            height: 42,
+            prev: HashVal([1; 32]),
+            txx: vec![("a".into(), 1), ("b".into(), 2), ("c".into(), 3)],
+        };
+        let root = tree_hash(&header).unwrap();
+        let proof = tree_proof(&header, &["height"]).unwrap().unwrap();
+        assert!(proof.verify(root, tree_hash(&42u64).unwrap()));
+        assert!(!proof.verify(root, tree_hash(&43u64).unwrap()));
+        let proof = tree_proof(&header, &["txx", "2", "1"]).unwrap().unwrap();
+        assert!(proof.verify(root, tree_hash(&3u32).unwrap()));
+        let proof = tree_proof(&header, &["txx", "1"]).unwrap().unwrap();
+        assert!(proof.verify(root, tree_hash(&("b".to_string(), 2u32)).unwrap()));
+        assert!(tree_proof(&header, &["nonexistent"]).unwrap().is_none());
+        assert!(tree_proof(&header, &["txx", "3"]).unwrap().is_none());
+
+        // an untrusted count too large to pad is rejected rather than overflowing
+        let mut bad = tree_proof(&header, &["height"]).unwrap().unwrap();
+        bad.levels[0].count = u64::MAX;
+        assert!(!bad.verify(root, tree_hash(&42u64).unwrap()));
+    }
+
+    #[test]
+    fn distinct_empty_values() {
+        let unit = tree_hash(&()).unwrap();
+        let none = tree_hash(&None::<u8>).unwrap();
+        let empty_seq = tree_hash(&Vec::<u32>::new()).unwrap();
+        assert_ne!(unit, none);
+        assert_ne!(unit, empty_seq);
+        assert_ne!(none, empty_seq);
+    }
+
+    #[test]
+    fn compact_form() {
+        // hashvals are hashed as their raw bytes, not as hex strings
+        let hash = HashVal([7; 32]);
+        assert_eq!(tree_hash(&hash).unwrap(), tree_hash(&hash.0).unwrap());
+        assert_ne!(
+            tree_hash(&hash).unwrap(),
+            tree_hash(&hash.to_string()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! An ECVRF verifiable random function over ed25519 keys, following the ECVRF-EDWARDS25519-SHA512-TAI suite of RFC 9381.
+//!
+//! The holder of a [VrfSK] can compute a pseudorandom [VrfOutput] for any input, together with a [VrfProof] that anyone with the [VrfPK] can check. Nobody else can predict the output, and the key holder can't choose it: there is exactly one valid output per key and input. This makes it a good source of per-participant randomness in protocols such as [crate::majority_beacon].
+
+use std::fmt;
+
+use arrayref::array_ref;
+use curve25519_dalek_ng::{
+    constants::ED25519_BASEPOINT_TABLE,
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+use crate::{BigArray, Ed25519PK, Ed25519SK};
+
+const SUITE: u8 = 0x03;
+
+/// A VRF secret key, which is just an ed25519 secret key.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VrfSK(pub Ed25519SK);
+
+/// A VRF public key, which is just an ed25519 public key.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VrfPK(pub Ed25519PK);
+
+/// The 64-byte pseudorandom output of the VRF.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VrfOutput(#[serde(with = "BigArray")] pub [u8; 64]);
+
+/// An 80-byte proof that a [VrfOutput] is correct.
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VrfProof(#[serde(with = "BigArray")] pub [u8; 80]);
+
+impl VrfSK {
+    pub fn generate() -> Self {
+        Self(Ed25519SK::generate())
+    }
+
+    pub fn to_public(&self) -> VrfPK {
+        VrfPK(self.0.to_public())
+    }
+
+    /// Computes the VRF output for `alpha`, along with a proof of its correctness.
+    pub fn prove(&self, alpha: &[u8]) -> (VrfOutput, VrfProof) {
+        let pk = self.0.to_public();
+        let x = self.0.signing_scalar();
+        let h = encode_to_curve(&pk.0, alpha)
+            .expect("hash to curve failed 256 times in a row, which has probability 2^-256");
+        let h_string = h.compress();
+        let gamma = x * h;
+        let nonce_hash = Sha512::digest(&self.0 .0[..32]);
+        let k = scalar_from_hash(
+            Sha512::new()
+                .chain_update(&nonce_hash[32..])
+                .chain_update(h_string.as_bytes()),
+        );
+        let c = challenge(
+            &pk.0,
+            &h_string,
+            &gamma.compress(),
+            &(&k * &ED25519_BASEPOINT_TABLE),
+            &(k * h),
+        );
+        let s = k + c * x;
+        let mut proof = [0u8; 80];
+        proof[..32].copy_from_slice(gamma.compress().as_bytes());
+        proof[32..48].copy_from_slice(&c.as_bytes()[..16]);
+        proof[48..].copy_from_slice(s.as_bytes());
+        (proof_to_output(&gamma), VrfProof(proof))
+    }
+}
+
+impl VrfPK {
+    /// Verifies a proof for `alpha`, returning the VRF output if it is valid.
+    pub fn verify(&self, alpha: &[u8], proof: &VrfProof) -> Option<VrfOutput> {
+        let y = CompressedEdwardsY(self.0 .0).decompress()?;
+        if y.is_small_order() {
+            return None;
+        }
+        let gamma = CompressedEdwardsY(*array_ref![proof.0, 0, 32]).decompress()?;
+        let mut c_bytes = [0u8; 32];
+        c_bytes[..16].copy_from_slice(&proof.0[32..48]);
+        let c = Scalar::from_bits(c_bytes);
+        let s = Scalar::from_canonical_bytes(*array_ref![proof.0, 48, 32])?;
+        let h = encode_to_curve(&self.0 .0, alpha)?;
+        let u = &s * &ED25519_BASEPOINT_TABLE - c * y;
+        let v = s * h - c * gamma;
+        let expected = challenge(&self.0 .0, &h.compress(), &gamma.compress(), &u, &v);
+        if expected.as_bytes()[..16] != c_bytes[..16] {
+            return None;
+        }
+        Some(proof_to_output(&gamma))
+    }
+}
+
+impl VrfProof {
+    /// The output that this proof claims, without checking the proof. Only use this if the proof has already been verified.
+    pub fn to_output_unchecked(&self) -> Option<VrfOutput> {
+        let gamma = CompressedEdwardsY(*array_ref![self.0, 0, 32]).decompress()?;
+        Some(proof_to_output(&gamma))
+    }
+}
+
+/// The try-and-increment hash to curve of RFC 9381, section 5.4.1.1.
+fn encode_to_curve(pk: &[u8; 32], alpha: &[u8]) -> Option<EdwardsPoint> {
+    (0..=255u8).find_map(|ctr| {
+        let hash = Sha512::new()
+            .chain_update([SUITE, 0x01])
+            .chain_update(pk)
+            .chain_update(alpha)
+            .chain_update([ctr, 0x00])
+            .finalize();
+        CompressedEdwardsY(*array_ref![hash, 0, 32])
+            .decompress()
+            .map(|p| p.mul_by_cofactor())
+    })
+}
+
+/// The challenge of RFC 9381, section 5.4.3, as a scalar whose top 16 bytes are zero.
+fn challenge(
+    pk: &[u8; 32],
+    h: &CompressedEdwardsY,
+    gamma: &CompressedEdwardsY,
+    u: &EdwardsPoint,
+    v: &EdwardsPoint,
+) -> Scalar {
+    let hash = Sha512::new()
+        .chain_update([SUITE, 0x02])
+        .chain_update(pk)
+        .chain_update(h.as_bytes())
+        .chain_update(gamma.as_bytes())
+        .chain_update(u.compress().as_bytes())
+        .chain_update(v.compress().as_bytes())
+        .chain_update([0x00])
+        .finalize();
+    let mut c = [0u8; 32];
+    c[..16].copy_from_slice(&hash[..16]);
+    Scalar::from_bits(c)
+}
+
+fn proof_to_output(gamma: &EdwardsPoint) -> VrfOutput {
+    let hash = Sha512::new()
+        .chain_update([SUITE, 0x03])
+        .chain_update(gamma.mul_by_cofactor().compress().as_bytes())
+        .chain_update([0x00])
+        .finalize();
+    VrfOutput(hash.into())
+}
+
+fn scalar_from_hash(hasher: Sha512) -> Scalar {
+    let hash: [u8; 64] = hasher.finalize().into();
+    Scalar::from_bytes_mod_order_wide(&hash)
+}
+
+impl From<Ed25519SK> for VrfSK {
+    fn from(sk: Ed25519SK) -> Self {
+        Self(sk)
+    }
+}
+
+impl From<Ed25519PK> for VrfPK {
+    fn from(pk: Ed25519PK) -> Self {
+        Self(pk)
+    }
+}
+
+impl fmt::Debug for VrfSK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VrfSK({:?})", self.to_public())
+    }
+}
+
+impl fmt::Debug for VrfPK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#<VrfPK:{}>", hex::encode(&self.0 .0[..5]))
+    }
+}
+
+impl fmt::Debug for VrfOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#<VrfOutput:{}>", hex::encode(&self.0[..5]))
+    }
+}
+
+impl fmt::Debug for VrfProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#<VrfProof:{}>", hex::encode(&self.0[..5]))
+    }
+}
+
+impl fmt::Display for VrfOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::encode(self.0).fmt(f)
+    }
+}
+
+impl fmt::Display for VrfProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::encode(self.0).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    #[test]
+    fn ecvrf_rfc9381() {
+        // example 16 from RFC 9381
+        let seed: [u8; 32] =
+            hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let sk = VrfSK(Ed25519SK::from_seed(seed));
+        let (output, proof) = sk.prove(b"");
+        assert_eq!(proof.to_string(), "8657106690b5526245a92b003bb079ccd1a92130477671f6fc01ad16f26f723f26f8a57ccaed74ee1b190bed1f479d9727d2d0f9b005a6e456a35d4fb0daab1268a1b0db10836d9826a528ca76567805");
+        assert_eq!(output.to_string(), "90cf1df3b703cce59e2a35b925d411164068269d7b2d29f3301c03dd757876ff66b71dda49d2de59d03450451af026798e8f81cd2e333de5cdf4f3e140fdd8ae");
+        assert_eq!(sk.to_public().verify(b"", &proof), Some(output));
+        assert_eq!(sk.to_public().verify(b"x", &proof), None);
+    }
+}
@@ -0,0 +1,67 @@
+//! JavaScript bindings for the core operations, so that browser wallets can share this crate's exact key, signature, hash, and address formats instead of reimplementing them.
+//!
+//! Byte strings cross the boundary as `Uint8Array`s, and errors are thrown as JavaScript `Error`s.
+
+use std::fmt::Display;
+
+use wasm_bindgen::prelude::*;
+
+use crate::bindings;
+
+fn js_err(e: impl Display) -> JsError {
+    JsError::new(&e.to_string())
+}
+
+/// Generates a new random 64-byte secret key.
+#[wasm_bindgen(js_name = generateSecretKey)]
+pub fn generate_secret_key() -> Vec<u8> {
+    bindings::generate_secret_key()
+}
+
+/// Returns the 32-byte public key of a secret key.
+#[wasm_bindgen(js_name = publicKey)]
+pub fn public_key(sk: &[u8]) -> Result<Vec<u8>, JsError> {
+    bindings::public_key(sk).map_err(js_err)
+}
+
+/// Signs a message, returning a 64-byte signature.
+#[wasm_bindgen]
+pub fn sign(sk: &[u8], msg: &[u8]) -> Result<Vec<u8>, JsError> {
+    bindings::sign(sk, msg).map_err(js_err)
+}
+
+/// Verifies a signature. Malformed keys and signatures are simply invalid.
+#[wasm_bindgen]
+pub fn verify(pk: &[u8], msg: &[u8], sig: &[u8]) -> bool {
+    bindings::verify(pk, msg, sig)
+}
+
+/// Hashes a value, like [crate::hash_single].
+#[wasm_bindgen]
+pub fn hash(data: &[u8]) -> Vec<u8> {
+    bindings::hash(data)
+}
+
+/// Hashes a value with a key, like [crate::hash_keyed].
+#[wasm_bindgen(js_name = hashKeyed)]
+pub fn hash_keyed(key: &[u8], data: &[u8]) -> Vec<u8> {
+    bindings::hash_keyed(key, data)
+}
+
+/// The `t...` address of the standard covenant locked by a public key.
+#[wasm_bindgen(js_name = addressFromPublicKey)]
+pub fn address_from_public_key(pk: &[u8]) -> Result<String, JsError> {
+    bindings::address_from_public_key(pk).map_err(js_err)
+}
+
+/// Formats a 32-byte covenant hash as a `t...` address.
+#[wasm_bindgen(js_name = hashToAddress)]
+pub fn hash_to_address(hash: &[u8]) -> Result<String, JsError> {
+    bindings::hash_to_address(hash).map_err(js_err)
+}
+
+/// Parses a `t...` address into its 32-byte covenant hash, checking the checksum.
+#[wasm_bindgen(js_name = addressToHash)]
+pub fn address_to_hash(addr: &str) -> Result<Vec<u8>, JsError> {
+    bindings::address_to_hash(addr).map_err(js_err)
+}
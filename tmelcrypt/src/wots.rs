@@ -0,0 +1,200 @@
+//! Winternitz one-time signatures (WOTS+), built only on blake3.
+//!
+//! Unlike ed25519, their security rests only on the hash function, so they are believed to resist quantum computers. This makes them a conservative choice for long-term archival commitments.
+//!
+//! **Each key may sign only one message.** Two signatures by the same key let anyone forge signatures on other messages. To make this hard to get wrong, [WotsSK::sign] consumes the secret key. Signers who need many keys should derive them from a master seed with [WotsSK::derive], never reusing an index.
+//!
+//! The parameters are `n = 32` and `w = 16`: a message hash is signed as 64 base-16 digits plus a 3-digit checksum, so signatures are 67 × 32 bytes. Public keys are compressed to a 32-byte public seed and a 32-byte root.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::{fill_random, hash_keyed, HashVal};
+
+const MSG_DIGITS: usize = 64;
+const CHECKSUM_DIGITS: usize = 3;
+const CHAINS: usize = MSG_DIGITS + CHECKSUM_DIGITS;
+const W: u8 = 16;
+
+/// A WOTS+ secret key. Zeroed out on drop, and deliberately not [Copy] or [Clone], since it must only ever sign once.
+#[derive(Serialize, Deserialize)]
+pub struct WotsSK {
+    #[serde(with = "stdcode::hex32")]
+    seed: [u8; 32],
+}
+
+/// A WOTS+ public key.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WotsPK {
+    #[serde(with = "stdcode::hex32")]
+    pub public_seed: [u8; 32],
+    pub root: HashVal,
+}
+
+/// A WOTS+ signature: one hash chain element for each of the 67 digits.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WotsSig(pub Vec<HashVal>);
+
+impl WotsSK {
+    /// Generates a new random key.
+    pub fn generate() -> Self {
+        let mut seed = [0u8; 32];
+        fill_random(&mut seed);
+        Self { seed }
+    }
+
+    /// Deterministically derives the `index`th one-time key from a master seed.
+    pub fn derive(master: &[u8; 32], index: u64) -> Self {
+        let mut buf = master.to_vec();
+        buf.extend_from_slice(&index.to_le_bytes());
+        let key = Self {
+            seed: hash_keyed(b"tmelcrypt-wots-derive", &buf).0,
+        };
+        buf.zeroize();
+        key
+    }
+
+    /// The public key, which is published before signing.
+    pub fn to_public(&self) -> WotsPK {
+        let public_seed = self.public_seed();
+        let ends: Vec<[u8; 32]> = (0..CHAINS)
+            .map(|i| chain(&public_seed, i, 0, W - 1, self.chain_secret(i)))
+            .collect();
+        WotsPK {
+            public_seed,
+            root: compress(&public_seed, &ends),
+        }
+    }
+
+    /// Signs a message, consuming the key so that it can't sign again.
+    pub fn sign(self, msg: &[u8]) -> WotsSig {
+        let pk = self.to_public();
+        WotsSig(
+            digits(&pk, msg)
+                .iter()
+                .enumerate()
+                .map(|(i, d)| HashVal(chain(&pk.public_seed, i, 0, *d, self.chain_secret(i))))
+                .collect(),
+        )
+    }
+
+    fn public_seed(&self) -> [u8; 32] {
+        hash_keyed(b"tmelcrypt-wots-public-seed", self.seed).0
+    }
+
+    fn chain_secret(&self, i: usize) -> [u8; 32] {
+        let mut buf = self.seed.to_vec();
+        buf.extend_from_slice(&(i as u32).to_le_bytes());
+        let secret = hash_keyed(b"tmelcrypt-wots-chain-secret", &buf).0;
+        buf.zeroize();
+        secret
+    }
+}
+
+impl WotsPK {
+    /// Checks a signature on a message.
+    pub fn verify(&self, msg: &[u8], sig: &WotsSig) -> bool {
+        if sig.0.len() != CHAINS {
+            return false;
+        }
+        let ends: Vec<[u8; 32]> = digits(self, msg)
+            .iter()
+            .zip(sig.0.iter())
+            .enumerate()
+            .map(|(i, (d, s))| chain(&self.public_seed, i, *d, W - 1 - d, s.0))
+            .collect();
+        compress(&self.public_seed, &ends) == self.root
+    }
+}
+
+/// Applies `steps` steps of the `i`th hash chain to `x`, which is at position `start`.
+fn chain(public_seed: &[u8; 32], i: usize, start: u8, steps: u8, mut x: [u8; 32]) -> [u8; 32] {
+    for pos in start..start + steps {
+        let mut buf = [0u8; 35];
+        buf[0] = 0x00;
+        buf[1] = i as u8;
+        buf[2] = pos;
+        buf[3..].copy_from_slice(&x);
+        x = *blake3::keyed_hash(public_seed, &buf).as_bytes();
+    }
+    x
+}
+
+fn compress(public_seed: &[u8; 32], ends: &[[u8; 32]]) -> HashVal {
+    let mut hasher = blake3::Hasher::new_keyed(public_seed);
+    hasher.update(&[0x01]);
+    for end in ends {
+        hasher.update(end);
+    }
+    HashVal(*hasher.finalize().as_bytes())
+}
+
+/// The base-16 digits of the message hash, followed by the checksum digits.
+fn digits(pk: &WotsPK, msg: &[u8]) -> [u8; CHAINS] {
+    let mut hasher = blake3::Hasher::new_keyed(&pk.public_seed);
+    hasher.update(&[0x02]);
+    hasher.update(&pk.root.0);
+    hasher.update(msg);
+    let hash = hasher.finalize();
+
+    let mut toret = [0u8; CHAINS];
+    for (i, byte) in hash.as_bytes().iter().enumerate() {
+        toret[2 * i] = byte >> 4;
+        toret[2 * i + 1] = byte & 0x0f;
+    }
+    let checksum: u32 = toret[..MSG_DIGITS]
+        .iter()
+        .map(|d| u32::from(W - 1 - d))
+        .sum();
+    for j in 0..CHECKSUM_DIGITS {
+        toret[MSG_DIGITS + j] = ((checksum >> (4 * (CHECKSUM_DIGITS - 1 - j))) & 0x0f) as u8;
+    }
+    toret
+}
+
+impl Drop for WotsSK {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+    }
+}
+
+impl fmt::Debug for WotsSK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WotsSK({:?})", self.to_public())
+    }
+}
+
+impl fmt::Debug for WotsPK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#<WotsPK:{}>", hex::encode(&self.root.0[..5]))
+    }
+}
+
+impl fmt::Debug for WotsSig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#<WotsSig:{} chains>", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wots_roundtrip() {
+        let sk = WotsSK::derive(&[7; 32], 0);
+        let pk = sk.to_public();
+        assert_eq!(pk, WotsSK::derive(&[7; 32], 0).to_public());
+        assert_ne!(pk, WotsSK::derive(&[7; 32], 1).to_public());
+
+        let sig = sk.sign(b"archive root");
+        assert_eq!(sig.0.len(), 67);
+        assert!(pk.verify(b"archive root", &sig));
+        assert!(!pk.verify(b"other root", &sig));
+        let sig: WotsSig = stdcode::deserialize(&stdcode::serialize(&sig).unwrap()).unwrap();
+        assert!(pk.verify(b"archive root", &sig));
+        assert!(!WotsSK::generate().to_public().verify(b"archive root", &sig));
+    }
+}
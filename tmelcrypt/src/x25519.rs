@@ -0,0 +1,194 @@
+//! X25519 key agreement, for establishing shared secrets between peers.
+//!
+//! Ed25519 keys can be converted to X25519 keys with [Ed25519SK::to_x25519] and [Ed25519PK::to_x25519], using the birational map between the two curves, so one identity key can serve for both signing and key agreement.
+
+use std::{convert::TryInto, fmt, str::FromStr};
+
+use arrayref::array_ref;
+use curve25519_dalek_ng::{
+    constants::X25519_BASEPOINT, edwards::CompressedEdwardsY, montgomery::MontgomeryPoint,
+    scalar::Scalar,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use zeroize::Zeroize;
+
+use crate::{fill_random, Ed25519PK, Ed25519SK};
+
+/// An X25519 public key.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct X25519PK(#[serde(with = "stdcode::hex32")] pub [u8; 32]);
+
+/// An X25519 secret key. Like [crate::Ed25519SK], it's zeroed out on drop.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct X25519SK(#[serde(with = "stdcode::hex32")] pub [u8; 32]);
+
+impl X25519SK {
+    /// Generates a new random secret key, e.g. an ephemeral key for a single exchange.
+    pub fn generate() -> Self {
+        let mut sk = [0u8; 32];
+        fill_random(&mut sk);
+        Self(sk)
+    }
+
+    pub fn to_public(&self) -> X25519PK {
+        X25519PK((self.scalar() * X25519_BASEPOINT).to_bytes())
+    }
+
+    /// Computes the shared secret with a peer's public key. Returns `None` if the result is all zeros, which happens exactly when the peer's key is a low-order point and the "shared" secret would be known to everybody.
+    ///
+    /// The result is not uniformly random, so it should be hashed (e.g. with [crate::hash_keyed]) before use as a key.
+    pub fn diffie_hellman(&self, their_pk: &X25519PK) -> Option<[u8; 32]> {
+        let shared = (self.scalar() * MontgomeryPoint(their_pk.0)).to_bytes();
+        if shared == [0u8; 32] {
+            None
+        } else {
+            Some(shared)
+        }
+    }
+
+    /// The whole secret key in hexadecimal, for intentionally exporting it. [Display](fmt::Display) and [Debug](fmt::Debug) only show part of the public key.
+    pub fn reveal_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// The clamped scalar, as in RFC 7748.
+    fn scalar(&self) -> Scalar {
+        let mut bits = self.0;
+        bits[0] &= 248;
+        bits[31] &= 127;
+        bits[31] |= 64;
+        Scalar::from_bits(bits)
+    }
+}
+
+impl Ed25519SK {
+    /// The X25519 secret key with the same scalar as this key, as in libsodium's `crypto_sign_ed25519_sk_to_curve25519`. Its public key is [Ed25519PK::to_x25519] of this key's public key.
+    pub fn to_x25519(&self) -> X25519SK {
+        let mut expanded: [u8; 64] = Sha512::digest(&self.0[..32]).into();
+        let sk = X25519SK(*array_ref![expanded, 0, 32]);
+        expanded.zeroize();
+        sk
+    }
+}
+
+impl Ed25519PK {
+    /// The X25519 public key for the same point, mapped from the Edwards to the Montgomery form of the curve. Returns `None` if this isn't a valid ed25519 public key.
+    pub fn to_x25519(&self) -> Option<X25519PK> {
+        let point = CompressedEdwardsY(self.0).decompress()?;
+        Some(X25519PK(point.to_montgomery().to_bytes()))
+    }
+}
+
+impl Drop for X25519SK {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl PartialEq for X25519SK {
+    fn eq(&self, other: &Self) -> bool {
+        crate::ct_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for X25519SK {}
+
+impl FromStr for X25519PK {
+    type Err = hex::FromHexError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let vv = hex::decode(s)?;
+        Ok(X25519PK(
+            vv.try_into()
+                .map_err(|_| hex::FromHexError::InvalidStringLength)?,
+        ))
+    }
+}
+
+impl FromStr for X25519SK {
+    type Err = hex::FromHexError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let vv = match crate::ct_hex_decode(s) {
+            Some(vv) => vv,
+            None => return Err(hex::decode(s).unwrap_err()),
+        };
+        Ok(X25519SK(
+            vv.try_into()
+                .map_err(|_| hex::FromHexError::InvalidStringLength)?,
+        ))
+    }
+}
+
+impl fmt::Display for X25519PK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        hex::encode(self.0).fmt(f)
+    }
+}
+
+impl fmt::Display for X25519SK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<redacted:{}>", hex::encode(&self.to_public().0[..5]))
+    }
+}
+
+impl fmt::Debug for X25519PK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("#<XPK:{}>", hex::encode(&self.0[..5])))
+    }
+}
+
+impl fmt::Debug for X25519SK {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("XSK({})", self.to_public()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc7748_vector() {
+        let alice: X25519SK = "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a"
+            .parse()
+            .unwrap();
+        let bob: X25519SK = "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            alice.to_public().to_string(),
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"
+        );
+        let shared = alice.diffie_hellman(&bob.to_public()).unwrap();
+        assert_eq!(Some(shared), bob.diffie_hellman(&alice.to_public()));
+        assert_eq!(
+            hex::encode(shared),
+            "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742"
+        );
+        assert_eq!(alice.diffie_hellman(&X25519PK([0; 32])), None);
+    }
+
+    #[test]
+    fn secret_key_redacted() {
+        let sk = X25519SK::generate();
+        assert_eq!(sk.reveal_hex().parse::<X25519SK>().unwrap(), sk);
+        let secret = hex::encode(sk.0);
+        assert!(!sk.to_string().contains(&secret[..10]));
+        assert!(!format!("{:?}", sk).contains(&secret[..10]));
+    }
+
+    #[test]
+    fn from_ed25519() {
+        let alice = Ed25519SK::generate();
+        let bob = Ed25519SK::generate();
+        let alice_x = alice.to_x25519();
+        assert_eq!(Some(alice_x.to_public()), alice.to_public().to_x25519());
+        assert_eq!(
+            alice_x.diffie_hellman(&bob.to_public().to_x25519().unwrap()),
+            bob.to_x25519()
+                .diffie_hellman(&alice.to_public().to_x25519().unwrap())
+        );
+    }
+}
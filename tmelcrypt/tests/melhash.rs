@@ -0,0 +1,80 @@
+#![cfg(feature = "derive")]
+
+use tmelcrypt::{HashVal, MelHash, MelHasher};
+
+#[derive(MelHash)]
+struct Header {
+    height: u64,
+    previous: HashVal,
+    extra: Option<Vec<u8>>,
+}
+
+#[derive(MelHash)]
+#[mel_hash(domain = "Header/v2")]
+struct HeaderV2 {
+    height: u64,
+    previous: HashVal,
+    extra: Option<Vec<u8>>,
+}
+
+#[derive(MelHash)]
+struct Vote {
+    round: u8,
+    index: usize,
+    payload: Vec<u8>,
+}
+
+#[derive(MelHash)]
+enum Message<T> {
+    Ping,
+    Data(T, bool),
+}
+
+#[test]
+fn derived_matches_manual() {
+    let hdr = Header {
+        height: 10,
+        previous: HashVal([1; 32]),
+        extra: None,
+    };
+    let manual = MelHasher::new("Header")
+        .field(&hdr.height)
+        .field(&hdr.previous)
+        .field(&hdr.extra)
+        .finish();
+    assert_eq!(hdr.mel_hash(), manual);
+
+    let hdr2 = HeaderV2 {
+        height: 10,
+        previous: HashVal([1; 32]),
+        extra: None,
+    };
+    assert_ne!(hdr.mel_hash(), hdr2.mel_hash());
+}
+
+#[test]
+fn enum_variants_separated() {
+    let ping: Message<u32> = Message::Ping;
+    assert_eq!(ping.mel_hash(), MelHasher::new("Message::Ping").finish());
+    assert_ne!(
+        Message::Data(1u32, true).mel_hash(),
+        Message::Data(1u32, false).mel_hash()
+    );
+}
+
+#[test]
+fn small_integers() {
+    let vote = Vote {
+        round: 3,
+        index: 7,
+        payload: vec![1, 2, 3],
+    };
+    let manual = MelHasher::new("Vote")
+        .field(&3u8)
+        .field(&7u64)
+        .raw(&MelHasher::new("bytes").raw(&[1, 2, 3]).finish().0)
+        .finish();
+    assert_eq!(vote.mel_hash(), manual);
+    assert_eq!(7usize.mel_hash(), 7u64.mel_hash());
+    assert_ne!(3u8.mel_hash(), 3u16.mel_hash());
+}